@@ -0,0 +1,104 @@
+use super::component::ComponentType;
+use super::error::NetworkError;
+
+/// Template of a bay; a set of components and internal nodes with named external terminals, which can be added to a network any number of times, see [crate::network::Network::add_bay]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bay {
+    name: String,
+    nodes: Vec<String>,
+    terminals: Vec<String>,
+    components: Vec<(String, ComponentType, Vec<String>)>,
+}
+
+impl Bay {
+    /// Constructor, sets the bay name
+    pub fn new(name: &str) -> Bay {
+        Bay {
+            name: name.to_string(),
+            nodes: vec![],
+            terminals: vec![],
+            components: vec![],
+        }
+    }
+
+    /// Get bay name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Add an internal node, created for every instance of the bay
+    pub fn add_node(&mut self, name: &str) -> Result<(), NetworkError> {
+        self.check_name(name)?;
+        self.nodes.push(name.to_string());
+        Ok(())
+    }
+
+    /// Add an external terminal, connected to an existing network node when the bay is added to a network
+    pub fn add_terminal(&mut self, name: &str) -> Result<(), NetworkError> {
+        self.check_name(name)?;
+        self.terminals.push(name.to_string());
+        Ok(())
+    }
+
+    /// Add a component of the given type, connecting its terminals in order to the given internal nodes or external terminals. Trailing terminals may be left unconnected. The terminal count of a custom type is only known to the network it is registered with, so it is checked when the bay is added, see [crate::network::Network::add_bay].
+    pub fn add_component(
+        &mut self,
+        name: &str,
+        r#type: ComponentType,
+        nodes: &[&str],
+    ) -> Result<(), NetworkError> {
+        self.check_name(name)?;
+        if !matches!(r#type, ComponentType::Custom(_)) && nodes.len() > r#type.terminal_count() {
+            return Err(NetworkError::Unsupported(format!(
+                "Components of type {} have {} terminals, {} nodes given",
+                r#type,
+                r#type.terminal_count(),
+                nodes.len()
+            )));
+        }
+        if let Some(node) = nodes
+            .iter()
+            .find(|n| !self.nodes.iter().chain(&self.terminals).any(|x| x == *n))
+        {
+            return Err(NetworkError::NotFound(format!(
+                "No node or terminal with name {} exists in bay {}",
+                node, self.name
+            )));
+        }
+        self.components.push((
+            name.to_string(),
+            r#type,
+            nodes.iter().map(|n| n.to_string()).collect(),
+        ));
+        Ok(())
+    }
+
+    /// Return the names of the internal nodes
+    pub fn nodes(&self) -> &Vec<String> {
+        &self.nodes
+    }
+
+    /// Return the names of the external terminals
+    pub fn terminals(&self) -> &Vec<String> {
+        &self.terminals
+    }
+
+    /// Return the name, type and connected internal nodes or external terminals of every component
+    pub fn components(&self) -> &Vec<(String, ComponentType, Vec<String>)> {
+        &self.components
+    }
+
+    /// Return [NetworkError::DuplicateName] if the name is already used in the bay
+    fn check_name(&self, name: &str) -> Result<(), NetworkError> {
+        let used = self
+            .nodes
+            .iter()
+            .chain(&self.terminals)
+            .chain(self.components.iter().map(|(n, _, _)| n))
+            .any(|x| x == name);
+        match used {
+            true => Err(NetworkError::DuplicateName(name.to_string())),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+use super::position::PositionState;
+
+/// A recorded value of an element, see [ChangeEntry]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeValue {
+    /// Switchgear position state
+    Position(PositionState),
+    /// Position state of an integrated earthing switch
+    EarthPosition(PositionState),
+    /// Measurement value
+    Measurement(f64),
+}
+
+impl fmt::Display for ChangeValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeValue::Position(state) => write!(f, "{}", state),
+            ChangeValue::EarthPosition(state) => write!(f, "earth {}", state),
+            ChangeValue::Measurement(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A timestamped change of a single element
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEntry {
+    time: f64,
+    element: String,
+    old: ChangeValue,
+    new: ChangeValue,
+}
+
+impl ChangeEntry {
+    /// Constructor, the time is the simulation time in seconds at which the change happened
+    pub(crate) fn new(time: f64, element: &str, old: ChangeValue, new: ChangeValue) -> ChangeEntry {
+        ChangeEntry {
+            time,
+            element: element.to_string(),
+            old,
+            new,
+        }
+    }
+
+    /// Get the simulation time in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Get the name of the changed element
+    pub fn element(&self) -> &String {
+        &self.element
+    }
+
+    /// Get the value before the change
+    pub fn old_value(&self) -> ChangeValue {
+        self.old
+    }
+
+    /// Get the value after the change
+    pub fn new_value(&self) -> ChangeValue {
+        self.new
+    }
+}
+
+impl fmt::Display for ChangeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} -> {}",
+            self.time, self.element, self.old, self.new
+        )
+    }
+}
+
+/// Chronological log of the switch operations, measurement updates and resets performed through a network, see [crate::network::Network::changelog]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeLog {
+    entries: Vec<ChangeEntry>,
+}
+
+impl ChangeLog {
+    /// Constructor, creates an empty log
+    pub fn new() -> ChangeLog {
+        ChangeLog::default()
+    }
+
+    /// Append an entry
+    pub(crate) fn record(&mut self, entry: ChangeEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Get the entries in the order they were recorded
+    pub fn entries(&self) -> &Vec<ChangeEntry> {
+        &self.entries
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -42,26 +42,23 @@ pub fn run() {
         stdout().flush().unwrap();
         stdin().read_line(&mut buf).unwrap();
         let cmd = process_input(&buf);
-        match execute_command(net, cmd) {
-            Err(e) => {
-                println!("<{}", e);
-            }
-            Ok(_) => {}
+        if let Err(e) = execute_command(net, cmd) {
+            println!("<{}", e);
         }
     }
 }
 
 fn execute_command(net: &Network, cmd: Command) -> Result<(), String> {
     match cmd {
-        Command::Create(args) => create(&net, args[0], args[1]),
-        Command::Delete(arg) => delete(&net, arg),
-        Command::List() => list(&net),
-        Command::Show(arg) => show(&net, arg),
-        Command::Connect(args) => connect(&net, args[0], args[1], args[2]),
-        Command::Disconnect(args) => disconnect(&net, args[0], args[1]),
-        Command::Open(arg) => open(&net, arg),
-        Command::Close(arg) => close(&net, arg),
-        Command::Update(args) => update(&net, args[0], args[1]),
+        Command::Create(args) => create(net, args[0], args[1]),
+        Command::Delete(arg) => delete(net, arg),
+        Command::List() => list(net),
+        Command::Show(arg) => show(net, arg),
+        Command::Connect(args) => connect(net, args[0], args[1], args[2]),
+        Command::Disconnect(args) => disconnect(net, args[0], args[1]),
+        Command::Open(arg) => open(net, arg),
+        Command::Close(arg) => close(net, arg),
+        Command::Update(args) => update(net, args[0], args[1]),
         Command::Exit => process::exit(0),
         Command::Help => print_help(),
         Command::Undefined => Err("Invalid command; type 'help' to see valid commands".to_string()),
@@ -72,30 +69,54 @@ fn create(net: &Network, _type: &str, name: &str) -> Result<(), String> {
     match _type {
         "cb" => {
             println!("<Created Circuit Breaker {}", name);
-            net.create_component::<CircuitBreaker>(&name)
+            net.create_component::<CircuitBreaker>(name)
         }
         "ds" => {
             println!("<Created Disconnector {}", name);
-            net.create_component::<Disconnector>(&name)
+            net.create_component::<Disconnector>(name)
+        }
+        "dse" => {
+            println!("<Created Disconnector Earth {}", name);
+            net.create_component::<DisconnectorEarth>(name)
         }
         "es" => {
             println!("<Created Disconnector {}", name);
-            net.create_component::<EarthingSwitch>(&name)
+            net.create_component::<EarthingSwitch>(name)
         }
         "vt" => {
             println!("<Created Voltage Transformer {}", name);
-            net.create_component::<VoltageTransformer>(&name)
+            net.create_component::<VoltageTransformer>(name)
         }
         "tf" => {
             println!("<Created Transformer {}", name);
-            net.create_component::<Transformer>(&name)
+            net.create_component::<Transformer>(name)
+        }
+        "ct" => {
+            println!("<Created Current Transformer {}", name);
+            net.create_component::<CurrentTransformer>(name)
+        }
+        "line" => {
+            println!("<Created Line {}", name);
+            net.create_component::<Line>(name)
+        }
+        "load" => {
+            println!("<Created Load {}", name);
+            net.create_component::<Load>(name)
+        }
+        "gen" => {
+            println!("<Created Generator {}", name);
+            net.create_component::<Generator>(name)
+        }
+        "jmp" => {
+            println!("<Created Jumper {}", name);
+            net.create_component::<Jumper>(name)
         }
         "node" => {
             println!("<Created Node {}", name);
-            net.create_node(&name)
+            net.create_node(name)
         }
         _ => Err(format!(
-            "{} type does not exist (cb, ds, es, vt, tf, node)",
+            "{} type does not exist (cb, ds, dse, es, vt, tf, ct, line, load, gen, jmp, node)",
             _type
         )),
     }
@@ -142,13 +163,10 @@ fn connect(
     component_name: &str,
     terminal: &str,
 ) -> Result<(), String> {
-    let terminal_usize: usize;
-    match terminal.parse::<usize>() {
-        Ok(t) => {
-            terminal_usize = t;
-        }
+    let terminal_usize = match terminal.parse::<usize>() {
+        Ok(t) => t,
         Err(_) => return Err("Terminal (3rd argument) is not an unsigned integer".to_string()),
-    }
+    };
     net.connect(node_name, component_name, terminal_usize)
 }
 
@@ -157,28 +175,25 @@ fn disconnect(net: &Network, node_name: &str, component_name: &str) -> Result<()
 }
 
 fn open(net: &Network, component_name: &str) -> Result<(), String> {
-    let c = net.get_component(component_name)?;
-    c.open()
+    net.open(component_name).map_err(|e| e.to_string())
 }
 
 fn close(net: &Network, component_name: &str) -> Result<(), String> {
-    let c = net.get_component(component_name)?;
-    c.close()
+    net.close(component_name).map_err(|e| e.to_string())
 }
 
 fn update(net: &Network, component_name: &str, value: &str) -> Result<(), String> {
-    let c = net.get_component(component_name)?;
     if let Ok(v) = value.parse::<f64>() {
-        c.update(v)
+        net.update(component_name, v).map_err(|e| e.to_string())
     } else {
         Err("Update value has to be a number".to_string())
     }
 }
 
-fn process_input(buf: &str) -> Command {
+fn process_input(buf: &str) -> Command<'_> {
     let buf = buf.trim();
     let split: Vec<&str> = buf.split(' ').collect();
-    if split.len() == 0 {
+    if split.is_empty() {
         return Command::Undefined;
     }
     let cmd: &str = &split[0].to_lowercase();
@@ -198,7 +213,7 @@ fn process_input(buf: &str) -> Command {
             }
         }
         "list" => {
-            if split.len() < 1 {
+            if split.is_empty() {
                 Command::Undefined
             } else {
                 Command::List()
@@ -426,6 +441,42 @@ mod tests {
 
         assert!(execute_command(net, Command::Create(["node", "7"])).is_ok());
 
+        assert!(execute_command(net, Command::Create(["dse", "8"])).is_ok());
+        assert_eq!(
+            net.get_component("8").unwrap().r#type(),
+            ComponentType::DisconnectorEarth
+        );
+
+        assert!(execute_command(net, Command::Create(["ct", "9"])).is_ok());
+        assert_eq!(
+            net.get_component("9").unwrap().r#type(),
+            ComponentType::CurrentTransformer
+        );
+
+        assert!(execute_command(net, Command::Create(["line", "10"])).is_ok());
+        assert_eq!(
+            net.get_component("10").unwrap().r#type(),
+            ComponentType::Line
+        );
+
+        assert!(execute_command(net, Command::Create(["load", "11"])).is_ok());
+        assert_eq!(
+            net.get_component("11").unwrap().r#type(),
+            ComponentType::Load
+        );
+
+        assert!(execute_command(net, Command::Create(["gen", "12"])).is_ok());
+        assert_eq!(
+            net.get_component("12").unwrap().r#type(),
+            ComponentType::Generator
+        );
+
+        assert!(execute_command(net, Command::Create(["jmp", "13"])).is_ok());
+        assert_eq!(
+            net.get_component("13").unwrap().r#type(),
+            ComponentType::Jumper
+        );
+
         assert!(execute_command(net, Command::Create(["garb", "name"])).is_err());
 
         // Delete
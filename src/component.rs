@@ -1,41 +1,191 @@
-use std::cell::RefCell;
+use std::any::Any;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
+use std::str::FromStr;
 
-use super::measurement::Measurement;
+use super::error::NetworkError;
+use super::measurement::{Measurement, Unit};
 use super::node::Node;
-use super::position::SwitchgearPosition;
+use super::position::{AutoReclose, SwitchgearPosition, ThreePositionState};
 use super::terminal::Terminal;
 
 /// Component Type
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ComponentType {
     /// Circuit Breaker
     CircuitBreaker,
     /// Disconnector
     Disconnector,
+    /// Disconnector with integrated earthing switch
+    DisconnectorEarth,
     /// Earthing Switch
     EarthingSwitch,
     /// Voltage Transformer
     VoltageTransformer,
     /// Transformer
     Transformer,
+    /// Current Transformer
+    CurrentTransformer,
+    /// Line
+    Line,
+    /// Load
+    Load,
+    /// Generator
+    Generator,
+    /// Zero-impedance hard-wired link between two nodes
+    Jumper,
+    /// User-defined type, the index of its [CustomType] in the network it is registered with, see [crate::network::Network::register_custom_type]. Index 0 is the generic custom type every network knows, see [CustomType::generic].
+    Custom(u32),
 }
 
-impl fmt::Display for ComponentType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let typestr: &str;
+/// Name, terminal count and conduction rule of a custom component type, see [crate::network::Network::register_custom_type]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomType {
+    name: String,
+    terminals: usize,
+    conducts: bool,
+}
+
+impl CustomType {
+    /// Constructor; components of the type have the given number of terminals and always conduct between them if conducts is true, never otherwise
+    pub(crate) fn new(name: &str, terminals: usize, conducts: bool) -> CustomType {
+        CustomType {
+            name: name.to_string(),
+            terminals,
+            conducts,
+        }
+    }
+
+    /// The generic custom type "Custom" with two terminals that always conduct, [ComponentType::Custom] with index 0
+    pub fn generic() -> CustomType {
+        CustomType::new("Custom", 2, true)
+    }
+
+    /// Get the type name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get the number of terminals of components of this type
+    pub fn terminals(&self) -> usize {
+        self.terminals
+    }
+
+    /// True if components of this type conduct between their terminals
+    pub fn conducts(&self) -> bool {
+        self.conducts
+    }
+}
+
+impl ComponentType {
+    /// Return the number of terminals components of this type should have. Custom types are defined by the network they are registered with, see [crate::network::Network::terminal_count]; here only the generic custom type has terminals.
+    pub fn terminal_count(&self) -> usize {
+        match self {
+            ComponentType::CircuitBreaker => 2,
+            ComponentType::Disconnector => 2,
+            ComponentType::DisconnectorEarth => 2,
+            ComponentType::EarthingSwitch => 1,
+            ComponentType::VoltageTransformer => 1,
+            ComponentType::Transformer => 3,
+            ComponentType::CurrentTransformer => 2,
+            ComponentType::Line => 2,
+            ComponentType::Load => 1,
+            ComponentType::Generator => 1,
+            ComponentType::Jumper => 2,
+            ComponentType::Custom(0) => CustomType::generic().terminals,
+            ComponentType::Custom(_) => 0,
+        }
+    }
+
+    /// Construct a new component of this type with the given name. Custom types are defined by the network they are registered with, see [crate::network::Network::instantiate]; here every custom type gets the generic definition.
+    pub fn instantiate(&self, name: &str) -> Rc<dyn Component> {
         match self {
-            ComponentType::CircuitBreaker => typestr = "CircuitBreaker",
-            ComponentType::Disconnector => typestr = "Disconnector",
-            ComponentType::EarthingSwitch => typestr = "Earthing Switch",
-            ComponentType::VoltageTransformer => typestr = "Voltage Transformer",
-            ComponentType::Transformer => typestr = "Transformer",
+            ComponentType::CircuitBreaker => Rc::new(CircuitBreaker::new(name)),
+            ComponentType::Disconnector => Rc::new(Disconnector::new(name)),
+            ComponentType::DisconnectorEarth => Rc::new(DisconnectorEarth::new(name)),
+            ComponentType::EarthingSwitch => Rc::new(EarthingSwitch::new(name)),
+            ComponentType::VoltageTransformer => Rc::new(VoltageTransformer::new(name)),
+            ComponentType::Transformer => Rc::new(Transformer::new(name)),
+            ComponentType::CurrentTransformer => Rc::new(CurrentTransformer::new(name)),
+            ComponentType::Line => Rc::new(Line::new(name)),
+            ComponentType::Load => Rc::new(Load::new(name)),
+            ComponentType::Generator => Rc::new(Generator::new(name)),
+            ComponentType::Jumper => Rc::new(Jumper::new(name)),
+            ComponentType::Custom(_) => Rc::new(CustomComponent::new(name)),
         }
+    }
+}
+
+impl fmt::Display for ComponentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let typestr = match self {
+            ComponentType::CircuitBreaker => "CircuitBreaker",
+            ComponentType::Disconnector => "Disconnector",
+            ComponentType::DisconnectorEarth => "Disconnector Earth",
+            ComponentType::EarthingSwitch => "Earthing Switch",
+            ComponentType::VoltageTransformer => "Voltage Transformer",
+            ComponentType::Transformer => "Transformer",
+            ComponentType::CurrentTransformer => "Current Transformer",
+            ComponentType::Line => "Line",
+            ComponentType::Load => "Load",
+            ComponentType::Generator => "Generator",
+            ComponentType::Jumper => "Jumper",
+            ComponentType::Custom(0) => "Custom",
+            ComponentType::Custom(index) => return write!(f, "Custom {}", index),
+        };
         write!(f, "{}", typestr)
     }
 }
 
+impl FromStr for ComponentType {
+    type Err = NetworkError;
+
+    /// Parse either the [fmt::Display] string ("Earthing Switch") or the compact code ("ESW") of a component type. Of the custom types only the generic "Custom" is known here, see [crate::network::Network::parse_type] for registered custom types.
+    fn from_str(s: &str) -> Result<ComponentType, NetworkError> {
+        match s {
+            "CircuitBreaker" | "CBR" => Ok(ComponentType::CircuitBreaker),
+            "Disconnector" | "DIS" => Ok(ComponentType::Disconnector),
+            "Disconnector Earth" | "DSE" => Ok(ComponentType::DisconnectorEarth),
+            "Earthing Switch" | "ESW" => Ok(ComponentType::EarthingSwitch),
+            "Voltage Transformer" | "VTR" => Ok(ComponentType::VoltageTransformer),
+            "Transformer" | "TFR" => Ok(ComponentType::Transformer),
+            "Current Transformer" | "CTR" => Ok(ComponentType::CurrentTransformer),
+            "Line" | "LIN" => Ok(ComponentType::Line),
+            "Load" | "LOD" => Ok(ComponentType::Load),
+            "Generator" | "GEN" => Ok(ComponentType::Generator),
+            "Jumper" | "JMP" => Ok(ComponentType::Jumper),
+            "Custom" => Ok(ComponentType::Custom(0)),
+            _ => Err(NetworkError::Unsupported(format!(
+                "Unknown component type {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// How energization crosses a transformer between its windings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnergizationPolicy {
+    /// An energized winding energizes all other windings
+    MagneticCoupling,
+    /// Only the primary winding, terminal 0, energizes the other windings; a de-energized winding does not back-feed the primary
+    GalvanicIsolation,
+}
+
+/// What an earthing switch connects to when closed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EarthKind {
+    /// The system ground; a closed switch on an energized node is a fault
+    SystemEarth,
+    /// A local maintenance earth, not counted as system ground in analysis
+    MaintenanceEarth,
+}
+
+/// Access token for the raw state cells of a component, such as its name and id. Implementations outside this crate receive it in the cell accessors they provide but can not create one, so the state is only changed through the provided methods.
+pub struct Key(());
+
 /// Trait to define components. Each component should have a [ComponentType] and at least one [Terminal]
 pub trait Component {
     /// Constructor; sets the component name
@@ -45,11 +195,101 @@ pub trait Component {
 
     /// Returns the [ComponentType] of the component
     fn r#type(&self) -> ComponentType;
-    /// Returns the name of the component
-    fn name(&self) -> &String;
+    /// Returns the name cell of the component; only this crate can call it, see [Key]
+    fn name_cell(&self, key: Key) -> &RefCell<String>;
+    /// Returns the user metadata of the component
+    fn metadata(&self) -> &RefCell<HashMap<String, String>>;
+    /// Returns the coordinates cell of the component; only this crate can call it, see [Key]
+    fn coordinates_cell(&self, key: Key) -> &Cell<Option<(f64, f64)>>;
+    /// Returns the id cell of the component; only this crate can call it, see [Key]
+    fn id_cell(&self, key: Key) -> &Cell<Option<u32>>;
+    /// Returns the component as [Any], to downcast it to its concrete type
+    fn as_any(&self) -> &dyn Any;
     /// Returns the terminal with a given index, or an error if the component has less terminals than the given index.
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String>;
 
+    /// Returns the name of the component
+    fn name(&self) -> Ref<'_, String> {
+        self.name_cell(Key(())).borrow()
+    }
+
+    /// Returns the id of the component, assigned when it is added to a network. None if not added to a network.
+    fn id(&self) -> Option<u32> {
+        self.id_cell(Key(())).get()
+    }
+
+    /// Return the (x, y) coordinates of the component, if positioned
+    fn coordinates(&self) -> Option<(f64, f64)> {
+        self.coordinates_cell(Key(())).get()
+    }
+
+    /// Set the (x, y) coordinates of the component, or None if unpositioned
+    fn set_coordinates(&self, coordinates: Option<(f64, f64)>) {
+        self.coordinates_cell(Key(())).set(coordinates);
+    }
+
+    /// Returns an iterator over all terminals in index order
+    fn terminals(&self) -> Box<dyn Iterator<Item = &RefCell<Terminal>> + '_> {
+        Box::new((0..).map_while(move |i| self.terminal(i).ok()))
+    }
+
+    /// Return the name of the node connected to the terminal with the given index, None if the terminal is unconnected.
+    fn terminal_node_name(&self, index: usize) -> Result<Option<String>, NetworkError> {
+        let t = self.terminal(index)?;
+        match t.try_borrow() {
+            Ok(t) => Ok(t.get_node().ok().map(|n| n.name().clone())),
+            Err(_) => Err(NetworkError::Busy(format!(
+                "Terminal {} of component {} is already borrowed",
+                index,
+                self.name()
+            ))),
+        }
+    }
+
+    /// True if the terminal with the given index is connected to a node
+    fn is_terminal_connected(&self, index: usize) -> Result<bool, NetworkError> {
+        Ok(self.terminal_node_name(index)?.is_some())
+    }
+
+    /// Return the index of the first terminal connected to the given node, None if the component is not connected to it
+    fn terminal_index_of_node(&self, node: &Rc<Node>) -> Option<usize> {
+        self.terminals().position(|t| match t.borrow().get_node() {
+            Ok(n) => Rc::ptr_eq(&n, node),
+            Err(_) => false,
+        })
+    }
+
+    /// Return the nodes connected to the other terminals, if the component is connected to the given node. Empty if it is not.
+    fn other_nodes(&self, from: &Rc<Node>) -> Vec<Rc<Node>> {
+        if self.terminal_index_of_node(from).is_none() {
+            return vec![];
+        }
+        self.terminals()
+            .filter_map(|t| t.borrow().get_node().ok())
+            .filter(|n| !Rc::ptr_eq(n, from))
+            .collect()
+    }
+
+    /// Return the node on the opposite terminal of a two-terminal component connected to the given node. None for other components or if either side is unconnected.
+    fn other_node(&self, from: &Rc<Node>) -> Option<Rc<Node>> {
+        if self.terminals().count() != 2 {
+            return None;
+        }
+        self.other_nodes(from).pop()
+    }
+
+    /// Set a user metadata value
+    fn set_meta(&self, key: &str, value: &str) {
+        self.metadata()
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Get a user metadata value, if set
+    fn get_meta(&self, key: &str) -> Option<String> {
+        self.metadata().borrow().get(key).cloned()
+    }
+
     /// Connect the component to a node on a given terminal index
     ///
     /// Only allow a connection if
@@ -57,53 +297,87 @@ pub trait Component {
     /// * The given terminal index exists (the component has less terminals than the given index)
     /// * The given terminal is not already connected to another node
     fn connect(&self, node: Rc<Node>, terminal_index: usize) -> Result<(), String> {
-        let mut i = 0;
-        loop {
+        for (i, t) in self.terminals().enumerate() {
             if i == terminal_index {
-                i += 1;
                 continue;
             }
-            match self.terminal(i) {
-                Err(_) => {
-                    break;
-                }
-                Ok(t) => {
-                    if let Ok(n) = t.borrow().get_node() {
-                        if Rc::ptr_eq(&node, &n) {
-                            return Err(format!(
-                                "Component {} is already connected to node {} on terminal {}",
-                                self.name(),
-                                node.name(),
-                                i
-                            ));
-                        }
-                    }
+            if let Ok(n) = t.borrow().get_node() {
+                if Rc::ptr_eq(&node, &n) {
+                    return Err(format!(
+                        "Component {} is already connected to node {} on terminal {}",
+                        self.name(),
+                        node.name(),
+                        i
+                    ));
                 }
             }
-            i += 1;
         }
         let t = self.terminal(terminal_index)?;
         t.borrow_mut().connect(node)?;
         Ok(())
     }
 
-    /// Disconnect the component from the given node. Returns an error if not connected to it.
-    fn disconnect(&self, node: Rc<Node>) -> Result<(), String> {
-        let mut i = 0;
-        while let Ok(t) = self.terminal(i) {
+    /// Disconnect the component from the given node and remove the component from the node children. Returns an error if not connected to it.
+    fn disconnect(&self, node: Rc<Node>) -> Result<(), NetworkError> {
+        for t in self.terminals() {
             let mut t = t.borrow_mut();
             if let Ok(n) = t.get_node() {
                 if Rc::ptr_eq(&n, &node) {
-                    return t.disconnect();
+                    n.detach(self as *const Self as *const ());
+                    return Ok(t.disconnect()?);
                 }
             }
-            i += 1;
         }
-        Err(format!(
+        Err(NetworkError::Other(format!(
             "Component {} is not connnected to node {}",
             self.name(),
             node.name()
-        ))
+        )))
+    }
+
+    /// Disconnect every terminal of the component and remove the component from the children of the nodes it was connected to
+    fn disconnect_all(&self) {
+        for t in self.terminals() {
+            let mut t = t.borrow_mut();
+            if let Ok(n) = t.get_node() {
+                n.detach(self as *const Self as *const ());
+                t.disconnect().unwrap();
+            }
+        }
+    }
+
+    /// Disconnect the terminal with the given index from whichever node it is connected to and remove the component from that node's children, see [Component::disconnect]. Errors if the index is out of range or the terminal is not connected.
+    fn disconnect_terminal(&self, index: usize) -> Result<(), NetworkError> {
+        let mut t = self.terminal(index)?.borrow_mut();
+        match t.get_node() {
+            Ok(n) => {
+                n.detach(self as *const Self as *const ());
+                Ok(t.disconnect()?)
+            }
+            Err(_) => Err(NetworkError::Other(format!(
+                "Terminal {} of component {} is not connected",
+                index,
+                self.name()
+            ))),
+        }
+    }
+
+    /// Connect a terminal to a node like [Component::connect], but return [NetworkError::Busy] instead of panicking if any terminal is already borrowed
+    fn try_connect(&self, node: Rc<Node>, terminal_index: usize) -> Result<(), NetworkError> {
+        for (i, t) in self.terminals().enumerate() {
+            let free = match i == terminal_index {
+                true => t.try_borrow_mut().is_ok(),
+                false => t.try_borrow().is_ok(),
+            };
+            if !free {
+                return Err(NetworkError::Busy(format!(
+                    "Terminal {} of component {} is already borrowed",
+                    i,
+                    self.name()
+                )));
+            }
+        }
+        Ok(self.connect(node, terminal_index)?)
     }
 
     /// Get component [SwitchgearPosition], only implemented for switchgear
@@ -114,222 +388,1310 @@ pub trait Component {
         ))
     }
 
-    /// Open switchgear
-    fn open(&self) -> Result<(), String> {
+    /// Return a short description of the component state; "open", "closed" or "transit" for switchgear and "n/a" for other components
+    fn state_summary(&self) -> String {
+        match self.position() {
+            Ok(pos) => pos.borrow().to_string().to_lowercase(),
+            Err(_) => "n/a".to_string(),
+        }
+    }
+
+    /// Send a trip (open) command to switchgear, see [SwitchgearPosition::trip]
+    fn trip(&self) -> Result<(), String> {
         let pos = self.position()?;
-        pos.borrow_mut().open()?;
+        pos.borrow_mut().trip();
         Ok(())
     }
 
-    /// Close switchgear
-    fn close(&self) -> Result<(), String> {
+    /// Send a close command to switchgear, see [SwitchgearPosition::command_close]
+    fn command_close(&self) -> Result<(), String> {
         let pos = self.position()?;
-        pos.borrow_mut().close()?;
+        pos.borrow_mut().command_close();
         Ok(())
     }
 
-    /// Update measurement value
-    fn update(&self, _value: f64) -> Result<(), String> {
+    /// Get the [AutoReclose] scheme, only implemented for circuit breakers. None if auto-reclose is disabled.
+    fn auto_reclose(&self) -> Result<&RefCell<Option<AutoReclose>>, String> {
         Err(format!(
-            "Components of type {} have no measurement",
+            "Components of type {} have no auto-reclose",
             self.r#type()
         ))
     }
 
-    /// Get measurement value
-    fn value(&self) -> Result<f64, String> {
+    /// Set or disable the [AutoReclose] scheme
+    fn set_auto_reclose(&self, auto_reclose: Option<AutoReclose>) -> Result<(), String> {
+        *self.auto_reclose()?.borrow_mut() = auto_reclose;
+        Ok(())
+    }
+
+    /// Trip switchgear by protection at the given time; sends a trip command, opens the switchgear and arms the auto-reclose scheme if configured
+    fn protection_trip(&self, now: f64) -> Result<(), String> {
+        self.trip()?;
+        let pos = self.position()?;
+        if !pos.borrow().is_open() {
+            pos.borrow_mut().open()?;
+        }
+        if let Ok(ar) = self.auto_reclose() {
+            if let Some(ar) = ar.borrow_mut().as_mut() {
+                ar.on_trip(now);
+            }
+        }
+        Ok(())
+    }
+
+    /// Advance time based behaviour to the given time; recloses switchgear whose auto-reclose dead time has elapsed
+    fn tick(&self, now: f64) {
+        let due = match self.auto_reclose() {
+            Ok(ar) => ar.borrow_mut().as_mut().is_some_and(|ar| ar.take_due(now)),
+            Err(_) => false,
+        };
+        if due {
+            if let Ok(pos) = self.position() {
+                let mut pos = pos.borrow_mut();
+                pos.command_close();
+                if pos.is_open() && !pos.is_locked() {
+                    pos.close().unwrap();
+                }
+            }
+        }
+    }
+
+    /// Get earthing [SwitchgearPosition], only implemented for switchgear with an integrated earthing switch
+    fn earth_position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Err(format!(
+            "Components of type {} have no integrated earthing switch",
+            self.r#type()
+        ))
+    }
+
+    /// Close integrated earthing switch. Errors with [NetworkError::Locked] while locked and with [NetworkError::Interlock] unless the main contact is open.
+    fn close_earth(&self) -> Result<(), NetworkError> {
+        let earth = self.earth_position().map_err(NetworkError::Unsupported)?;
+        if self.is_locked() {
+            return Err(NetworkError::Locked(format!(
+                "Component {} is locked",
+                self.name()
+            )));
+        }
+        if !self.position()?.borrow().is_open() {
+            return Err(NetworkError::Interlock(format!(
+                "Component {} has to be open before closing to earth",
+                self.name()
+            )));
+        }
+        Ok(earth.borrow_mut().close()?)
+    }
+
+    /// Open integrated earthing switch. Errors with [NetworkError::Locked] while locked.
+    fn open_earth(&self) -> Result<(), NetworkError> {
+        let earth = self.earth_position().map_err(NetworkError::Unsupported)?;
+        if self.is_locked() {
+            return Err(NetworkError::Locked(format!(
+                "Component {} is locked",
+                self.name()
+            )));
+        }
+        Ok(earth.borrow_mut().open()?)
+    }
+
+    /// Check that switchgear may be opened, without opening it. Errors with [NetworkError::Locked] while locked.
+    fn check_open(&self) -> Result<(), NetworkError> {
+        if self.position()?.borrow().is_locked() {
+            return Err(NetworkError::Locked(format!(
+                "Component {} is locked",
+                self.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that switchgear may be closed, without closing it. Errors with [NetworkError::Locked] while locked; components with an interlock add their own conditions, a disconnector with integrated earthing switch has to be open to earth.
+    fn check_close(&self) -> Result<(), NetworkError> {
+        if self.position()?.borrow().is_locked() {
+            return Err(NetworkError::Locked(format!(
+                "Component {} is locked",
+                self.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Open switchgear if [Component::check_open] allows it. Borrows the position mutably, see [Component::try_open] for a non-panicking variant
+    fn open(&self) -> Result<(), NetworkError> {
+        self.check_open()?;
+        self.position()?.borrow_mut().open()?;
+        Ok(())
+    }
+
+    /// Close switchgear if [Component::check_close] allows it. Borrows the position mutably, see [Component::try_close] for a non-panicking variant
+    fn close(&self) -> Result<(), NetworkError> {
+        self.check_close()?;
+        self.position()?.borrow_mut().close()?;
+        Ok(())
+    }
+
+    /// Open switchgear like [Component::open], but return [NetworkError::Busy] instead of panicking if the position or earth position is already borrowed
+    fn try_open(&self) -> Result<(), NetworkError> {
+        check_positions_free(self)?;
+        self.open()
+    }
+
+    /// Close switchgear like [Component::close], but return [NetworkError::Busy] instead of panicking if the position or earth position is already borrowed
+    fn try_close(&self) -> Result<(), NetworkError> {
+        check_positions_free(self)?;
+        self.close()
+    }
+
+    /// Lock switchgear against operation
+    fn lock(&self) -> Result<(), String> {
+        self.position()?.borrow_mut().lock();
+        Ok(())
+    }
+
+    /// Release the lock of switchgear
+    fn unlock(&self) -> Result<(), String> {
+        self.position()?.borrow_mut().unlock();
+        Ok(())
+    }
+
+    /// True if the component is switchgear that is locked against operation
+    fn is_locked(&self) -> bool {
+        match self.position() {
+            Ok(pos) => pos.borrow().is_locked(),
+            Err(_) => false,
+        }
+    }
+
+    /// Return the number of operations of switchgear, see [SwitchgearPosition::operation_count]
+    fn operation_count(&self) -> Result<u32, String> {
+        Ok(self.position()?.borrow().operation_count())
+    }
+
+    /// Reset the operation counter of switchgear, see [SwitchgearPosition::reset_counter]
+    fn reset_counter(&self) -> Result<(), String> {
+        self.position()?.borrow_mut().reset_counter();
+        Ok(())
+    }
+
+    /// Get earthing switch [EarthKind], only implemented for earthing switches and disconnectors with integrated earthing switch
+    fn earth_kind(&self) -> Result<&Cell<EarthKind>, String> {
+        Err(format!(
+            "Components of type {} have no earth kind",
+            self.r#type()
+        ))
+    }
+
+    /// True unless the component earths to a [EarthKind::MaintenanceEarth]
+    fn is_system_earth(&self) -> bool {
+        match self.earth_kind() {
+            Ok(kind) => kind.get() == EarthKind::SystemEarth,
+            Err(_) => true,
+        }
+    }
+
+    /// Get transformer [EnergizationPolicy], only implemented for transformers
+    fn policy(&self) -> Result<&Cell<EnergizationPolicy>, String> {
+        Err(format!(
+            "Components of type {} have no energization policy",
+            self.r#type()
+        ))
+    }
+
+    /// True if energization passes between the connected terminals. Switchgear only conducts when closed; in which direction a transformer conducts depends on its [EnergizationPolicy], see [Component::feeds].
+    fn conducts(&self) -> bool {
+        if let Ok(pos) = self.position() {
+            return pos.borrow().is_closed();
+        }
+        true
+    }
+
+    /// Return the pairs of terminal indices that are internally connected in the current state. All terminals of a conducting component are linked to each other, a non-conducting component links none. A transformer with [EnergizationPolicy::GalvanicIsolation] only links its primary winding, terminal 0, to each other winding.
+    fn internal_connections(&self) -> Vec<(usize, usize)> {
+        if !self.conducts() {
+            return vec![];
+        }
+        self.feeds().into_iter().filter(|(i, j)| i < j).collect()
+    }
+
+    /// Return the (from, to) pairs of terminal indices across which energization passes while the component conducts. All terminals feed each other, except for a transformer with [EnergizationPolicy::GalvanicIsolation], which only feeds from its primary winding.
+    fn feeds(&self) -> Vec<(usize, usize)> {
+        let count = self.terminals().count();
+        if self
+            .policy()
+            .is_ok_and(|p| p.get() == EnergizationPolicy::GalvanicIsolation)
+        {
+            return (1..count).map(|j| (0, j)).collect();
+        }
+        (0..count)
+            .flat_map(|i| (i + 1..count).flat_map(move |j| [(i, j), (j, i)]))
+            .collect()
+    }
+
+    /// Get the current rating in A, only implemented for lines, transformers and current transformers. None if no rating is set.
+    fn rating(&self) -> Result<&Cell<Option<f64>>, String> {
+        Err(format!(
+            "Components of type {} have no current rating",
+            self.r#type()
+        ))
+    }
+
+    /// Set the current rating in A
+    fn set_rating(&self, rating: Option<f64>) -> Result<(), String> {
+        self.rating()?.set(rating);
+        Ok(())
+    }
+
+    /// Get the series impedance as (resistance, reactance) in ohm, only implemented for lines and transformers. None if no impedance is set.
+    fn impedance(&self) -> Result<&Cell<Option<(f64, f64)>>, String> {
+        Err(format!(
+            "Components of type {} have no impedance",
+            self.r#type()
+        ))
+    }
+
+    /// Set the series impedance as (resistance, reactance) in ohm
+    fn set_impedance(&self, impedance: Option<(f64, f64)>) -> Result<(), String> {
+        self.impedance()?.set(impedance);
+        Ok(())
+    }
+
+    /// Get the active power in MW, only implemented for loads (demand) and generators (output)
+    fn active_power(&self) -> Result<&Cell<f64>, String> {
+        Err(format!(
+            "Components of type {} have no active power",
+            self.r#type()
+        ))
+    }
+
+    /// Return the active power injected into the network in MW; generator output is positive, load demand negative and other components inject nothing
+    fn injection(&self) -> f64 {
+        match (self.r#type(), self.active_power()) {
+            (ComponentType::Generator, Ok(p)) => p.get(),
+            (ComponentType::Load, Ok(p)) => -p.get(),
+            _ => 0f64,
+        }
+    }
+
+    /// Get component [Measurement], only implemented for measurement transformers
+    fn measurement(&self) -> Result<&RefCell<Measurement>, String> {
         Err(format!(
             "Components of type {} have no measurement",
             self.r#type()
         ))
     }
-}
 
-impl fmt::Display for dyn Component {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Component {} of type {:?}", self.name(), self.r#type())
+    /// Return the name of the component type; the [fmt::Display] string of its [ComponentType], or the registered name of a custom type
+    fn type_name(&self) -> String {
+        self.r#type().to_string()
+    }
+
+    /// Create a copy of the component under a new name. The copy has the same type, metadata, positions, measurement, policy, rating and impedance, but its terminals are not connected.
+    fn duplicate(&self, new_name: &str) -> Rc<dyn Component> {
+        let copy = self.r#type().instantiate(new_name);
+        copy_settings(self, copy.as_ref());
+        copy
+    }
+
+    /// Return the component to its default state, leaving its connections intact. Switchgear and integrated earthing switches return to their normal position, the auto-reclose scheme is reset and measurements are cleared, see [SwitchgearPosition::reset], [AutoReclose::reset] and [Measurement::reset].
+    fn reset(&self) {
+        if let Ok(pos) = self.position() {
+            pos.borrow_mut().reset();
+        }
+        if let Ok(earth) = self.earth_position() {
+            earth.borrow_mut().reset();
+        }
+        if let Ok(auto_reclose) = self.auto_reclose() {
+            if let Some(auto_reclose) = auto_reclose.borrow_mut().as_mut() {
+                auto_reclose.reset();
+            }
+        }
+        if let Ok(m) = self.measurement() {
+            m.borrow_mut().reset();
+        }
+    }
+
+    /// Update measurement value
+    fn update(&self, value: f64) -> Result<(), String> {
+        let m = self.measurement()?;
+        m.borrow_mut().update(value)
+    }
+
+    /// Get measurement value
+    fn value(&self) -> Result<f64, String> {
+        let m = self.measurement()?;
+        let value = m.borrow().value();
+        Ok(value)
+    }
+}
+
+impl dyn Component {
+    /// Set the name in place, so existing references see the new name. The network checks that the name is unique, see [crate::network::Network::rename].
+    pub(crate) fn set_name(&self, name: &str) {
+        *self.name_cell(Key(())).borrow_mut() = name.to_string();
+    }
+
+    /// Set the id, done by the network when the component is added to it
+    pub(crate) fn set_id(&self, id: Option<u32>) {
+        self.id_cell(Key(())).set(id);
+    }
+
+    /// Run a closure with mutable access to the terminal with the given index. Returns an error instead of panicking if the terminal is already borrowed.
+    pub fn with_terminal<R>(
+        &self,
+        index: usize,
+        f: impl FnOnce(&mut Terminal) -> R,
+    ) -> Result<R, NetworkError> {
+        let t = self.terminal(index)?;
+        match t.try_borrow_mut() {
+            Ok(mut t) => Ok(f(&mut t)),
+            Err(_) => Err(NetworkError::Busy(format!(
+                "Terminal {} of component {} is already borrowed",
+                index,
+                self.name()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for dyn Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Component {} of type {:?}", self.name(), self.r#type())
+    }
+}
+
+/// Copy the metadata, positions, measurement, auto-reclose scheme, policy, earth kind, active power, rating and impedance to another component, as far as both components have them
+pub(crate) fn copy_settings<C: Component + ?Sized>(from: &C, to: &dyn Component) {
+    *to.metadata().borrow_mut() = from.metadata().borrow().clone();
+    if let (Ok(from), Ok(to)) = (from.position(), to.position()) {
+        *to.borrow_mut() = from.borrow().clone();
+    }
+    if let (Ok(from), Ok(to)) = (from.earth_position(), to.earth_position()) {
+        *to.borrow_mut() = from.borrow().clone();
+    }
+    if let (Ok(from), Ok(to)) = (from.measurement(), to.measurement()) {
+        *to.borrow_mut() = from.borrow().clone();
+    }
+    if let (Ok(from), Ok(to)) = (from.auto_reclose(), to.auto_reclose()) {
+        *to.borrow_mut() = from.borrow().clone();
+    }
+    if let (Ok(from), Ok(to)) = (from.policy(), to.policy()) {
+        to.set(from.get());
+    }
+    if let (Ok(from), Ok(to)) = (from.earth_kind(), to.earth_kind()) {
+        to.set(from.get());
+    }
+    if let (Ok(from), Ok(to)) = (from.active_power(), to.active_power()) {
+        to.set(from.get());
+    }
+    if let (Ok(from), Ok(to)) = (from.rating(), to.rating()) {
+        to.set(from.get());
+    }
+    if let (Ok(from), Ok(to)) = (from.impedance(), to.impedance()) {
+        to.set(from.get());
+    }
+}
+
+/// Return [NetworkError::Busy] if the position or earth position, when the component has them, can not be borrowed mutably
+fn check_positions_free<C: Component + ?Sized>(c: &C) -> Result<(), NetworkError> {
+    let position = c.position().map(|p| p.try_borrow_mut().is_ok());
+    let earth = c.earth_position().map(|p| p.try_borrow_mut().is_ok());
+    match (position, earth) {
+        (Ok(false), _) | (_, Ok(false)) => Err(NetworkError::Busy(format!(
+            "Position of component {} is already borrowed",
+            c.name()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Wrap a new component in an [Rc] and connect it to the nodes, see [connect_nodes]. This backs the `connected` constructors of the two-terminal components.
+fn wire<T: 'static + Component>(c: T, nodes: &[Rc<Node>]) -> Result<Rc<T>, NetworkError> {
+    let c = Rc::new(c);
+    let component: Rc<dyn Component> = c.clone();
+    connect_nodes(&component, nodes)?;
+    Ok(c)
+}
+
+/// Connect terminal i of the component to the i-th node, adding it to the children of each node. If a connection fails the connections made so far are undone, so the nodes are left unchanged.
+pub(crate) fn connect_nodes(
+    component: &Rc<dyn Component>,
+    nodes: &[Rc<Node>],
+) -> Result<(), NetworkError> {
+    for (i, node) in nodes.iter().enumerate() {
+        let result = component
+            .connect(node.clone(), i)
+            .and_then(|_| node.add_component(component.clone()));
+        if let Err(e) = result {
+            component.disconnect_all();
+            return Err(NetworkError::Other(e));
+        }
+    }
+    Ok(())
+}
+
+/// Circuit Breaker
+pub struct CircuitBreaker {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    position: RefCell<SwitchgearPosition>,
+    auto_reclose: RefCell<Option<AutoReclose>>,
+    terminals: [RefCell<Terminal>; 2],
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<CircuitBreaker>, NetworkError> {
+        wire(CircuitBreaker::new(name), &[node_a, node_b])
+    }
+}
+
+impl Component for CircuitBreaker {
+    fn new(name: &str) -> CircuitBreaker {
+        CircuitBreaker {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            position: RefCell::new(SwitchgearPosition::new()),
+            auto_reclose: RefCell::new(None),
+            terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::CircuitBreaker
+    }
+
+    fn auto_reclose(&self) -> Result<&RefCell<Option<AutoReclose>>, String> {
+        Ok(&self.auto_reclose)
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Ok(&self.position)
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+}
+
+/// Disconnector
+pub struct Disconnector {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    position: RefCell<SwitchgearPosition>,
+    terminals: [RefCell<Terminal>; 2],
+}
+
+impl Disconnector {
+    /// Create a disconnector connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<Disconnector>, NetworkError> {
+        wire(Disconnector::new(name), &[node_a, node_b])
+    }
+}
+
+impl Component for Disconnector {
+    fn new(name: &str) -> Disconnector {
+        Disconnector {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            position: RefCell::new(SwitchgearPosition::new()),
+            terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::Disconnector
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Ok(&self.position)
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+}
+
+/// Disconnector with integrated earthing switch. Has three positions; open, closed to line and closed to earth. Terminal 1 is earthed in the closed to earth position.
+pub struct DisconnectorEarth {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    position: RefCell<SwitchgearPosition>,
+    earth: RefCell<SwitchgearPosition>,
+    earth_kind: Cell<EarthKind>,
+    terminals: [RefCell<Terminal>; 2],
+}
+
+impl DisconnectorEarth {
+    /// Create a disconnector with integrated earthing switch connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<DisconnectorEarth>, NetworkError> {
+        wire(DisconnectorEarth::new(name), &[node_a, node_b])
+    }
+
+    /// Return the three-position state
+    pub fn state(&self) -> ThreePositionState {
+        if self.position.borrow().is_closed() {
+            ThreePositionState::ClosedLine
+        } else if self.earth.borrow().is_closed() {
+            ThreePositionState::ClosedEarth
+        } else {
+            ThreePositionState::Open
+        }
+    }
+}
+
+impl Component for DisconnectorEarth {
+    fn new(name: &str) -> DisconnectorEarth {
+        DisconnectorEarth {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            position: RefCell::new(SwitchgearPosition::new()),
+            earth: RefCell::new(SwitchgearPosition::new()),
+            earth_kind: Cell::new(EarthKind::SystemEarth),
+            terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::DisconnectorEarth
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Ok(&self.position)
+    }
+
+    fn earth_position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Ok(&self.earth)
+    }
+
+    fn earth_kind(&self) -> Result<&Cell<EarthKind>, String> {
+        Ok(&self.earth_kind)
+    }
+
+    fn check_close(&self) -> Result<(), NetworkError> {
+        if self.is_locked() {
+            return Err(NetworkError::Locked(format!(
+                "Component {} is locked",
+                self.name()
+            )));
+        }
+        if !self.earth.borrow().is_open() {
+            return Err(NetworkError::Interlock(format!(
+                "Component {} has to be opened from earth before closing to line",
+                self.name()
+            )));
+        }
+        Ok(())
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+}
+
+/// Earthing Switch
+pub struct EarthingSwitch {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    position: RefCell<SwitchgearPosition>,
+    earth_kind: Cell<EarthKind>,
+    terminals: [RefCell<Terminal>; 1],
+}
+
+impl Component for EarthingSwitch {
+    fn new(name: &str) -> EarthingSwitch {
+        EarthingSwitch {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            position: RefCell::new(SwitchgearPosition::new()),
+            earth_kind: Cell::new(EarthKind::SystemEarth),
+            terminals: [RefCell::new(Terminal::new()); 1],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::EarthingSwitch
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+
+    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
+        Ok(&self.position)
+    }
+
+    fn earth_kind(&self) -> Result<&Cell<EarthKind>, String> {
+        Ok(&self.earth_kind)
+    }
+}
+
+/// Voltage Transformer
+pub struct VoltageTransformer {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    measurement: RefCell<Measurement>,
+    terminals: [RefCell<Terminal>; 1],
+}
+
+impl Component for VoltageTransformer {
+    fn new(name: &str) -> VoltageTransformer {
+        // A voltage magnitude can not be negative
+        let mut measurement = Measurement::with_unit(Unit::Kilovolt);
+        measurement.set_limits(Some(0f64), None);
+        VoltageTransformer {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            measurement: RefCell::new(measurement),
+            terminals: [RefCell::new(Terminal::new())],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::VoltageTransformer
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+
+    fn measurement(&self) -> Result<&RefCell<Measurement>, String> {
+        Ok(&self.measurement)
+    }
+}
+
+/// Transformer
+pub struct Transformer {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    policy: Cell<EnergizationPolicy>,
+    rating: Cell<Option<f64>>,
+    impedance: Cell<Option<(f64, f64)>>,
+    terminals: [RefCell<Terminal>; 3],
+}
+
+impl Component for Transformer {
+    fn new(name: &str) -> Transformer {
+        Transformer {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            policy: Cell::new(EnergizationPolicy::MagneticCoupling),
+            rating: Cell::new(None),
+            impedance: Cell::new(None),
+            terminals: [
+                RefCell::new(Terminal::new()),
+                RefCell::new(Terminal::new()),
+                RefCell::new(Terminal::new()),
+            ],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::Transformer
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+
+    fn policy(&self) -> Result<&Cell<EnergizationPolicy>, String> {
+        Ok(&self.policy)
+    }
+
+    fn rating(&self) -> Result<&Cell<Option<f64>>, String> {
+        Ok(&self.rating)
+    }
+
+    fn impedance(&self) -> Result<&Cell<Option<(f64, f64)>>, String> {
+        Ok(&self.impedance)
+    }
+}
+
+/// Current Transformer, connected in series between two nodes
+pub struct CurrentTransformer {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    measurement: RefCell<Measurement>,
+    rating: Cell<Option<f64>>,
+    terminals: [RefCell<Terminal>; 2],
+}
+
+impl CurrentTransformer {
+    /// Create a current transformer connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<CurrentTransformer>, NetworkError> {
+        wire(CurrentTransformer::new(name), &[node_a, node_b])
+    }
+}
+
+impl Component for CurrentTransformer {
+    fn new(name: &str) -> CurrentTransformer {
+        // A current magnitude can not be negative
+        let mut measurement = Measurement::with_unit(Unit::Ampere);
+        measurement.set_limits(Some(0f64), None);
+        CurrentTransformer {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            measurement: RefCell::new(measurement),
+            rating: Cell::new(None),
+            terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
+        }
+    }
+
+    fn r#type(&self) -> ComponentType {
+        ComponentType::CurrentTransformer
+    }
+
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
+        &self.name
+    }
+
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+        match self.terminals.get(index) {
+            Some(t) => Ok(t),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
+        }
+    }
+
+    fn measurement(&self) -> Result<&RefCell<Measurement>, String> {
+        Ok(&self.measurement)
+    }
+
+    fn rating(&self) -> Result<&Cell<Option<f64>>, String> {
+        Ok(&self.rating)
     }
 }
 
-/// Circuit Breaker
-pub struct CircuitBreaker {
-    name: String,
-    position: RefCell<SwitchgearPosition>,
+/// Line
+pub struct Line {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    rating: Cell<Option<f64>>,
+    impedance: Cell<Option<(f64, f64)>>,
     terminals: [RefCell<Terminal>; 2],
 }
 
-impl Component for CircuitBreaker {
-    fn new(name: &str) -> CircuitBreaker {
-        CircuitBreaker {
-            name: name.to_string(),
-            position: RefCell::new(SwitchgearPosition::new()),
+impl Line {
+    /// Create a line connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<Line>, NetworkError> {
+        wire(Line::new(name), &[node_a, node_b])
+    }
+}
+
+impl Component for Line {
+    fn new(name: &str) -> Line {
+        Line {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            rating: Cell::new(None),
+            impedance: Cell::new(None),
             terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
         }
     }
 
     fn r#type(&self) -> ComponentType {
-        ComponentType::CircuitBreaker
+        ComponentType::Line
     }
 
-    fn name(&self) -> &String {
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
         &self.name
     }
 
-    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
-        Ok(&self.position)
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
         match self.terminals.get(index) {
             Some(t) => Ok(t),
-            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name, self.r#type(), index, self.terminals.len())),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
         }
     }
+
+    fn rating(&self) -> Result<&Cell<Option<f64>>, String> {
+        Ok(&self.rating)
+    }
+
+    fn impedance(&self) -> Result<&Cell<Option<(f64, f64)>>, String> {
+        Ok(&self.impedance)
+    }
 }
 
-/// Disconnector
-pub struct Disconnector {
-    name: String,
-    position: RefCell<SwitchgearPosition>,
+/// Jumper, a zero-impedance link that always conducts
+pub struct Jumper {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
     terminals: [RefCell<Terminal>; 2],
 }
 
-impl Component for Disconnector {
-    fn new(name: &str) -> Disconnector {
-        Disconnector {
-            name: name.to_string(),
-            position: RefCell::new(SwitchgearPosition::new()),
+impl Jumper {
+    /// Create a jumper connected from node_a to node_b, errors if either connection fails
+    pub fn connected(
+        name: &str,
+        node_a: Rc<Node>,
+        node_b: Rc<Node>,
+    ) -> Result<Rc<Jumper>, NetworkError> {
+        wire(Jumper::new(name), &[node_a, node_b])
+    }
+}
+
+impl Component for Jumper {
+    fn new(name: &str) -> Jumper {
+        Jumper {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
             terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
         }
     }
 
     fn r#type(&self) -> ComponentType {
-        ComponentType::Disconnector
+        ComponentType::Jumper
     }
 
-    fn name(&self) -> &String {
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
         &self.name
     }
 
-    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
-        Ok(&self.position)
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
         match self.terminals.get(index) {
             Some(t) => Ok(t),
-            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name, self.r#type(), index, self.terminals.len())),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
         }
     }
 }
 
-/// Earthing Switch
-pub struct EarthingSwitch {
-    name: String,
-    position: RefCell<SwitchgearPosition>,
+/// Load, consumes its active power demand
+pub struct Load {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    active_power: Cell<f64>,
     terminals: [RefCell<Terminal>; 1],
 }
 
-impl Component for EarthingSwitch {
-    fn new(name: &str) -> EarthingSwitch {
-        EarthingSwitch {
-            name: name.to_string(),
-            position: RefCell::new(SwitchgearPosition::new()),
-            terminals: [RefCell::new(Terminal::new()); 1],
+impl Component for Load {
+    fn new(name: &str) -> Load {
+        Load {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            active_power: Cell::new(0f64),
+            terminals: [RefCell::new(Terminal::new())],
         }
     }
 
     fn r#type(&self) -> ComponentType {
-        ComponentType::EarthingSwitch
+        ComponentType::Load
     }
 
-    fn name(&self) -> &String {
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
         &self.name
     }
 
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
         match self.terminals.get(index) {
             Some(t) => Ok(t),
-            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name, self.r#type(), index, self.terminals.len())),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
         }
     }
 
-    fn position(&self) -> Result<&RefCell<SwitchgearPosition>, String> {
-        Ok(&self.position)
+    fn active_power(&self) -> Result<&Cell<f64>, String> {
+        Ok(&self.active_power)
     }
 }
 
-/// Voltage Transformer
-pub struct VoltageTransformer {
-    name: String,
-    measurement: RefCell<Measurement>,
+/// Generator, supplies its active power output
+pub struct Generator {
+    name: RefCell<String>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    active_power: Cell<f64>,
     terminals: [RefCell<Terminal>; 1],
 }
 
-impl Component for VoltageTransformer {
-    fn new(name: &str) -> VoltageTransformer {
-        VoltageTransformer {
-            name: name.to_string(),
-            measurement: RefCell::new(Measurement::new()),
+impl Component for Generator {
+    fn new(name: &str) -> Generator {
+        Generator {
+            name: RefCell::new(name.to_string()),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            active_power: Cell::new(0f64),
             terminals: [RefCell::new(Terminal::new())],
         }
     }
 
     fn r#type(&self) -> ComponentType {
-        ComponentType::VoltageTransformer
+        ComponentType::Generator
     }
 
-    fn name(&self) -> &String {
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
         &self.name
     }
 
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
         match self.terminals.get(index) {
             Some(t) => Ok(t),
-            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name, self.r#type(), index, self.terminals.len())),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
         }
     }
 
-    fn update(&self, value: f64) -> Result<(), String> {
-        self.measurement.borrow_mut().update(value);
-        Ok(())
-    }
-
-    fn value(&self) -> Result<f64, String> {
-        Ok(self.measurement.borrow().value())
+    fn active_power(&self) -> Result<&Cell<f64>, String> {
+        Ok(&self.active_power)
     }
 }
 
-/// Transformer
-pub struct Transformer {
-    name: String,
-    terminals: [RefCell<Terminal>; 3],
+/// Component of a user-defined type, see [CustomType]
+pub struct CustomComponent {
+    name: RefCell<String>,
+    index: u32,
+    definition: CustomType,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
+    terminals: Vec<RefCell<Terminal>>,
 }
 
-impl Component for Transformer {
-    fn new(name: &str) -> Transformer {
-        Transformer {
-            name: name.to_string(),
-            terminals: [
-                RefCell::new(Terminal::new()),
-                RefCell::new(Terminal::new()),
-                RefCell::new(Terminal::new()),
-            ],
+impl CustomComponent {
+    /// Constructor for a component of the custom type with the given index and definition, with the terminals and conduction rule of the definition
+    pub(crate) fn of_type(name: &str, index: u32, definition: &CustomType) -> CustomComponent {
+        CustomComponent {
+            name: RefCell::new(name.to_string()),
+            index,
+            definition: definition.clone(),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
+            terminals: (0..definition.terminals)
+                .map(|_| RefCell::new(Terminal::new()))
+                .collect(),
         }
     }
+}
+
+impl Component for CustomComponent {
+    /// Create a component of the generic custom type, see [CustomType::generic]; use [crate::network::Network::instantiate] for registered types
+    fn new(name: &str) -> CustomComponent {
+        CustomComponent::of_type(name, 0, &CustomType::generic())
+    }
 
     fn r#type(&self) -> ComponentType {
-        ComponentType::Transformer
+        ComponentType::Custom(self.index)
+    }
+
+    fn type_name(&self) -> String {
+        self.definition.name.clone()
+    }
+
+    fn duplicate(&self, new_name: &str) -> Rc<dyn Component> {
+        let copy: Rc<dyn Component> = Rc::new(CustomComponent::of_type(
+            new_name,
+            self.index,
+            &self.definition,
+        ));
+        copy_settings(self, copy.as_ref());
+        copy
     }
 
-    fn name(&self) -> &String {
+    fn name_cell(&self, _: Key) -> &RefCell<String> {
         &self.name
     }
 
+    fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+        &self.coordinates
+    }
+
+    fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+        &self.id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn conducts(&self) -> bool {
+        self.definition.conducts
+    }
+
     fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
         match self.terminals.get(index) {
             Some(t) => Ok(t),
-            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name, self.r#type(), index, self.terminals.len())),
+            None => Err(format!("Component {} of type {:?} does not have a terminal with index {}; it only has {} terminals", self.name(), self.r#type(), index, self.terminals.len())),
         }
     }
 }
@@ -337,6 +1699,7 @@ impl Component for Transformer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::measurement::Quality;
 
     fn create_test_components() -> (
         CircuitBreaker,
@@ -358,11 +1721,23 @@ mod tests {
     fn component_names() {
         let (cb, ds, es, vt, tf) = create_test_components();
 
-        assert_eq!(cb.name(), "cb");
-        assert_eq!(ds.name(), "ds");
-        assert_eq!(es.name(), "es");
-        assert_eq!(vt.name(), "vt");
-        assert_eq!(tf.name(), "tf");
+        assert_eq!(cb.name().as_str(), "cb");
+        assert_eq!(ds.name().as_str(), "ds");
+        assert_eq!(es.name().as_str(), "es");
+        assert_eq!(vt.name().as_str(), "vt");
+        assert_eq!(tf.name().as_str(), "tf");
+    }
+
+    #[test]
+    fn component_metadata() {
+        let cb = CircuitBreaker::new("cb");
+        assert_eq!(cb.get_meta("asset_id"), None);
+        cb.set_meta("asset_id", "CB-0042");
+        cb.set_meta("color", "red");
+        assert_eq!(cb.get_meta("asset_id"), Some("CB-0042".to_string()));
+        cb.set_meta("color", "green");
+        assert_eq!(cb.get_meta("color"), Some("green".to_string()));
+        assert_eq!(cb.metadata().borrow().len(), 2);
     }
 
     #[test]
@@ -376,6 +1751,92 @@ mod tests {
         assert_eq!(tf.r#type(), ComponentType::Transformer)
     }
 
+    #[test]
+    fn component_as_any() {
+        fn is<T: 'static>(t: ComponentType) -> bool {
+            t.instantiate("c").as_any().is::<T>()
+        }
+        assert!(is::<CircuitBreaker>(ComponentType::CircuitBreaker));
+        assert!(is::<Disconnector>(ComponentType::Disconnector));
+        assert!(is::<DisconnectorEarth>(ComponentType::DisconnectorEarth));
+        assert!(is::<EarthingSwitch>(ComponentType::EarthingSwitch));
+        assert!(is::<VoltageTransformer>(ComponentType::VoltageTransformer));
+        assert!(is::<Transformer>(ComponentType::Transformer));
+        assert!(is::<CurrentTransformer>(ComponentType::CurrentTransformer));
+        assert!(is::<Line>(ComponentType::Line));
+        assert!(is::<Load>(ComponentType::Load));
+        assert!(is::<Generator>(ComponentType::Generator));
+        assert!(is::<Jumper>(ComponentType::Jumper));
+
+        let des = ComponentType::DisconnectorEarth.instantiate("des");
+        let concrete = des.as_any().downcast_ref::<DisconnectorEarth>().unwrap();
+        assert_eq!(concrete.name().as_str(), "des");
+        assert!(des.as_any().downcast_ref::<Disconnector>().is_none());
+        assert!(des.as_any().downcast_ref::<Transformer>().is_none());
+    }
+
+    #[test]
+    fn component_type_from_str() {
+        for t in [
+            ComponentType::CircuitBreaker,
+            ComponentType::Disconnector,
+            ComponentType::DisconnectorEarth,
+            ComponentType::EarthingSwitch,
+            ComponentType::VoltageTransformer,
+            ComponentType::Transformer,
+            ComponentType::CurrentTransformer,
+            ComponentType::Line,
+            ComponentType::Load,
+            ComponentType::Generator,
+            ComponentType::Jumper,
+        ] {
+            assert_eq!(t.to_string().parse::<ComponentType>().unwrap(), t);
+        }
+        assert_eq!(
+            "CBR".parse::<ComponentType>().unwrap(),
+            ComponentType::CircuitBreaker
+        );
+        assert_eq!(
+            "DIS".parse::<ComponentType>().unwrap(),
+            ComponentType::Disconnector
+        );
+        assert!(matches!(
+            "earthing switch".parse::<ComponentType>(),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert!("".parse::<ComponentType>().is_err());
+    }
+
+    #[test]
+    fn component_custom_type() {
+        let generic = CustomComponent::new("c");
+        assert_eq!(generic.r#type(), ComponentType::Custom(0));
+        assert_eq!(generic.type_name(), "Custom");
+        assert_eq!(generic.terminals().count(), 2);
+        assert!(generic.conducts());
+        assert_eq!(
+            "Custom".parse::<ComponentType>().unwrap(),
+            ComponentType::Custom(0)
+        );
+        assert_eq!(ComponentType::Custom(0).terminal_count(), 2);
+        assert_eq!(ComponentType::Custom(1).to_string(), "Custom 1");
+
+        let svc = CustomComponent::of_type(
+            "svc1",
+            1,
+            &CustomType::new("Static Var Compensator", 1, false),
+        );
+        assert_eq!(svc.r#type(), ComponentType::Custom(1));
+        assert_eq!(svc.type_name(), "Static Var Compensator");
+        assert_eq!(svc.terminals().count(), 1);
+        assert!(!svc.conducts());
+        let copy = svc.duplicate("svc2");
+        assert_eq!(copy.r#type(), ComponentType::Custom(1));
+        assert_eq!(copy.type_name(), "Static Var Compensator");
+        assert_eq!(copy.terminals().count(), 1);
+        assert!(!copy.conducts());
+    }
+
     #[test]
     fn component_terminals() {
         let (cb, ds, es, vt, tf) = create_test_components();
@@ -391,6 +1852,20 @@ mod tests {
         assert!(es.terminal(1).is_err());
         assert!(vt.terminal(1).is_err());
         assert!(tf.terminal(3).is_err());
+
+        for c in [&cb as &dyn Component, &ds, &es, &vt, &tf] {
+            assert_eq!(c.terminals().count(), c.r#type().terminal_count());
+        }
+
+        assert_eq!(cb.terminals().count(), 2);
+        assert_eq!(ds.terminals().count(), 2);
+        assert_eq!(es.terminals().count(), 1);
+        assert_eq!(vt.terminals().count(), 1);
+        assert_eq!(tf.terminals().count(), 3);
+        assert!(tf
+            .terminals()
+            .zip(tf.terminals.iter())
+            .all(|(a, b)| std::ptr::eq(a, b)));
     }
 
     #[test]
@@ -409,6 +1884,254 @@ mod tests {
         assert!(cb.connect(n2.clone(), 0).is_err());
     }
 
+    #[test]
+    fn component_with_terminal() {
+        let n = Rc::new(Node::new("node"));
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+
+        cb.with_terminal(1, |t| t.connect(n.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(cb.with_terminal(1, |t| t.get_node().is_ok()).unwrap());
+        assert!(cb.with_terminal(2, |_| ()).is_err());
+
+        let nested = cb.with_terminal(1, |_| cb.with_terminal(1, |_| ()));
+        assert!(matches!(nested, Ok(Err(NetworkError::Busy(_)))));
+        assert!(cb
+            .with_terminal(0, |_| cb.with_terminal(1, |_| ()))
+            .unwrap()
+            .is_ok());
+    }
+
+    #[test]
+    fn component_try_borrow() {
+        let n = Rc::new(Node::new("node"));
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+
+        let guard = cb.position().unwrap().borrow();
+        assert!(matches!(cb.try_close(), Err(NetworkError::Busy(_))));
+        assert!(matches!(cb.try_open(), Err(NetworkError::Busy(_))));
+        drop(guard);
+        cb.try_close().unwrap();
+        assert!(cb.conducts());
+        cb.try_open().unwrap();
+
+        let guard = cb.terminal(0).unwrap().borrow_mut();
+        assert!(matches!(
+            cb.try_connect(n.clone(), 1),
+            Err(NetworkError::Busy(_))
+        ));
+        drop(guard);
+        cb.try_connect(n.clone(), 1).unwrap();
+        assert!(matches!(cb.try_connect(n, 0), Err(NetworkError::Other(_))));
+
+        let des: Rc<dyn Component> = Rc::new(DisconnectorEarth::new("des"));
+        let _guard = des.earth_position().unwrap().borrow_mut();
+        assert!(matches!(des.try_close(), Err(NetworkError::Busy(_))));
+    }
+
+    #[test]
+    fn component_terminal_connection_status() {
+        let n = Rc::new(Node::new("node"));
+        let cb = CircuitBreaker::new("cb");
+        cb.connect(n.clone(), 1).unwrap();
+
+        assert!(!cb.is_terminal_connected(0).unwrap());
+        assert!(cb.is_terminal_connected(1).unwrap());
+        assert_eq!(cb.terminal_node_name(0).unwrap(), None);
+        assert_eq!(cb.terminal_node_name(1).unwrap(), Some("node".to_string()));
+        assert!(cb.is_terminal_connected(2).is_err());
+        assert!(cb.terminal_node_name(2).is_err());
+
+        let _guard = cb.terminal(1).unwrap().borrow_mut();
+        assert!(matches!(
+            cb.terminal_node_name(1),
+            Err(NetworkError::Busy(_))
+        ));
+    }
+
+    #[test]
+    fn component_other_node() {
+        let n1 = Rc::new(Node::new("n1"));
+        let n2 = Rc::new(Node::new("n2"));
+        let n3 = Rc::new(Node::new("n3"));
+        let cb = CircuitBreaker::new("cb");
+        cb.connect(n1.clone(), 0).unwrap();
+        assert!(cb.other_node(&n1).is_none());
+        cb.connect(n2.clone(), 1).unwrap();
+
+        assert!(Rc::ptr_eq(&cb.other_node(&n1).unwrap(), &n2));
+        assert!(Rc::ptr_eq(&cb.other_node(&n2).unwrap(), &n1));
+        assert!(cb.other_node(&n3).is_none());
+
+        let tf = Transformer::new("tf");
+        for (i, n) in [&n1, &n2, &n3].iter().enumerate() {
+            tf.connect((*n).clone(), i).unwrap();
+        }
+        assert!(tf.other_node(&n1).is_none());
+        let others = tf.other_nodes(&n2);
+        assert_eq!(others.len(), 2);
+        assert!(Rc::ptr_eq(&others[0], &n1) && Rc::ptr_eq(&others[1], &n3));
+        assert!(cb.other_nodes(&n3).is_empty());
+    }
+
+    #[test]
+    fn component_terminal_index_of_node() {
+        let n1 = Rc::new(Node::new("n1"));
+        let n2 = Rc::new(Node::new("n2"));
+        let cb = CircuitBreaker::new("cb");
+        assert_eq!(cb.terminal_index_of_node(&n1), None);
+        cb.connect(n1.clone(), 1).unwrap();
+        assert_eq!(cb.terminal_index_of_node(&n1), Some(1));
+        assert_eq!(cb.terminal_index_of_node(&n2), None);
+        cb.connect(n2.clone(), 0).unwrap();
+        assert_eq!(cb.terminal_index_of_node(&n2), Some(0));
+        cb.disconnect(n1.clone()).unwrap();
+        assert_eq!(cb.terminal_index_of_node(&n1), None);
+    }
+
+    #[test]
+    fn component_reset() {
+        let n1 = Rc::new(Node::new("n1"));
+        let n2 = Rc::new(Node::new("n2"));
+        let cb: Rc<dyn Component> =
+            CircuitBreaker::connected("cb", n1.clone(), n2.clone()).unwrap();
+        let dse: Rc<dyn Component> = Rc::new(DisconnectorEarth::new("dse"));
+        let ct: Rc<dyn Component> = Rc::new(CurrentTransformer::new("ct"));
+        cb.set_auto_reclose(Some(AutoReclose::new(1, 0.5))).unwrap();
+        cb.close().unwrap();
+        cb.protection_trip(0f64).unwrap();
+        dse.close_earth().unwrap();
+        ct.measurement()
+            .unwrap()
+            .borrow_mut()
+            .update_at(1f64, 400f64)
+            .unwrap();
+
+        for c in [&cb, &dse, &ct] {
+            c.reset();
+        }
+        let pos = cb.position().unwrap().borrow().clone();
+        assert!(pos.is_open());
+        assert_eq!(pos.command(), None);
+        assert_eq!(
+            cb.auto_reclose()
+                .unwrap()
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .remaining(),
+            1
+        );
+        assert!(dse.earth_position().unwrap().borrow().is_open());
+        let m = ct.measurement().unwrap().borrow().clone();
+        assert_eq!(m.value(), 0f64);
+        assert!(m.history().is_empty());
+        assert_eq!(m.unit(), Unit::Ampere);
+
+        // Connections are kept
+        assert_eq!(cb.terminal_node_name(1).unwrap(), Some("n2".to_string()));
+        assert_eq!(n1.degree(), 1);
+    }
+
+    #[test]
+    fn component_disconnect_clears_node() {
+        let n = Rc::new(Node::new("node"));
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+        cb.connect(n.clone(), 1).unwrap();
+        n.add_component(cb.clone()).unwrap();
+
+        cb.disconnect(n.clone()).unwrap();
+        assert!(cb.terminal(1).unwrap().borrow().get_node().is_err());
+        assert!(n.components().is_empty());
+        assert!(n.remove_component(cb).is_err());
+    }
+
+    #[test]
+    fn component_disconnect_terminal() {
+        let n = Rc::new(Node::new("node"));
+        let n2 = Rc::new(Node::new("node2"));
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+        for (node, t) in [(&n, 0), (&n2, 1)] {
+            cb.connect(node.clone(), t).unwrap();
+            node.add_component(cb.clone()).unwrap();
+        }
+
+        cb.disconnect_terminal(1).unwrap();
+        assert!(cb.terminal(1).unwrap().borrow().get_node().is_err());
+        assert!(n2.components().is_empty());
+        assert!(Rc::ptr_eq(
+            &cb.terminal(0).unwrap().borrow().get_node().unwrap(),
+            &n
+        ));
+        assert_eq!(n.degree(), 1);
+
+        assert!(cb.disconnect_terminal(1).is_err());
+        assert!(cb.disconnect_terminal(2).is_err());
+    }
+
+    #[test]
+    fn component_connected() {
+        let n1 = Rc::new(Node::new("n1"));
+        let n2 = Rc::new(Node::new("n2"));
+        let cb = CircuitBreaker::connected("cb", n1.clone(), n2.clone()).unwrap();
+        let line = Line::connected("line", n2.clone(), n1.clone()).unwrap();
+        for (i, n) in [&n1, &n2].iter().enumerate() {
+            assert!(Rc::ptr_eq(
+                &cb.terminal(i).unwrap().borrow().get_node().unwrap(),
+                n
+            ));
+        }
+        assert!(Rc::ptr_eq(
+            &line.terminal(0).unwrap().borrow().get_node().unwrap(),
+            &n2
+        ));
+        assert_eq!(n1.degree(), 2);
+        assert_eq!(n2.degree(), 2);
+
+        let n3 = Rc::new(Node::new("n3"));
+        let others: [Rc<dyn Component>; 3] = [
+            DisconnectorEarth::connected("dse", n2.clone(), n3.clone()).unwrap(),
+            CurrentTransformer::connected("ct", n2.clone(), n3.clone()).unwrap(),
+            Jumper::connected("jumper", n2.clone(), n3.clone()).unwrap(),
+        ];
+        for c in others.iter() {
+            assert!(Rc::ptr_eq(
+                &c.terminal(1).unwrap().borrow().get_node().unwrap(),
+                &n3
+            ));
+        }
+        assert_eq!(n3.degree(), 3);
+
+        // A failed wiring leaves the nodes untouched
+        assert!(Disconnector::connected("ds", n1.clone(), n1.clone()).is_err());
+        assert!(Jumper::connected("j", n1.clone(), n1.clone()).is_err());
+        assert_eq!(n1.degree(), 2);
+    }
+
+    #[test]
+    fn component_disconnect_all() {
+        let n = Rc::new(Node::new("node"));
+        let n2 = Rc::new(Node::new("node2"));
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+        let ds: Rc<dyn Component> = Rc::new(Disconnector::new("ds"));
+
+        for (c, node, t) in [(&cb, &n, 0), (&cb, &n2, 1), (&ds, &n, 0)] {
+            c.connect(node.clone(), t).unwrap();
+            node.add_component(c.clone()).unwrap();
+        }
+
+        cb.disconnect_all();
+        assert!(cb.terminals().all(|t| t.borrow().get_node().is_err()));
+        assert_eq!(n2.degree(), 0);
+        assert_eq!(n.degree(), 1);
+        assert!(ds.terminal(0).unwrap().borrow().get_node().is_ok());
+
+        ds.disconnect_all();
+        assert!(ds.terminal(0).unwrap().borrow().get_node().is_err());
+        assert!(n.components().is_empty());
+    }
+
     #[test]
     fn component_openclose() {
         let (cb, ds, es, vt, tf) = create_test_components();
@@ -432,6 +2155,124 @@ mod tests {
         assert!(es.open().is_ok());
     }
 
+    #[test]
+    fn component_lock() {
+        let cb = CircuitBreaker::new("cb");
+        cb.lock().unwrap();
+        assert!(cb.is_locked());
+        assert!(matches!(cb.close(), Err(NetworkError::Locked(_))));
+        assert!(cb.position().unwrap().borrow().is_open());
+        cb.unlock().unwrap();
+        assert!(!cb.is_locked());
+        cb.close().unwrap();
+        cb.lock().unwrap();
+        assert!(matches!(cb.open(), Err(NetworkError::Locked(_))));
+        assert!(cb.position().unwrap().borrow().is_closed());
+
+        let dse = DisconnectorEarth::new("dse");
+        dse.lock().unwrap();
+        assert!(matches!(dse.close(), Err(NetworkError::Locked(_))));
+        assert!(VoltageTransformer::new("vt").lock().is_err());
+    }
+
+    #[test]
+    fn component_command() {
+        let (cb, _, _, vt, _) = create_test_components();
+        assert!(vt.trip().is_err());
+        assert!(vt.command_close().is_err());
+
+        cb.command_close().unwrap();
+        assert!(cb.position().unwrap().borrow().command_position_mismatch());
+        cb.close().unwrap();
+        assert!(!cb.position().unwrap().borrow().command_position_mismatch());
+        cb.trip().unwrap();
+        assert!(cb.position().unwrap().borrow().command_position_mismatch());
+    }
+
+    #[test]
+    fn component_state_summary() {
+        let (cb, ds, es, vt, tf) = create_test_components();
+        cb.close().unwrap();
+
+        assert_eq!(cb.state_summary(), "closed");
+        assert_eq!(ds.state_summary(), "open");
+        assert_eq!(es.state_summary(), "open");
+        assert_eq!(vt.state_summary(), "n/a");
+        assert_eq!(tf.state_summary(), "n/a");
+    }
+
+    #[test]
+    fn component_conducts() {
+        let (cb, ds, es, vt, tf) = create_test_components();
+        assert!(!cb.conducts());
+        assert!(!ds.conducts());
+        assert!(!es.conducts());
+        assert!(vt.conducts());
+        assert!(vt.policy().is_err());
+
+        cb.close().unwrap();
+        assert!(cb.conducts());
+        assert!(tf.conducts());
+        assert_eq!(tf.feeds().len(), 6);
+        tf.policy()
+            .unwrap()
+            .set(EnergizationPolicy::GalvanicIsolation);
+        assert!(tf.conducts());
+        assert_eq!(tf.feeds(), vec![(0, 1), (0, 2)]);
+        cb.open().unwrap();
+        assert_eq!(cb.feeds(), vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn component_internal_connections() {
+        let (cb, ds, es, vt, tf) = create_test_components();
+        assert!(cb.internal_connections().is_empty());
+        cb.close().unwrap();
+        assert_eq!(cb.internal_connections(), vec![(0, 1)]);
+        cb.open().unwrap();
+        assert!(cb.internal_connections().is_empty());
+        ds.close().unwrap();
+        assert_eq!(ds.internal_connections(), vec![(0, 1)]);
+        es.close().unwrap();
+        assert!(es.internal_connections().is_empty());
+        assert!(vt.internal_connections().is_empty());
+        assert_eq!(tf.internal_connections(), vec![(0, 1), (0, 2), (1, 2)]);
+        tf.policy()
+            .unwrap()
+            .set(EnergizationPolicy::GalvanicIsolation);
+        assert_eq!(tf.internal_connections(), vec![(0, 1), (0, 2)]);
+        assert_eq!(Line::new("line").internal_connections(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn component_disconnector_earth() {
+        let dse = DisconnectorEarth::new("dse");
+        assert_eq!(dse.r#type(), ComponentType::DisconnectorEarth);
+        assert_eq!(dse.terminals().count(), 2);
+        assert_eq!(dse.state(), ThreePositionState::Open);
+
+        // Open -> line -> open -> earth -> open
+        dse.close().unwrap();
+        assert_eq!(dse.state(), ThreePositionState::ClosedLine);
+        assert!(dse.conducts());
+        assert!(dse.close_earth().is_err());
+        assert_eq!(dse.state(), ThreePositionState::ClosedLine);
+        dse.open().unwrap();
+        dse.close_earth().unwrap();
+        assert_eq!(dse.state(), ThreePositionState::ClosedEarth);
+        assert!(!dse.conducts());
+        assert!(dse.close().is_err());
+        assert_eq!(dse.state(), ThreePositionState::ClosedEarth);
+        assert!(dse.close_earth().is_err());
+        dse.open_earth().unwrap();
+        assert_eq!(dse.state(), ThreePositionState::Open);
+        assert!(dse.open_earth().is_err());
+
+        let (cb, _, es, _, _) = create_test_components();
+        assert!(cb.close_earth().is_err());
+        assert!(es.open_earth().is_err());
+    }
+
     #[test]
     fn component_update() {
         let (cb, ds, es, vt, tf) = create_test_components();
@@ -445,4 +2286,40 @@ mod tests {
         assert!(vt.update(1578.51758).is_ok());
         assert_eq!(vt.value().unwrap(), 1578.51758);
     }
+
+    #[test]
+    fn component_duplicate() {
+        let n = Rc::new(Node::new("node"));
+        let vt = VoltageTransformer::new("vt");
+        vt.connect(n, 0).unwrap();
+        vt.update(110f64).unwrap();
+        vt.set_meta("bay", "A1");
+
+        let copy = vt.duplicate("vt2");
+        assert_eq!(copy.name().as_str(), "vt2");
+        assert_eq!(copy.r#type(), ComponentType::VoltageTransformer);
+        assert_eq!(copy.value().unwrap(), 110f64);
+        assert_eq!(copy.get_meta("bay"), Some("A1".to_string()));
+        assert!(!copy.is_terminal_connected(0).unwrap());
+        assert!(vt.is_terminal_connected(0).unwrap());
+
+        let cb = CircuitBreaker::new("cb");
+        cb.close().unwrap();
+        assert!(cb.duplicate("cb2").position().unwrap().borrow().is_closed());
+    }
+
+    #[test]
+    fn component_measurement_quality() {
+        let vt = VoltageTransformer::new("vt");
+        let m = vt.measurement().unwrap();
+
+        vt.update(-10f64).unwrap();
+        assert_eq!(m.borrow().quality(), Quality::Bad);
+        vt.update(10f64).unwrap();
+        assert_eq!(m.borrow().quality(), Quality::Good);
+
+        m.borrow_mut().set_limits(Some(9f64), Some(11f64));
+        vt.update(12f64).unwrap();
+        assert_eq!(m.borrow().quality(), Quality::Bad);
+    }
 }
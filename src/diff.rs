@@ -0,0 +1,68 @@
+use super::position::PositionState;
+
+/// Differences between two networks, see [crate::network::Network::diff]
+#[derive(Debug, Default, PartialEq)]
+pub struct NetworkDiff {
+    added_nodes: Vec<String>,
+    removed_nodes: Vec<String>,
+    added_components: Vec<String>,
+    removed_components: Vec<String>,
+    changed_positions: Vec<(String, PositionState, PositionState)>,
+    changed_measurements: Vec<(String, f64, f64)>,
+}
+
+impl NetworkDiff {
+    /// Constructor, lists of element names and the (old, new) values of changed states
+    pub(crate) fn new(
+        added_nodes: Vec<String>,
+        removed_nodes: Vec<String>,
+        added_components: Vec<String>,
+        removed_components: Vec<String>,
+        changed_positions: Vec<(String, PositionState, PositionState)>,
+        changed_measurements: Vec<(String, f64, f64)>,
+    ) -> NetworkDiff {
+        NetworkDiff {
+            added_nodes,
+            removed_nodes,
+            added_components,
+            removed_components,
+            changed_positions,
+            changed_measurements,
+        }
+    }
+
+    /// Return the names of the nodes only present in the new network
+    pub fn added_nodes(&self) -> &Vec<String> {
+        &self.added_nodes
+    }
+
+    /// Return the names of the nodes only present in the old network
+    pub fn removed_nodes(&self) -> &Vec<String> {
+        &self.removed_nodes
+    }
+
+    /// Return the names of the components only present in the new network
+    pub fn added_components(&self) -> &Vec<String> {
+        &self.added_components
+    }
+
+    /// Return the names of the components only present in the old network
+    pub fn removed_components(&self) -> &Vec<String> {
+        &self.removed_components
+    }
+
+    /// Return the name, old and new position state of switchgear whose position changed
+    pub fn changed_positions(&self) -> &Vec<(String, PositionState, PositionState)> {
+        &self.changed_positions
+    }
+
+    /// Return the name, old and new value of measurements whose value changed
+    pub fn changed_measurements(&self) -> &Vec<(String, f64, f64)> {
+        &self.changed_measurements
+    }
+
+    /// True if there are no differences
+    pub fn is_empty(&self) -> bool {
+        *self == NetworkDiff::default()
+    }
+}
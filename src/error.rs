@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// Error returned by the network API
+#[derive(Debug, PartialEq)]
+pub enum NetworkError {
+    /// A node or component with this name already exists
+    DuplicateName(String),
+    /// No node or component with this name exists
+    NotFound(String),
+    /// The operation is not supported by this component type
+    Unsupported(String),
+    /// The operation is blocked by an interlock
+    Interlock(String),
+    /// The switchgear is locked against operation
+    Locked(String),
+    /// The element is already borrowed elsewhere
+    Busy(String),
+    /// Reading or writing a file failed
+    Io(String),
+    /// A serialized network could not be parsed
+    Parse(String),
+    /// Error message passed on from the component, node or terminal APIs
+    Other(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::DuplicateName(name) => {
+                write!(f, "A node or component with name {} already exists", name)
+            }
+            NetworkError::NotFound(name) => {
+                write!(f, "No node or component with name {} exists", name)
+            }
+            NetworkError::Unsupported(msg) => write!(f, "{}", msg),
+            NetworkError::Interlock(msg) => write!(f, "{}", msg),
+            NetworkError::Locked(msg) => write!(f, "{}", msg),
+            NetworkError::Busy(msg) => write!(f, "{}", msg),
+            NetworkError::Io(msg) => write!(f, "{}", msg),
+            NetworkError::Parse(msg) => write!(f, "{}", msg),
+            NetworkError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(e: std::io::Error) -> NetworkError {
+        NetworkError::Io(e.to_string())
+    }
+}
+
+impl From<String> for NetworkError {
+    fn from(msg: String) -> NetworkError {
+        NetworkError::Other(msg)
+    }
+}
@@ -1,110 +1,302 @@
-use std::fs::{OpenOptions, create_dir, read_to_string};
+use std::fmt;
+use std::fs::{create_dir, read_to_string, OpenOptions};
 use std::io::prelude::*;
-use std::io::{ErrorKind, Error};
-use std::collections::HashMap;
+use std::io::ErrorKind;
 
-use super::network::Network;
 use super::component::*;
-use super::node::Node;
 
-/// Strip the first and last character of a string
-fn strip_outer_characters(s: &str) -> String {
-    let mut chars = s.chars();
-    chars.next();
-    chars.next_back();
-    chars.as_str().to_string()
+/// Quote a string as a json string value, escaping quotes, backslashes and control characters
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
 /// Trait to (de)serialize objects to/from json
-pub trait JsonSerializable: JsonReadable + JsonWritable {
-}
+pub trait JsonSerializable: JsonReadable + JsonWritable {}
 
+/// Trait to deserialize objects from json
 pub trait JsonReadable {
     /// Get object intance from json representation
     fn from_json(filename: &str) -> Result<Box<Self>, String>;
 }
 
+/// Trait to serialize objects to json
 pub trait JsonWritable {
     /// Get json representation of object
     fn to_json(&self) -> Result<(), String>;
 }
 
+/// Reads json documents; [JsonReader::parse] parses a document from text, [JsonReader::read] the file of a single component in the json directory
 struct JsonReader<'a> {
     filename: &'a str,
-    keys: HashMap<String, String>,
+    chars: Vec<char>,
+    pos: usize,
+    document: JsonValue,
 }
 
 impl JsonReader<'_> {
-    fn new(filename: &str) -> JsonReader {
+    fn new(filename: &str) -> JsonReader<'_> {
         JsonReader {
             filename,
-            keys: HashMap::new(),
+            chars: vec![],
+            pos: 0,
+            document: JsonValue::Null,
         }
     }
 
+    /// Parse a json document
+    fn parse(json: &str) -> Result<JsonValue, String> {
+        let mut reader = JsonReader::new("");
+        reader.chars = json.chars().collect();
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        if reader.pos < reader.chars.len() {
+            return Err(format!(
+                "Invalid json: unexpected character at position {}",
+                reader.pos
+            ));
+        }
+        Ok(value)
+    }
+
     fn get_key(&self, key: &str) -> Result<&str, String> {
-        match self.keys.get(key) {
+        match self.document.get(key).and_then(|v| v.as_str()) {
             Some(s) => Ok(s),
-            None => Err(format!("Invalid Json: Key {} not found in json file {}", key, self.filename)),
+            None => Err(format!(
+                "Invalid Json: Key {} not found in json file {}",
+                key, self.filename
+            )),
         }
     }
 
     fn read(&mut self) -> Result<(), String> {
-        let mut json: String;
         match read_to_string(format!("{}/{}.json", "json", self.filename)) {
             Err(e) => panic!("{}", e),
             Ok(s) => {
-                json = s;
-                json.retain(|c| !c.is_whitespace());
-                json = strip_outer_characters(json.as_str()); // Strip braces
-                let split = json.split(',');
-                for item in split.clone() {
-                    let split2 = item.split(':').collect::<Vec<&str>>();
-                    let key = split2.get(0);
-                    let value = split2.get(1);
-                    match (key, value) {
-                        (Some(k), Some(v)) => {
-                            let k = strip_outer_characters(k); // Strip quotes
-                            let v = strip_outer_characters(v); // Strip quotes
-                            self.keys.insert(k, v);
-                        },
-                        (_, _) => return Err(format!("Failed to read json file {} on k-v pair {}", self.filename, item)),
+                self.document = JsonReader::parse(&s)
+                    .map_err(|e| format!("Failed to read json file {}: {}", self.filename, e))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, String> {
+        Err(format!(
+            "Invalid json: expected {} at position {}",
+            expected, self.pos
+        ))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", c))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, String> {
+        for c in word.chars() {
+            if self.chars.get(self.pos) != Some(&c) {
+                return self.error(word);
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos) {
+            Some('n') => self.literal("null", JsonValue::Null),
+            Some('t') => self.literal("true", JsonValue::Bool(true)),
+            Some('f') => self.literal("false", JsonValue::Bool(false)),
+            Some('"') => Ok(JsonValue::String(self.string()?)),
+            Some('[') => {
+                self.pos += 1;
+                let mut array = vec![];
+                self.skip_whitespace();
+                if self.chars.get(self.pos) == Some(&']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(array));
+                }
+                loop {
+                    array.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.chars.get(self.pos) {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Array(array));
+                        }
+                        _ => return self.error("',' or ']'"),
                     }
                 }
-                Ok(())
             }
+            Some('{') => {
+                self.pos += 1;
+                let mut object = vec![];
+                self.skip_whitespace();
+                if self.chars.get(self.pos) == Some(&'}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(object));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    object.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.chars.get(self.pos) {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Object(object));
+                        }
+                        _ => return self.error("',' or '}'"),
+                    }
+                }
+            }
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|c| "+-.eE".contains(*c) || c.is_ascii_digit())
+                {
+                    self.pos += 1;
+                }
+                let number: String = self.chars[start..self.pos].iter().collect();
+                match number.parse() {
+                    Ok(n) => Ok(JsonValue::Number(n)),
+                    Err(_) => Err(format!(
+                        "Invalid json: invalid number {} at position {}",
+                        number, start
+                    )),
+                }
+            }
+            _ => self.error("a value"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.chars.get(self.pos) != Some(&'"') {
+            return self.error("a string");
+        }
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let c = match self.chars.get(self.pos) {
+                Some(c) => *c,
+                None => return self.error("'\"'"),
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = match self.chars.get(self.pos) {
+                        Some(c) => *c,
+                        None => return self.error("an escape sequence"),
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        '"' | '\\' | '/' => s.push(escaped),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => s.push(self.unicode_escape()?),
+                        _ => return self.error("an escape sequence"),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Parse the digits of a \u escape, combining a UTF-16 surrogate pair written as two escapes into one character
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if self.chars.get(self.pos) != Some(&'\\')
+                    || self.chars.get(self.pos + 1) != Some(&'u')
+                {
+                    return self.error("a low surrogate escape");
+                }
+                self.pos += 2;
+                match self.hex4()? {
+                    low @ 0xdc00..=0xdfff => 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                    _ => return self.error("a low surrogate escape"),
+                }
+            }
+            code => code,
+        };
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("a unicode escape"),
+        }
+    }
+
+    /// Parse four hexadecimal digits
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+        match u32::from_str_radix(&hex, 16) {
+            Ok(code) if hex.len() == 4 => {
+                self.pos += 4;
+                Ok(code)
+            }
+            _ => self.error("four hexadecimal digits"),
         }
     }
 }
 
+/// Writes json documents; [JsonWriter::write] writes the keys of a single component to its file in the json directory, [JsonWriter::format] formats any document
 struct JsonWriter<'a> {
     filename: &'a str,
-    json: String,
+    keys: Vec<(String, JsonValue)>,
 }
 
 impl JsonWriter<'_> {
-    fn new(filename: &str) -> JsonWriter {
-        match create_dir("json") {
-            Err(e) if e.kind() == ErrorKind::AlreadyExists => {},
-            Err(e) => panic!("{}", e),
-            Ok(_) => {},
-        }
+    fn new(filename: &str) -> JsonWriter<'_> {
         JsonWriter {
             filename,
-            json: "{\n".to_string(),
+            keys: vec![],
         }
     }
 
     fn add_key<T: ToString>(&mut self, key: &'static str, value: T) {
-        let s = format!("    \"{}\": \"{}\",\n", key, value.to_string());
-        self.json += s.as_str();
+        self.keys.push((key.to_string(), value.to_string().into()));
     }
 
     fn write(&mut self) -> std::io::Result<()> {
-        let mut chars = self.json.chars();
-        chars.next_back();
-        chars.next_back();
-        self.json = chars.as_str().to_string() + "\n}";
+        match create_dir("json") {
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+            Ok(_) => {}
+        }
+        let json = JsonValue::Object(self.keys.clone()).to_string();
 
         let mut file = OpenOptions::new()
             .create(true)
@@ -112,19 +304,52 @@ impl JsonWriter<'_> {
             .truncate(true)
             .open(format!("{}/{}.json", "json", self.filename))?;
 
-        file.write_all(self.json.as_bytes())?;
+        file.write_all(json.as_bytes())?;
         Ok(())
     }
+
+    /// Format the value with the given indentation level
+    fn format(f: &mut fmt::Formatter, value: &JsonValue, indent: usize) -> fmt::Result {
+        let pad = "    ".repeat(indent + 1);
+        match value {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) if n.is_finite() => write!(f, "{}", n),
+            JsonValue::Number(_) => write!(f, "null"),
+            JsonValue::String(s) => write!(f, "{}", quote(s)),
+            JsonValue::Array(a) if a.is_empty() => write!(f, "[]"),
+            JsonValue::Array(a) => {
+                writeln!(f, "[")?;
+                for (i, v) in a.iter().enumerate() {
+                    write!(f, "{}", pad)?;
+                    JsonWriter::format(f, v, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < a.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", "    ".repeat(indent))
+            }
+            JsonValue::Object(o) if o.is_empty() => write!(f, "{{}}"),
+            JsonValue::Object(o) => {
+                writeln!(f, "{{")?;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    write!(f, "{}{}: ", pad, quote(k))?;
+                    JsonWriter::format(f, v, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < o.len() { "," } else { "" })?;
+                }
+                write!(f, "{}}}", "    ".repeat(indent))
+            }
+        }
+    }
 }
 
 impl JsonWritable for dyn Component {
     fn to_json(&self) -> Result<(), String> {
-        let mut writer = JsonWriter::new(self.name().as_str());
-        writer.add_key("name", self.name());
-        writer.add_key("type", format!("{}", self.r#type()));
+        let name = self.name();
+        let mut writer = JsonWriter::new(&name);
+        writer.add_key("name", name.as_str());
+        writer.add_key("type", self.type_name());
         match writer.write() {
             Ok(_) => Ok(()),
-            Err(e) => Err(e.to_string())
+            Err(e) => Err(e.to_string()),
         }
     }
 }
@@ -138,7 +363,132 @@ impl JsonReadable for CircuitBreaker {
         if r#type == "CircuitBreaker" {
             Ok(Box::new(CircuitBreaker::new(name)))
         } else {
-            Err(format!("Invalid json: component type CircuitBreaker expected in file {}", filename))
+            Err(format!(
+                "Invalid json: component type CircuitBreaker expected in file {}",
+                filename
+            ))
+        }
+    }
+}
+
+/// A json document, used to (de)serialize whole networks. Documents are parsed and formatted by the same reader and writer as the component files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    /// null
+    Null,
+    /// true or false
+    Bool(bool),
+    /// Any number
+    Number(f64),
+    /// String
+    String(String),
+    /// Array of values
+    Array(Vec<JsonValue>),
+    /// Object with its keys in document order
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parse a json document
+    pub fn parse(json: &str) -> Result<JsonValue, String> {
+        JsonReader::parse(json)
+    }
+
+    /// Get the value of a key if this is an object containing it
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a key, or an error naming the missing key
+    pub(crate) fn field(&self, key: &str) -> Result<&JsonValue, String> {
+        self.get(key)
+            .ok_or(format!("Invalid json: key {} not found", key))
+    }
+
+    /// Return the string if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Return the number if this is a number
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Return the boolean if this is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Return the elements if this is an array
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Return the entries if this is an object
+    pub fn as_object(&self) -> Option<&Vec<(String, JsonValue)>> {
+        match self {
+            JsonValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// True if this is null
+    pub fn is_null(&self) -> bool {
+        *self == JsonValue::Null
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        JsonWriter::format(f, self, 0)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> JsonValue {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(n: f64) -> JsonValue {
+        JsonValue::Number(n)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> JsonValue {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> JsonValue {
+        JsonValue::String(s)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(o: Option<T>) -> JsonValue {
+        match o {
+            Some(v) => v.into(),
+            None => JsonValue::Null,
         }
     }
 }
@@ -152,12 +502,44 @@ mod tests {
         // todo test reading invalid json files
     }
 
+    #[test]
+    fn json_value() {
+        let json =
+            r#"{"name": "net \"1\"", "values": [1, -2.5e1, true, null], "empty": {}, "ws": [ ] }"#;
+        let value = JsonValue::parse(json).unwrap();
+        assert_eq!(value.get("name").unwrap().as_str(), Some("net \"1\""));
+        let values = value.get("values").unwrap().as_array().unwrap();
+        assert_eq!(values[1].as_f64(), Some(-25f64));
+        assert_eq!(values[2].as_bool(), Some(true));
+        assert!(values[3].is_null());
+        assert_eq!(JsonValue::parse(&value.to_string()).unwrap(), value);
+
+        assert!(JsonValue::parse("{\"a\": }").is_err());
+        assert!(JsonValue::parse("[1, 2").is_err());
+        assert!(JsonValue::parse("1 2").is_err());
+        assert_eq!(
+            JsonValue::parse("\"\\u00e9\\n\"").unwrap(),
+            JsonValue::from("é\n")
+        );
+        assert_eq!(
+            JsonValue::parse("\"\\uD83D\\uDE00\"").unwrap(),
+            JsonValue::from("\u{1F600}")
+        );
+        assert_eq!(
+            JsonValue::parse(&quote("\u{1F600}")).unwrap(),
+            JsonValue::from("\u{1F600}")
+        );
+        assert!(JsonValue::parse("\"\\uD83D\"").is_err());
+        assert!(JsonValue::parse("\"\\uD83D\\u0041\"").is_err());
+        assert!(JsonValue::parse("\"\\u00e\"").is_err());
+    }
+
     #[test]
     fn json_component() {
         let cb: &dyn Component = &CircuitBreaker::new("test_cb");
         cb.to_json().unwrap();
         let cb = CircuitBreaker::from_json("test_cb").unwrap();
-        assert_eq!(cb.name(), "test_cb");
+        assert_eq!(cb.name().as_str(), "test_cb");
         assert_eq!(cb.r#type(), ComponentType::CircuitBreaker);
 
         // todo test all components
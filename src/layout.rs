@@ -0,0 +1,96 @@
+/// Seed of the random number generator used for initial placement, fixed so layouts are reproducible
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Small xorshift random number generator, deterministic for a given seed
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Next random number in [0, 1)
+    fn next(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Force-directed (Fruchterman-Reingold) layout of a graph. Vertices without a position are placed randomly first; then connected vertices attract each other and all vertices repel each other for the given number of iterations. Returns the new position of every vertex.
+pub fn force_directed(
+    positions: &[Option<(f64, f64)>],
+    edges: &[(usize, usize)],
+    iterations: usize,
+) -> Vec<(f64, f64)> {
+    let n = positions.len();
+    let size = (n.max(1) as f64).sqrt() * 10f64;
+    let k = size / (n.max(1) as f64).sqrt();
+    let mut rng = Rng::new(SEED);
+    let mut pos: Vec<(f64, f64)> = positions
+        .iter()
+        .map(|p| p.unwrap_or_else(|| (rng.next() * size, rng.next() * size)))
+        .collect();
+
+    let mut temperature = size / 10f64;
+    let cooling = temperature / (iterations as f64 + 1f64);
+    for _ in 0..iterations {
+        let mut displacement = vec![(0f64, 0f64); n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let (dx, dy, d) = delta(pos[i], pos[j]);
+                let force = k * k / d;
+                displacement[i].0 += dx / d * force;
+                displacement[i].1 += dy / d * force;
+                displacement[j].0 -= dx / d * force;
+                displacement[j].1 -= dy / d * force;
+            }
+        }
+        for (i, j) in edges {
+            let (dx, dy, d) = delta(pos[*i], pos[*j]);
+            let force = d * d / k;
+            displacement[*i].0 -= dx / d * force;
+            displacement[*i].1 -= dy / d * force;
+            displacement[*j].0 += dx / d * force;
+            displacement[*j].1 += dy / d * force;
+        }
+        for (p, (dx, dy)) in pos.iter_mut().zip(displacement) {
+            let d = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+            let step = d.min(temperature);
+            p.0 += dx / d * step;
+            p.1 += dy / d * step;
+        }
+        temperature -= cooling;
+    }
+    pos
+}
+
+/// Return the x and y difference and the (non-zero) distance between two positions
+fn delta(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let d = (dx * dx + dy * dy).sqrt().max(0.01);
+    (dx, dy, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        delta(a, b).2
+    }
+
+    #[test]
+    fn layout_force_directed() {
+        // 0 - 1 - 2 form a chain, 3 is unconnected
+        let edges = [(0, 1), (1, 2)];
+        let pos = force_directed(&[None; 4], &edges, 50);
+        assert_eq!(pos.len(), 4);
+        assert_eq!(pos, force_directed(&[None; 4], &edges, 50));
+        assert!(distance(pos[0], pos[1]) < distance(pos[0], pos[3]));
+        assert!(distance(pos[1], pos[2]) < distance(pos[2], pos[3]));
+    }
+}
@@ -4,19 +4,37 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::broken_intra_doc_links)]
 
+/// Reusable bay templates
+pub mod bay;
+/// Time-series log of state changes
+pub mod changelog;
 /// CLI interface to interact with the component, node and network APIs
 pub mod cli;
 /// Component API
 pub mod component;
+/// Differences between networks
+pub mod diff;
+/// Network API error type
+pub mod error;
+/// Save/Load Networks, Nodes and Components as json files
+pub mod json;
+/// Automatic layout algorithms
+pub mod layout;
+/// Warnings for common modeling mistakes
+pub mod lint;
 /// Measurement struct
 pub mod measurement;
+/// Automatic component naming
+pub mod naming;
 /// Network API
 pub mod network;
 /// Node API
 pub mod node;
 /// Switchgear Position struct
 pub mod position;
+/// Single-line diagram SVG rendering
+pub mod svg;
 /// Component Terminal struct
 pub mod terminal;
-/// Save/Load Networks, Nodes and Components as json files
-pub mod json;
+/// Bus-branch topology reduction
+pub mod topology;
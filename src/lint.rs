@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// Severity of a [Lint]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// Unusual, but may be intended
+    Info,
+    /// Most likely a modeling mistake
+    Warning,
+}
+
+/// A suspicious but legal pattern found in a network, see [crate::network::Network::lint]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    severity: Severity,
+    component: String,
+    message: String,
+}
+
+impl Lint {
+    /// Constructor, the component is the one the lint is reported for
+    pub(crate) fn new(severity: Severity, component: &str, message: String) -> Lint {
+        Lint {
+            severity,
+            component: component.to_string(),
+            message,
+        }
+    }
+
+    /// Get severity
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Get the name of the component the lint is reported for
+    pub fn component(&self) -> &String {
+        &self.component
+    }
+
+    /// Get message
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}: {}", self.severity, self.message)
+    }
+}
@@ -1,34 +1,706 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+use super::component::Component;
+use super::json::JsonValue;
+
+/// Measurement engineering unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    /// No unit
+    Dimensionless,
+    /// Kilovolt
+    Kilovolt,
+    /// Ampere
+    Ampere,
+    /// Megawatt
+    Megawatt,
+    /// Hertz
+    Hertz,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unitstr = match self {
+            Unit::Dimensionless => "",
+            Unit::Kilovolt => "kV",
+            Unit::Ampere => "A",
+            Unit::Megawatt => "MW",
+            Unit::Hertz => "Hz",
+        };
+        write!(f, "{}", unitstr)
+    }
+}
+
+/// Measurement quality
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quality {
+    /// Value is within limits
+    Good,
+    /// Value is outside of the configured limits
+    Bad,
+}
+
+/// Action taken when an updated value falls outside of the measurement limits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitAction {
+    /// Store the value and set the quality to [Quality::Bad]
+    FlagBad,
+    /// Reject the value and return an error
+    Reject,
+}
+
+/// Filter applied to the samples of a measurement to smooth noisy values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterKind {
+    /// No filtering, the value is the latest sample
+    None,
+    /// Exponential moving average, the factor in (0, 1] is the weight of the latest sample
+    Exponential(f64),
+    /// Simple moving average over the given number of latest samples
+    MovingAverage(usize),
+}
+
+/// Default number of timestamped samples kept by a measurement, see [Measurement::update_at]
+pub const HISTORY_LEN: usize = 100;
+
 /// Measurement
+#[derive(Debug, Clone)]
 pub struct Measurement {
     value: f64,
+    raw: f64,
+    sample: f64,
+    scale: f64,
+    offset: f64,
+    filter: FilterKind,
+    samples: VecDeque<f64>,
+    history: VecDeque<(f64, f64)>,
+    history_len: usize,
+    quality: Quality,
+    min: Option<f64>,
+    max: Option<f64>,
+    limit_action: LimitAction,
+    unit: Unit,
 }
 
 impl Measurement {
-    /// Constructor
+    /// Constructor, dimensionless and without limits
     pub fn new() -> Measurement {
-        Measurement { value: 0f64 }
+        Measurement::with_unit(Unit::Dimensionless)
+    }
+
+    /// Constructor with the given unit, without limits
+    pub fn with_unit(unit: Unit) -> Measurement {
+        Measurement {
+            value: 0f64,
+            raw: 0f64,
+            sample: 0f64,
+            scale: 1f64,
+            offset: 0f64,
+            filter: FilterKind::None,
+            samples: VecDeque::new(),
+            history: VecDeque::new(),
+            history_len: HISTORY_LEN,
+            quality: Quality::Good,
+            min: None,
+            max: None,
+            limit_action: LimitAction::FlagBad,
+            unit,
+        }
+    }
+
+    /// Update value from a raw sample, which is scaled to engineering units and checked against the limits before filtering it
+    pub fn update(&mut self, raw: f64) -> Result<(), String> {
+        let value = raw * self.scale + self.offset;
+        if self.within_limits(value) {
+            self.quality = Quality::Good;
+        } else {
+            match self.limit_action {
+                LimitAction::FlagBad => self.quality = Quality::Bad,
+                LimitAction::Reject => {
+                    return Err(format!(
+                        "Measurement value {} is outside of limits {:?} - {:?}",
+                        value, self.min, self.max
+                    ))
+                }
+            }
+        }
+        self.raw = raw;
+        self.sample = value;
+        self.value = match self.filter {
+            FilterKind::None => value,
+            FilterKind::Exponential(factor) => factor * value + (1f64 - factor) * self.value,
+            FilterKind::MovingAverage(window) => {
+                self.samples.push_back(value);
+                if self.samples.len() > window {
+                    self.samples.pop_front();
+                }
+                self.samples.iter().sum::<f64>() / self.samples.len() as f64
+            }
+        };
+        Ok(())
+    }
+
+    /// Clear the value, filter state and history and restore good quality. The unit, limits, scaling, filter kind and history length are kept.
+    pub fn reset(&mut self) {
+        self.value = 0f64;
+        self.raw = 0f64;
+        self.sample = 0f64;
+        self.samples.clear();
+        self.history.clear();
+        self.quality = Quality::Good;
+    }
+
+    /// Update value like [Measurement::update] and record the scaled sample with its timestamp in seconds in the history
+    pub fn update_at(&mut self, time: f64, raw: f64) -> Result<(), String> {
+        self.update(raw)?;
+        self.history.push_back((time, self.sample));
+        while self.history.len() > self.history_len {
+            self.history.pop_front();
+        }
+        Ok(())
     }
 
-    /// Update value
-    pub fn update(&mut self, value: f64) {
-        self.value = value;
+    /// Get the recorded (time, value) samples, oldest first
+    pub fn history(&self) -> &VecDeque<(f64, f64)> {
+        &self.history
     }
 
-    /// Get current value
+    /// Set the number of samples kept in the history, dropping the oldest samples if needed
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history_len = len;
+        while self.history.len() > len {
+            self.history.pop_front();
+        }
+    }
+
+    /// Return the slope in units per second between the two most recent samples in the history. None with fewer than two samples or if they have the same timestamp.
+    pub fn rate_of_change(&self) -> Option<f64> {
+        let mut recent = self.history.iter().rev();
+        let (t1, v1) = recent.next()?;
+        let (t0, v0) = recent.next()?;
+        if t1 == t0 {
+            return None;
+        }
+        Some((v1 - v0) / (t1 - t0))
+    }
+
+    /// Get current value, filtered if a filter is set
     pub fn value(&self) -> f64 {
         self.value
     }
+
+    /// Get the latest sample as received, before scaling and filtering
+    pub fn raw_value(&self) -> f64 {
+        self.raw
+    }
+
+    /// Get the scale factor from raw samples to engineering units
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Get the offset from raw samples to engineering units
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Set the linear conversion of raw samples to engineering units, value = raw * scale + offset. Applies to samples received afterwards.
+    pub fn set_scaling(&mut self, scale: f64, offset: f64) {
+        self.scale = scale;
+        self.offset = offset;
+    }
+
+    /// Get the filter
+    pub fn filter(&self) -> FilterKind {
+        self.filter
+    }
+
+    /// Set the filter, restarting it from the latest sample. Errors if the exponential factor is not in (0, 1] or the moving average window is empty.
+    pub fn set_filter(&mut self, filter: FilterKind) -> Result<(), String> {
+        check_filter(filter)?;
+        self.filter = filter;
+        self.samples.clear();
+        if let FilterKind::MovingAverage(_) = filter {
+            self.samples.push_back(self.sample);
+        }
+        self.value = self.sample;
+        Ok(())
+    }
+
+    /// Get current quality
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Get unit
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Set the lower and upper limits, None means unlimited
+    pub fn set_limits(&mut self, min: Option<f64>, max: Option<f64>) {
+        self.min = min;
+        self.max = max;
+    }
+
+    /// Set the action taken when a value outside the limits is received
+    pub fn set_limit_action(&mut self, action: LimitAction) {
+        self.limit_action = action;
+    }
+
+    /// True if the given value is within the limits
+    fn within_limits(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl Measurement {
+    /// Serialize the full measurement state
+    pub(crate) fn to_json(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            ("value".to_string(), self.value.into()),
+            ("raw".to_string(), self.raw.into()),
+            ("sample".to_string(), self.sample.into()),
+            ("scale".to_string(), self.scale.into()),
+            ("offset".to_string(), self.offset.into()),
+            ("filter".to_string(), filter_to_json(self.filter)),
+            (
+                "samples".to_string(),
+                JsonValue::Array(self.samples.iter().map(|s| (*s).into()).collect()),
+            ),
+            (
+                "history".to_string(),
+                JsonValue::Array(
+                    self.history
+                        .iter()
+                        .map(|(t, v)| JsonValue::Array(vec![(*t).into(), (*v).into()]))
+                        .collect(),
+                ),
+            ),
+            ("history_len".to_string(), (self.history_len as f64).into()),
+            ("quality".to_string(), format!("{:?}", self.quality).into()),
+            ("min".to_string(), self.min.into()),
+            ("max".to_string(), self.max.into()),
+            (
+                "limit_action".to_string(),
+                format!("{:?}", self.limit_action).into(),
+            ),
+            ("unit".to_string(), format!("{:?}", self.unit).into()),
+        ])
+    }
+
+    /// Deserialize a measurement written by [Measurement::to_json]
+    pub(crate) fn from_json(json: &JsonValue) -> Result<Measurement, String> {
+        let string = |key: &str| -> Result<&str, String> {
+            match json.field(key)?.as_str() {
+                Some(s) => Ok(s),
+                None => Err(format!("Invalid json: {} is not a string", key)),
+            }
+        };
+        let value = match json.field("value")?.as_f64() {
+            Some(value) => value,
+            None => return Err("Invalid json: value is not a number".to_string()),
+        };
+        let quality = match string("quality")? {
+            "Good" => Quality::Good,
+            "Bad" => Quality::Bad,
+            s => return Err(format!("Invalid json: unknown quality {}", s)),
+        };
+        let limit_action = match string("limit_action")? {
+            "FlagBad" => LimitAction::FlagBad,
+            "Reject" => LimitAction::Reject,
+            s => return Err(format!("Invalid json: unknown limit action {}", s)),
+        };
+        let unit = match string("unit")? {
+            "Dimensionless" => Unit::Dimensionless,
+            "Kilovolt" => Unit::Kilovolt,
+            "Ampere" => Unit::Ampere,
+            "Megawatt" => Unit::Megawatt,
+            "Hertz" => Unit::Hertz,
+            s => return Err(format!("Invalid json: unknown unit {}", s)),
+        };
+        let samples = match json.get("samples").and_then(|s| s.as_array()) {
+            Some(samples) => samples.iter().filter_map(|s| s.as_f64()).collect(),
+            None => VecDeque::new(),
+        };
+        let mut history = VecDeque::new();
+        if let Some(samples) = json.get("history").and_then(|h| h.as_array()) {
+            for sample in samples {
+                match sample.as_array().map(|s| s.as_slice()) {
+                    Some([t, v]) => match (t.as_f64(), v.as_f64()) {
+                        (Some(t), Some(v)) => history.push_back((t, v)),
+                        _ => return Err("Invalid json: history sample is not a number".to_string()),
+                    },
+                    _ => {
+                        return Err(
+                            "Invalid json: history sample is not a [time, value] pair".to_string()
+                        )
+                    }
+                }
+            }
+        }
+        let history_len = match json.get("history_len").and_then(|l| l.as_f64()) {
+            Some(len) => len as usize,
+            None => HISTORY_LEN,
+        };
+        Ok(Measurement {
+            value,
+            raw: json.get("raw").and_then(|r| r.as_f64()).unwrap_or(value),
+            sample: json.get("sample").and_then(|s| s.as_f64()).unwrap_or(value),
+            scale: json.get("scale").and_then(|s| s.as_f64()).unwrap_or(1f64),
+            offset: json.get("offset").and_then(|o| o.as_f64()).unwrap_or(0f64),
+            filter: match json.get("filter") {
+                Some(filter) => filter_from_json(filter)?,
+                None => FilterKind::None,
+            },
+            samples,
+            history,
+            history_len,
+            quality,
+            min: json.field("min")?.as_f64(),
+            max: json.field("max")?.as_f64(),
+            limit_action,
+            unit,
+        })
+    }
+}
+
+/// Serialize a filter as null or an object with its kind and parameter
+fn filter_to_json(filter: FilterKind) -> JsonValue {
+    let (kind, parameter) = match filter {
+        FilterKind::None => return JsonValue::Null,
+        FilterKind::Exponential(factor) => ("Exponential", factor),
+        FilterKind::MovingAverage(window) => ("MovingAverage", window as f64),
+    };
+    JsonValue::Object(vec![
+        ("kind".to_string(), kind.into()),
+        ("parameter".to_string(), parameter.into()),
+    ])
+}
+
+/// Check the filter parameters, see [Measurement::set_filter]
+fn check_filter(filter: FilterKind) -> Result<(), String> {
+    match filter {
+        FilterKind::Exponential(factor) if !(factor > 0f64 && factor <= 1f64) => Err(format!(
+            "Exponential filter factor {} is not in (0, 1]",
+            factor
+        )),
+        FilterKind::MovingAverage(0) => {
+            Err("Moving average filter window must hold at least one sample".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Deserialize a filter written by [filter_to_json], with the same checks as [Measurement::set_filter]
+fn filter_from_json(json: &JsonValue) -> Result<FilterKind, String> {
+    if json.is_null() {
+        return Ok(FilterKind::None);
+    }
+    let parameter = match json.field("parameter")?.as_f64() {
+        Some(parameter) => parameter,
+        None => return Err("Invalid json: filter parameter is not a number".to_string()),
+    };
+    let filter = match json.field("kind")?.as_str() {
+        Some("Exponential") => FilterKind::Exponential(parameter),
+        Some("MovingAverage") if parameter >= 0f64 && parameter.fract() == 0f64 => {
+            FilterKind::MovingAverage(parameter as usize)
+        }
+        Some("MovingAverage") => {
+            return Err(format!(
+                "Invalid json: moving average window {} is not a whole number",
+                parameter
+            ))
+        }
+        _ => return Err("Invalid json: unknown filter kind".to_string()),
+    };
+    match check_filter(filter) {
+        Ok(()) => Ok(filter),
+        Err(e) => Err(format!("Invalid json: {}", e)),
+    }
+}
+
+impl Default for Measurement {
+    fn default() -> Measurement {
+        Measurement::new()
+    }
+}
+
+/// Measurement computed on demand from the latest values of two source measurements
+pub struct DerivedMeasurement {
+    sources: [Rc<dyn Component>; 2],
+    unit: Unit,
+    function: Box<dyn Fn(f64, f64) -> f64>,
+}
+
+impl DerivedMeasurement {
+    /// Constructor, computes its value from the measurements of the given components with the given function
+    pub fn new(
+        a: Rc<dyn Component>,
+        b: Rc<dyn Component>,
+        unit: Unit,
+        function: impl Fn(f64, f64) -> f64 + 'static,
+    ) -> DerivedMeasurement {
+        DerivedMeasurement {
+            sources: [a, b],
+            unit,
+            function: Box::new(function),
+        }
+    }
+
+    /// Three-phase active power in MW from a line voltage in kV and a current in A, P = sqrt(3) * V * I * cos(phi)
+    pub fn power(
+        voltage: Rc<dyn Component>,
+        current: Rc<dyn Component>,
+        cos_phi: f64,
+    ) -> DerivedMeasurement {
+        DerivedMeasurement::new(voltage, current, Unit::Megawatt, move |v, i| {
+            3f64.sqrt() * v * i * cos_phi / 1000f64
+        })
+    }
+
+    /// Compute the value from the latest source values, errors if a source has no measurement
+    pub fn value(&self) -> Result<f64, String> {
+        let a = self.sources[0].value()?;
+        let b = self.sources[1].value()?;
+        Ok((self.function)(a, b))
+    }
+
+    /// Return [Quality::Bad] if either source is bad or has no measurement
+    pub fn quality(&self) -> Quality {
+        let good = self.sources.iter().all(|c| match c.measurement() {
+            Ok(m) => m.borrow().quality() == Quality::Good,
+            Err(_) => false,
+        });
+        if good {
+            Quality::Good
+        } else {
+            Quality::Bad
+        }
+    }
+
+    /// Get unit
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::component::{CircuitBreaker, CurrentTransformer, VoltageTransformer};
 
     #[test]
     fn measurement_update() {
         let mut m = Measurement::new();
         assert_eq!(m.value(), 0f64);
-        m.update(10.516);
+        m.update(10.516).unwrap();
         assert_eq!(m.value(), 10.516);
     }
+
+    #[test]
+    fn measurement_derived() {
+        let vt: Rc<dyn Component> = Rc::new(VoltageTransformer::new("vt"));
+        let ct: Rc<dyn Component> = Rc::new(CurrentTransformer::new("ct"));
+        let p = DerivedMeasurement::power(vt.clone(), ct.clone(), 0.9);
+        assert_eq!(p.unit(), Unit::Megawatt);
+        assert_eq!(p.value().unwrap(), 0f64);
+
+        vt.update(150f64).unwrap();
+        ct.update(400f64).unwrap();
+        assert!((p.value().unwrap() - 93.53074360871938).abs() < 1e-9);
+        assert_eq!(p.quality(), Quality::Good);
+        ct.update(200f64).unwrap();
+        assert!((p.value().unwrap() - 46.76537180435969).abs() < 1e-9);
+
+        ct.measurement()
+            .unwrap()
+            .borrow_mut()
+            .set_limits(None, Some(100f64));
+        ct.update(200f64).unwrap();
+        assert_eq!(p.quality(), Quality::Bad);
+
+        let sum = DerivedMeasurement::new(vt.clone(), vt, Unit::Kilovolt, |a, b| a + b);
+        assert_eq!(sum.value().unwrap(), 300f64);
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+        let invalid = DerivedMeasurement::new(cb, ct, Unit::Dimensionless, |a, b| a * b);
+        assert!(invalid.value().is_err());
+        assert_eq!(invalid.quality(), Quality::Bad);
+    }
+
+    #[test]
+    fn measurement_rate_of_change() {
+        let mut m = Measurement::with_unit(Unit::Hertz);
+        assert_eq!(m.rate_of_change(), None);
+        m.update_at(10f64, 50f64).unwrap();
+        assert_eq!(m.rate_of_change(), None);
+        m.update_at(10.5, 49.8).unwrap();
+        assert!((m.rate_of_change().unwrap() + 0.4).abs() < 1e-9);
+        m.update_at(12.5, 50.2).unwrap();
+        assert!((m.rate_of_change().unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(m.history().len(), 3);
+        assert_eq!(m.value(), 50.2);
+
+        // Untimed updates are not recorded and rejected values do not enter the history
+        m.update(60f64).unwrap();
+        m.set_limits(None, Some(55f64));
+        m.set_limit_action(LimitAction::Reject);
+        assert!(m.update_at(13f64, 70f64).is_err());
+        assert_eq!(m.history().back(), Some(&(12.5, 50.2)));
+
+        m.set_history_len(1);
+        assert_eq!(m.history().len(), 1);
+        assert_eq!(m.rate_of_change(), None);
+        m.update_at(12.5, 51f64).unwrap();
+        assert_eq!(m.rate_of_change(), None);
+    }
+
+    #[test]
+    fn measurement_scaling() {
+        let mut m = Measurement::with_unit(Unit::Kilovolt);
+        assert_eq!((m.scale(), m.offset()), (1f64, 0f64));
+
+        // Per unit on a 150 kV base
+        m.set_scaling(150f64, 0f64);
+        m.update(1.02).unwrap();
+        assert!((m.value() - 153f64).abs() < 1e-9);
+        assert_eq!(m.raw_value(), 1.02);
+
+        // 4-20 mA transducer for 0-200 kV
+        m.set_scaling(12.5, -50f64);
+        m.set_limits(Some(0f64), Some(180f64));
+        m.update_at(1f64, 16f64).unwrap();
+        assert_eq!(m.value(), 150f64);
+        assert_eq!(m.raw_value(), 16f64);
+        assert_eq!(m.history().back(), Some(&(1f64, 150f64)));
+        m.update_at(2f64, 20f64).unwrap();
+        assert_eq!(m.quality(), Quality::Bad);
+        assert_eq!(m.rate_of_change(), Some(50f64));
+
+        m.set_filter(FilterKind::Exponential(0.5)).unwrap();
+        assert_eq!(m.value(), 200f64);
+        let copy = Measurement::from_json(&m.to_json()).unwrap();
+        assert_eq!((copy.scale(), copy.offset()), (12.5, -50f64));
+        assert_eq!(copy.raw_value(), 20f64);
+    }
+
+    #[test]
+    fn measurement_json() {
+        let mut m = Measurement::with_unit(Unit::Kilovolt);
+        m.set_limits(Some(100f64), Some(170f64));
+        m.set_history_len(3);
+        for (t, v) in [(0f64, 150f64), (0.25, 151.5), (1.5, 149f64), (2f64, 180f64)] {
+            m.update_at(t, v).unwrap();
+        }
+        let copy =
+            Measurement::from_json(&JsonValue::parse(&m.to_json().to_string()).unwrap()).unwrap();
+        assert_eq!(copy.quality(), Quality::Bad);
+        assert_eq!(copy.unit(), Unit::Kilovolt);
+        assert_eq!(copy.value(), 180f64);
+        assert_eq!(copy.history(), m.history());
+        assert_eq!(copy.history().front(), Some(&(0.25, 151.5)));
+        assert_eq!(copy.rate_of_change(), m.rate_of_change());
+
+        // The history length is kept as well
+        let mut copy = copy;
+        copy.update_at(3f64, 150f64).unwrap();
+        assert_eq!(copy.history().len(), 3);
+        assert_eq!(copy.quality(), Quality::Good);
+    }
+
+    #[test]
+    fn measurement_filter() {
+        let mut m = Measurement::new();
+        m.set_filter(FilterKind::Exponential(0.5)).unwrap();
+        m.update(100f64).unwrap();
+        assert_eq!(m.value(), 50f64);
+        assert_eq!(m.raw_value(), 100f64);
+        for _ in 0..20 {
+            m.update(100f64).unwrap();
+        }
+        assert!((m.value() - 100f64).abs() < 1e-3);
+
+        // Switching filters restarts from the latest sample
+        m.set_filter(FilterKind::MovingAverage(4)).unwrap();
+        assert_eq!(m.value(), 100f64);
+        m.update(0f64).unwrap();
+        assert_eq!(m.value(), 50f64);
+        m.update(0f64).unwrap();
+        m.update(0f64).unwrap();
+        assert_eq!(m.value(), 25f64);
+        m.update(0f64).unwrap();
+        assert_eq!(m.value(), 0f64);
+        assert_eq!(m.raw_value(), 0f64);
+
+        m.update(8f64).unwrap();
+        m.set_filter(FilterKind::None).unwrap();
+        assert_eq!(m.value(), 8f64);
+        assert!(m.set_filter(FilterKind::Exponential(0f64)).is_err());
+        assert!(m.set_filter(FilterKind::Exponential(1.5)).is_err());
+        assert!(m.set_filter(FilterKind::MovingAverage(0)).is_err());
+        assert_eq!(m.filter(), FilterKind::None);
+
+        m.set_filter(FilterKind::MovingAverage(2)).unwrap();
+        m.update(4f64).unwrap();
+        let copy = Measurement::from_json(&m.to_json()).unwrap();
+        assert_eq!(copy.filter(), FilterKind::MovingAverage(2));
+        assert_eq!(copy.value(), 6f64);
+        m.update(2f64).unwrap();
+        let mut copy = copy;
+        copy.update(2f64).unwrap();
+        assert_eq!(copy.value(), m.value());
+
+        // Stored filters are checked like set_filter
+        for (kind, parameter) in [
+            ("MovingAverage", 0f64),
+            ("MovingAverage", 1.5),
+            ("Exponential", 0f64),
+            ("Exponential", 2f64),
+        ] {
+            let mut json = m.to_json();
+            if let JsonValue::Object(entries) = &mut json {
+                for (key, value) in entries.iter_mut() {
+                    if key == "filter" {
+                        *value = JsonValue::Object(vec![
+                            ("kind".to_string(), kind.into()),
+                            ("parameter".to_string(), parameter.into()),
+                        ]);
+                    }
+                }
+            }
+            assert!(Measurement::from_json(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn measurement_unit() {
+        assert_eq!(Measurement::new().unit(), Unit::Dimensionless);
+        let m = Measurement::with_unit(Unit::Kilovolt);
+        assert_eq!(m.unit(), Unit::Kilovolt);
+        assert_eq!(m.unit().to_string(), "kV");
+    }
+
+    #[test]
+    fn measurement_limits() {
+        let mut m = Measurement::new();
+        m.set_limits(Some(0f64), Some(100f64));
+
+        m.update(50f64).unwrap();
+        assert_eq!(m.quality(), Quality::Good);
+        m.update(150f64).unwrap();
+        assert_eq!(m.value(), 150f64);
+        assert_eq!(m.quality(), Quality::Bad);
+        m.update(100f64).unwrap();
+        assert_eq!(m.quality(), Quality::Good);
+        m.update(-1f64).unwrap();
+        assert_eq!(m.quality(), Quality::Bad);
+
+        m.set_limit_action(LimitAction::Reject);
+        m.update(10f64).unwrap();
+        assert!(m.update(101f64).is_err());
+        assert_eq!(m.value(), 10f64);
+        assert_eq!(m.quality(), Quality::Good);
+    }
 }
@@ -0,0 +1,48 @@
+use super::component::ComponentType;
+
+/// Scheme for naming automatically created components, see [crate::network::Network::set_naming_policy]
+///
+/// A name is a prefix followed by a counter. Components of a type with its own prefix are counted per prefix, all other components share the default prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamingPolicy {
+    default_prefix: String,
+    first: usize,
+    prefixes: Vec<(ComponentType, String)>,
+}
+
+impl NamingPolicy {
+    /// Constructor, names all types with the default prefix and counts from first
+    pub fn new(default_prefix: &str, first: usize) -> NamingPolicy {
+        NamingPolicy {
+            default_prefix: default_prefix.to_string(),
+            first,
+            prefixes: vec![],
+        }
+    }
+
+    /// Use the given prefix for components of the given type, replacing an earlier prefix for that type
+    pub fn set_prefix(&mut self, r#type: ComponentType, prefix: &str) {
+        self.prefixes.retain(|(t, _)| *t != r#type);
+        self.prefixes.push((r#type, prefix.to_string()));
+    }
+
+    /// Return the prefix used for components of the given type
+    pub fn prefix(&self, r#type: &ComponentType) -> &String {
+        match self.prefixes.iter().find(|(t, _)| t == r#type) {
+            Some((_, prefix)) => prefix,
+            None => &self.default_prefix,
+        }
+    }
+
+    /// Return the number the counter of each prefix starts at
+    pub fn first(&self) -> usize {
+        self.first
+    }
+}
+
+impl Default for NamingPolicy {
+    /// Names components "component_0", "component_1", ... regardless of their type
+    fn default() -> NamingPolicy {
+        NamingPolicy::new("component_", 0)
+    }
+}
@@ -1,15 +1,209 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::rc::Rc;
 
+#[cfg(feature = "petgraph")]
+use petgraph::graph::Graph;
+
+use super::bay::Bay;
+use super::changelog::{ChangeEntry, ChangeLog, ChangeValue};
 use super::component::*;
+use super::diff::NetworkDiff;
+use super::error::NetworkError;
+use super::json::JsonValue;
+use super::layout::force_directed;
+use super::lint::{Lint, Severity};
+use super::measurement::{Measurement, Quality, Unit};
+use super::naming::NamingPolicy;
 use super::node::*;
+use super::position::{AutoReclose, PositionState, SwitchgearPosition};
+use super::svg;
+use super::terminal::Phase;
+use super::topology::BusBranchModel;
+
+/// Build a [Network] from a compact description of its nodes and components, evaluating to `Result<Network, NetworkError>`.
+/// Components are given by their struct name and connected to the listed nodes in terminal order.
+///
+/// ```
+/// use hvnv::network;
+///
+/// let net = network! {
+///     name: "station",
+///     nodes: ["bus", "feeder"],
+///     components: [
+///         CircuitBreaker("cb1") => ["bus", "feeder"],
+///         VoltageTransformer("vt1") => ["feeder"],
+///     ],
+/// }
+/// .unwrap();
+/// assert_eq!(net.component_count(), 2);
+/// ```
+#[macro_export]
+macro_rules! network {
+    (
+        name: $name:expr,
+        nodes: [$($node:expr),* $(,)?],
+        components: [$($type:ident($component:expr) => [$($terminal:expr),* $(,)?]),* $(,)?] $(,)?
+    ) => {
+        (|| -> ::std::result::Result<$crate::network::Network, $crate::error::NetworkError> {
+            let net = $crate::network::Network::new($name);
+            $(net.create_node($node)?;)*
+            $(
+                net.create_component::<$crate::component::$type>($component)?;
+                let mut _terminal = 0;
+                $(
+                    net.connect($terminal, $component, _terminal)?;
+                    _terminal += 1;
+                )*
+            )*
+            Ok(net)
+        })()
+    };
+}
+
+/// A switching operation on a named switchgear component
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitchOp {
+    /// Open the switchgear
+    Open(String),
+    /// Close the switchgear
+    Close(String),
+}
+
+impl SwitchOp {
+    /// Return the name of the switchgear component to operate
+    pub fn component(&self) -> &String {
+        match self {
+            SwitchOp::Open(name) => name,
+            SwitchOp::Close(name) => name,
+        }
+    }
+
+    /// Return the position state after the operation
+    pub fn target(&self) -> PositionState {
+        match self {
+            SwitchOp::Open(_) => PositionState::Open,
+            SwitchOp::Close(_) => PositionState::Closed,
+        }
+    }
+}
+
+/// A new measurement value for a named component. Holds no references into the network, so updates can be collected on other threads and applied in a batch with [Network::apply_updates].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasurementUpdate {
+    /// Name of the measuring component
+    pub component: String,
+    /// New measurement value
+    pub value: f64,
+}
+
+/// A full station state received in one message, applied with [Network::apply_snapshot]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StationSnapshot {
+    /// Switchgear position state by component name
+    pub positions: HashMap<String, PositionState>,
+    /// Integrated earthing switch position state by component name, see [Component::earth_position]
+    pub earth_positions: HashMap<String, PositionState>,
+    /// Measurement value by component name
+    pub measurements: HashMap<String, f64>,
+}
+
+/// Energization status of a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnergyStatus {
+    /// Energized from a source node
+    Energized,
+    /// Not energized and connected to earth through a closed earthing switch
+    Earthed,
+    /// Neither energized nor earthed
+    Dead,
+    /// Neither energized nor earthed, but switchgear in transit may energize or earth it
+    Unknown,
+}
+
+/// Visitor over all elements of a [Network], see [Network::accept]
+pub trait NetworkVisitor {
+    /// Called once for every node
+    fn visit_node(&mut self, _node: &Node) {}
+    /// Called once for every component
+    fn visit_component(&mut self, _component: &dyn Component) {}
+}
+
+/// Element yielded by an [EnergizationWalk]
+#[derive(Clone)]
+pub enum WalkItem {
+    /// A node reached by the walk
+    Node(Rc<Node>),
+    /// A conducting component passed by the walk
+    Component(Rc<dyn Component>),
+}
+
+/// Lazy breadth-first walk from a node through the conducting components, see [Component::conducts]. Yields the start node, then alternately the conducting components attached to reached nodes and the nodes beyond them, each once.
+pub struct EnergizationWalk {
+    queue: VecDeque<WalkItem>,
+    nodes: Vec<Rc<Node>>,
+    components: Vec<Rc<dyn Component>>,
+}
+
+impl EnergizationWalk {
+    /// Constructor, starts the walk at the given node
+    pub fn new(start: Rc<Node>) -> EnergizationWalk {
+        EnergizationWalk {
+            queue: VecDeque::from(vec![WalkItem::Node(start.clone())]),
+            nodes: vec![start],
+            components: vec![],
+        }
+    }
+}
+
+impl Iterator for EnergizationWalk {
+    type Item = WalkItem;
+
+    fn next(&mut self) -> Option<WalkItem> {
+        let item = self.queue.pop_front()?;
+        match &item {
+            WalkItem::Node(n) => {
+                for c in n.components() {
+                    if c.conducts() && !self.components.iter().any(|x| Rc::ptr_eq(x, &c)) {
+                        self.components.push(c.clone());
+                        self.queue.push_back(WalkItem::Component(c));
+                    }
+                }
+            }
+            WalkItem::Component(c) => {
+                for n in c.terminals().filter_map(|t| t.borrow().get_node().ok()) {
+                    if !self.nodes.iter().any(|x| Rc::ptr_eq(x, &n)) {
+                        self.nodes.push(n.clone());
+                        self.queue.push_back(WalkItem::Node(n));
+                    }
+                }
+            }
+        }
+        Some(item)
+    }
+}
+
+/// Shared reference to a [Node], used as graph vertex weight
+pub type NodeRef = Rc<Node>;
+/// Shared reference to a [Component], used as graph edge weight
+pub type ComponentRef = Rc<dyn Component>;
 
-/// A network which contains nodes and components
+/// A network which contains nodes and components. State is kept in [RefCell]s, so operating on a component while a conflicting borrow is held panics; the `try_*` methods return [NetworkError::Busy] instead.
 pub struct Network {
     name: String,
     nodes: RefCell<Vec<Rc<Node>>>,
     components: RefCell<Vec<Rc<dyn Component>>>,
+    time: Cell<f64>,
+    next_id: Cell<u32>,
+    changelog: RefCell<ChangeLog>,
+    maintenance_threshold: Cell<Option<u32>>,
+    naming: RefCell<NamingPolicy>,
+    name_counters: RefCell<HashMap<String, usize>>,
+    ground: Rc<Node>,
+    custom_types: RefCell<Vec<CustomType>>,
 }
 
 impl Network {
@@ -19,6 +213,14 @@ impl Network {
             name: name.to_string(),
             nodes: RefCell::new(vec![]),
             components: RefCell::new(vec![]),
+            time: Cell::new(0f64),
+            next_id: Cell::new(0),
+            changelog: RefCell::new(ChangeLog::new()),
+            maintenance_threshold: Cell::new(None),
+            naming: RefCell::new(NamingPolicy::default()),
+            name_counters: RefCell::new(HashMap::new()),
+            ground: Rc::new(Node::new("ground")),
+            custom_types: RefCell::new(vec![CustomType::generic()]),
         }
     }
 
@@ -27,14 +229,17 @@ impl Network {
         &self.name
     }
 
-    /// Check if a given name already exists in the network. Used to enforce unique names between all nodes and components within the network.
+    /// Check if a given name already exists in the network. Used to enforce unique names between all nodes and components within the network; the name of the [Network::ground] node is reserved.
     fn check_name(&self, name: &str) -> Result<(), ()> {
+        if *self.ground.name() == name {
+            return Err(());
+        }
         let node_index = self.nodes.borrow().iter().position(|x| x.name() == name);
         let component_index = self
             .components
             .borrow()
             .iter()
-            .position(|x| x.name() == name);
+            .position(|x| *x.name() == name);
         match (node_index, component_index) {
             (Some(_), Some(_)) => Err(()),
             (Some(_), None) => Err(()),
@@ -49,6 +254,7 @@ impl Network {
             Err(_) => Err(format!("Failed to create node {} - A node or component with this name already exists in network {}", name, self.name())),
             Ok(()) => {
                 let n = Rc::new(Node::new(name));
+                n.set_id(self.take_id());
                 self.nodes.borrow_mut().push(n);
                 Ok(())
             }
@@ -71,6 +277,93 @@ impl Network {
         }
     }
 
+    /// Return the nodes without any attached components
+    pub fn orphan_nodes(&self) -> Vec<Rc<Node>> {
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|n| n.degree() == 0)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove the nodes without any attached components, returning their names
+    pub fn prune_orphans(&self) -> Vec<String> {
+        let orphans: Vec<String> = self
+            .orphan_nodes()
+            .iter()
+            .map(|n| n.name().clone())
+            .collect();
+        self.nodes.borrow_mut().retain(|n| n.degree() > 0);
+        orphans
+    }
+
+    /// Merge node b into node a. All component terminals connected to b are connected to a instead, after which b is removed from the network. Merging is refused, and nothing changes, if a component is connected to both nodes, as merging would short it.
+    pub fn merge_nodes(&self, a: &str, b: &str) -> Result<Rc<Node>, NetworkError> {
+        if a == b {
+            return Err(NetworkError::Other(format!(
+                "Failed to merge node {} - Can not merge a node with itself",
+                a
+            )));
+        }
+        let node_a = self
+            .get_node(a)
+            .map_err(|_| NetworkError::NotFound(a.to_string()))?;
+        let node_b = self
+            .get_node(b)
+            .map_err(|_| NetworkError::NotFound(b.to_string()))?;
+        let on = |c: &Rc<dyn Component>, node: &Rc<Node>| {
+            c.terminals().any(|t| match t.borrow().get_node() {
+                Ok(n) => Rc::ptr_eq(&n, node),
+                Err(_) => false,
+            })
+        };
+        if let Some(c) = node_b.components().iter().find(|c| on(c, &node_a)) {
+            return Err(NetworkError::Other(format!(
+                "Failed to merge node {} into {} - Component {} is connected to both nodes",
+                b,
+                a,
+                c.name()
+            )));
+        }
+        for c in node_b.components() {
+            for t in c.terminals() {
+                let mut t = t.borrow_mut();
+                match t.get_node() {
+                    Ok(n) if Rc::ptr_eq(&n, &node_b) => {
+                        t.disconnect()?;
+                        t.connect(node_a.clone())?;
+                    }
+                    _ => {}
+                }
+            }
+            node_a.add_component(c)?;
+        }
+        node_b.clear();
+        self.remove_node(b)?;
+        Ok(node_a)
+    }
+
+    /// Tie two nodes together with a [Jumper] named `jumper_<node_a>_<node_b>`, so they are electrically the same node for energization. Use [Network::merge_nodes] to merge them into one node instead.
+    pub fn tie(&self, node_a: &str, node_b: &str) -> Result<Rc<dyn Component>, NetworkError> {
+        if node_a == node_b {
+            return Err(NetworkError::Other(format!(
+                "Failed to tie node {} - Can not tie a node to itself",
+                node_a
+            )));
+        }
+        let nodes = [self.get_node(node_a)?, self.get_node(node_b)?];
+        let name = format!("jumper_{}_{}", node_a, node_b);
+        if self.check_name(&name).is_err() {
+            return Err(NetworkError::DuplicateName(name));
+        }
+        // Wired before registering, so a failed connection leaves no jumper behind
+        let jumper = ComponentType::Jumper.instantiate(&name);
+        connect_nodes(&jumper, &nodes)?;
+        self.register(jumper.clone());
+        Ok(jumper)
+    }
+
     /// Return the number of nodes in the network
     pub fn node_count(&self) -> usize {
         self.nodes.borrow().len()
@@ -97,36 +390,454 @@ impl Network {
         }
     }
 
+    /// Build a network from a list of (node, [ComponentType], node) edges. Nodes are created as needed, each edge creates a two-terminal component with a generated name connected between both nodes.
+    pub fn from_edges(edges: &[(&str, ComponentType, &str)]) -> Result<Network, NetworkError> {
+        Network::from_edges_with(NamingPolicy::default(), edges)
+    }
+
+    /// Build a network from a list of edges like [Network::from_edges], naming the components with the given [NamingPolicy]
+    pub fn from_edges_with(
+        policy: NamingPolicy,
+        edges: &[(&str, ComponentType, &str)],
+    ) -> Result<Network, NetworkError> {
+        let net = Network::new("network");
+        net.set_naming_policy(policy);
+        for (i, (node_a, r#type, node_b)) in edges.iter().enumerate() {
+            if net.terminal_count(*r#type) != 2 {
+                return Err(NetworkError::Unsupported(format!(
+                    "Failed to create edge {} - Components of type {} do not have two terminals",
+                    i, r#type
+                )));
+            }
+            net.add_edge(node_a, *r#type, node_b)?;
+        }
+        Ok(net)
+    }
+
+    /// Create a two-terminal component of the given type named by the [NamingPolicy] and connect it between the two named nodes, creating the nodes if they do not exist yet
+    pub fn add_edge(
+        &self,
+        node_a: &str,
+        r#type: ComponentType,
+        node_b: &str,
+    ) -> Result<Rc<dyn Component>, NetworkError> {
+        if self.terminal_count(r#type) != 2 {
+            return Err(NetworkError::Unsupported(format!(
+                "Components of type {} do not have two terminals",
+                r#type
+            )));
+        }
+        let name = self.auto_name(&r#type);
+        let c = self.instantiate(r#type, &name);
+        for node in [node_a, node_b] {
+            if self.get_node(node).is_err() {
+                self.create_node(node)?;
+            }
+        }
+        self.register(c.clone());
+        self.connect(node_a, &name, 0)?;
+        self.connect(node_b, &name, 1)?;
+        Ok(c)
+    }
+
+    /// Set the scheme used to name automatically created components and restart its counters. Names already in use are skipped, so automatic names stay unique.
+    pub fn set_naming_policy(&self, policy: NamingPolicy) {
+        *self.naming.borrow_mut() = policy;
+        self.name_counters.borrow_mut().clear();
+    }
+
+    /// Return the scheme used to name automatically created components
+    pub fn naming_policy(&self) -> NamingPolicy {
+        self.naming.borrow().clone()
+    }
+
+    /// Return the next unused name for a component of the given type according to the [NamingPolicy], advancing the counter of its prefix
+    pub(crate) fn auto_name(&self, r#type: &ComponentType) -> String {
+        let naming = self.naming.borrow();
+        let prefix = naming.prefix(r#type);
+        let mut counters = self.name_counters.borrow_mut();
+        let counter = counters.entry(prefix.clone()).or_insert(naming.first());
+        loop {
+            let name = format!("{}{}", prefix, counter);
+            *counter += 1;
+            if self.check_name(&name).is_ok() {
+                return name;
+            }
+        }
+    }
+
+    /// Walk every node and then every component once, in insertion order, calling the visitor for each
+    pub fn accept(&self, visitor: &mut impl NetworkVisitor) {
+        for n in self.nodes.borrow().iter() {
+            visitor.visit_node(n);
+        }
+        for c in self.components.borrow().iter() {
+            visitor.visit_component(c.as_ref());
+        }
+    }
+
+    /// Build a network from two csv tables; one with `name,type` rows for the components and one with `component,terminal,node` rows for the connections. Types are parsed with the [FromStr](std::str::FromStr) implementation of [ComponentType], nodes are created when first referenced and a header row is optional. Parse errors report the offending line.
+    pub fn from_csv(components_csv: &str, connections_csv: &str) -> Result<Network, NetworkError> {
+        let net = Network::new("network");
+        for row in csv_rows(components_csv, "name,type", 2) {
+            let (line, fields) = row?;
+            let r#type: ComponentType = fields[1]
+                .parse()
+                .map_err(|e| NetworkError::Parse(format!("Line {}: {}", line, e)))?;
+            if net.check_name(fields[0]).is_err() {
+                return Err(NetworkError::Parse(format!(
+                    "Line {}: duplicate name {}",
+                    line, fields[0]
+                )));
+            }
+            net.register(r#type.instantiate(fields[0]));
+        }
+        for row in csv_rows(connections_csv, "component,terminal,node", 3) {
+            let (line, fields) = row?;
+            let error = |msg: String| NetworkError::Parse(format!("Line {}: {}", line, msg));
+            let terminal: usize = fields[1]
+                .parse()
+                .map_err(|_| error(format!("invalid terminal index {}", fields[1])))?;
+            if net.get_node(fields[2]).is_err() {
+                net.create_node(fields[2]).map_err(error)?;
+            }
+            net.connect(fields[2], fields[0], terminal).map_err(error)?;
+        }
+        Ok(net)
+    }
+
+    /// Get references to all nodes with the given voltage level in kV
+    pub fn nodes_at_level(&self, kv: f64) -> Vec<Rc<Node>> {
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|n| n.voltage_level() == Some(kv))
+            .cloned()
+            .collect()
+    }
+
+    /// Register a user-defined component type with the given terminal count, whose components always conduct between their terminals if conducts is true and never otherwise. Components of the type are created with [Network::instantiate] or any method taking a [ComponentType], and the definition is saved with the network.
+    /// Errors with [NetworkError::DuplicateName] if the name is used by a built-in or already registered type, and with [NetworkError::Unsupported] if no terminals are given.
+    pub fn register_custom_type(
+        &self,
+        name: &str,
+        terminals: usize,
+        conducts: bool,
+    ) -> Result<ComponentType, NetworkError> {
+        if self.parse_type(name).is_ok() {
+            return Err(NetworkError::DuplicateName(format!(
+                "Component type {} already exists",
+                name
+            )));
+        }
+        if terminals == 0 {
+            return Err(NetworkError::Unsupported(format!(
+                "Component type {} needs at least one terminal",
+                name
+            )));
+        }
+        let mut types = self.custom_types.borrow_mut();
+        types.push(CustomType::new(name, terminals, conducts));
+        Ok(ComponentType::Custom(types.len() as u32 - 1))
+    }
+
+    /// Get the definition of a custom component type registered with this network, None for built-in types and unknown indices
+    pub fn custom_type(&self, r#type: ComponentType) -> Option<CustomType> {
+        match r#type {
+            ComponentType::Custom(index) => self.custom_types.borrow().get(index as usize).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Parse a component type like [std::str::FromStr], also accepting the names of the custom types registered with this network
+    pub fn parse_type(&self, s: &str) -> Result<ComponentType, NetworkError> {
+        if let Ok(r#type) = s.parse::<ComponentType>() {
+            return Ok(r#type);
+        }
+        match self
+            .custom_types
+            .borrow()
+            .iter()
+            .position(|t| t.name() == s)
+        {
+            Some(index) => Ok(ComponentType::Custom(index as u32)),
+            None => Err(NetworkError::Unsupported(format!(
+                "Unknown component type {}",
+                s
+            ))),
+        }
+    }
+
+    /// Return the number of terminals components of the given type should have, using the definitions of the custom types registered with this network, see [ComponentType::terminal_count]
+    pub fn terminal_count(&self, r#type: ComponentType) -> usize {
+        match self.custom_type(r#type) {
+            Some(definition) => definition.terminals(),
+            None => r#type.terminal_count(),
+        }
+    }
+
+    /// Construct a new component of the given type with the given name, using the definitions of the custom types registered with this network, see [ComponentType::instantiate]. The component is not added to the network.
+    pub fn instantiate(&self, r#type: ComponentType, name: &str) -> Rc<dyn Component> {
+        match (r#type, self.custom_type(r#type)) {
+            (ComponentType::Custom(index), Some(definition)) => {
+                Rc::new(CustomComponent::of_type(name, index, &definition))
+            }
+            _ => r#type.instantiate(name),
+        }
+    }
+
     /// Create a component of a given [ComponentType] with a given name, if the name is not already in use in this network
     pub fn create_component<T: 'static + Component>(&self, name: &str) -> Result<(), String> {
         match self.check_name(name) {
             Err(_) => Err(format!("Failed to create component {} - A node or component with this name already exists in network {}", name, self.name())),
             Ok(()) => {
-                let c = Rc::new(T::new(name));
-                self.components.borrow_mut().push(c);
+                self.register(Rc::new(T::new(name)));
                 Ok(())
             }
         }
     }
 
-    /// Remove a component with the given name, if it exists in the network
-    pub fn remove_component(&self, name: &str) -> Result<(), String> {
+    /// Assign the next unused id to a component and add it to the network
+    fn register(&self, c: Rc<dyn Component>) {
+        c.set_id(Some(self.take_id()));
+        if earths(&c) {
+            self.ground
+                .add_component(c.clone())
+                .expect("a newly registered component is not yet attached to ground");
+        }
+        self.components.borrow_mut().push(c);
+    }
+
+    /// Return the ground node of the network. Every earthing switch and disconnector with integrated earthing switch in the network is attached to it as a child, without using a terminal.
+    /// The ground node is not part of the node list, so it is not counted, looked up by name, saved or grouped into islands; earthing analyses and [Network::to_svg] reach ground through it.
+    pub fn ground(&self) -> Rc<Node> {
+        self.ground.clone()
+    }
+
+    /// Return the next unused id. Ids are shared by nodes and components and never reused, also not after removal.
+    fn take_id(&self) -> u32 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    /// Create a busbar split into sections; nodes `<name>_1` to `<name>_<sections>` joined in a row by open section switches `<name>_s1` to `<name>_s<sections - 1>` of the given switchgear type. [Network::reduce] treats the sections as one bus while the section switches are closed.
+    pub fn create_sectionalized_busbar(
+        &self,
+        name: &str,
+        sections: usize,
+        switch_type: ComponentType,
+    ) -> Result<(), NetworkError> {
+        let probe = switch_type.instantiate(name);
+        if probe.position().is_err() || switch_type.terminal_count() != 2 {
+            return Err(NetworkError::Unsupported(format!(
+                "Components of type {} can not be used as section switch",
+                switch_type
+            )));
+        }
+        if sections == 0 {
+            return Err(NetworkError::Unsupported(format!(
+                "Busbar {} needs at least one section",
+                name
+            )));
+        }
+        let node = |i: usize| format!("{}_{}", name, i);
+        let switch = |i: usize| format!("{}_s{}", name, i);
+        let names = (1..=sections).map(node).chain((1..sections).map(switch));
+        for n in names {
+            if self.check_name(&n).is_err() {
+                return Err(NetworkError::DuplicateName(n));
+            }
+        }
+        for i in 1..=sections {
+            self.create_node(&node(i))?;
+        }
+        for i in 1..sections {
+            self.register(switch_type.instantiate(&switch(i)));
+            self.connect(&node(i), &switch(i), 0)?;
+            self.connect(&node(i + 1), &switch(i), 1)?;
+        }
+        Ok(())
+    }
+
+    /// Add an instance of a bay template. Internal nodes and components are named `<prefix>_<name>` and every external terminal of the bay is connected to the network node given for it in `connections`, as (terminal, node) pairs.
+    /// Nothing is added if a terminal is not connected, a node does not exist, a name is already used or a connection fails.
+    pub fn add_bay(
+        &self,
+        bay: &Bay,
+        prefix: &str,
+        connections: &[(&str, &str)],
+    ) -> Result<(), NetworkError> {
+        let mut external = HashMap::new();
+        for terminal in bay.terminals() {
+            let node = match connections.iter().find(|(t, _)| t == terminal) {
+                Some((_, node)) => node,
+                None => {
+                    return Err(NetworkError::NotFound(format!(
+                        "Terminal {} of bay {} is not connected to a node",
+                        terminal,
+                        bay.name()
+                    )))
+                }
+            };
+            external.insert(terminal.clone(), node.to_string());
+            self.get_node(node)?;
+        }
+        let prefixed = |name: &String| format!("{}_{}", prefix, name);
+        let names = bay
+            .nodes()
+            .iter()
+            .chain(bay.components().iter().map(|(name, _, _)| name));
+        for name in names {
+            if self.check_name(&prefixed(name)).is_err() {
+                return Err(NetworkError::DuplicateName(prefixed(name)));
+            }
+        }
+        for node in bay.nodes() {
+            self.create_node(&prefixed(node))?;
+        }
+        let mut added = vec![];
+        for (name, r#type, nodes) in bay.components() {
+            let name = prefixed(name);
+            self.register(self.instantiate(*r#type, &name));
+            added.push(name.clone());
+            for (i, node) in nodes.iter().enumerate() {
+                let connected = match external.get(node) {
+                    Some(node) => self.connect(node, &name, i),
+                    None => self.connect(&prefixed(node), &name, i),
+                };
+                if let Err(e) = connected {
+                    // Roll back the partial bay
+                    for name in added.iter() {
+                        self.remove_component(name)?;
+                    }
+                    for node in bay.nodes() {
+                        self.remove_node(&prefixed(node))?;
+                    }
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a reference to the node with the given id
+    pub fn node_by_id(&self, id: u32) -> Result<Rc<Node>, NetworkError> {
+        self.nodes
+            .borrow()
+            .iter()
+            .find(|n| n.id() == Some(id))
+            .cloned()
+            .ok_or(NetworkError::NotFound(format!("id {}", id)))
+    }
+
+    /// Get a reference to the component with the given id
+    pub fn component_by_id(&self, id: u32) -> Result<Rc<dyn Component>, NetworkError> {
+        self.components
+            .borrow()
+            .iter()
+            .find(|c| c.id() == Some(id))
+            .cloned()
+            .ok_or(NetworkError::NotFound(format!("id {}", id)))
+    }
+
+    /// Remove a component with the given name, if it exists in the network. The component is disconnected from all nodes first.
+    pub fn remove_component(&self, name: &str) -> Result<(), NetworkError> {
         let index = self
             .components
             .borrow()
             .iter()
-            .position(|x| x.name() == name);
+            .position(|x| *x.name() == name);
         match index {
             Some(i) => {
-                self.components.borrow_mut().remove(i);
+                let c = self.components.borrow_mut().remove(i);
+                c.disconnect_all();
+                if earths(&c) {
+                    self.ground.remove_component(c)?;
+                }
                 Ok(())
             }
-            None => Err(format!(
-                "Failed to remove component {} - No component with this name exists in network {}",
+            None => Err(NetworkError::NotFound(name.to_string())),
+        }
+    }
+
+    /// Replace the component with the given name by a new component of another type, keeping its name, id, coordinates, the node connection of each terminal and the settings both types have, as copied by [Component::duplicate]. Errors if the terminal counts differ; the network is left unchanged on error.
+    pub fn replace_component(
+        &self,
+        name: &str,
+        new_type: ComponentType,
+    ) -> Result<(), NetworkError> {
+        let old = self.get_component(name)?;
+        let index = self
+            .components
+            .borrow()
+            .iter()
+            .position(|c| Rc::ptr_eq(c, &old))
+            .unwrap();
+        let count = old.terminals().count();
+        if count != self.terminal_count(new_type) {
+            return Err(NetworkError::Unsupported(format!(
+                "Component {} has {} terminals, components of type {} have {}",
                 name,
-                self.name()
-            )),
+                count,
+                new_type,
+                self.terminal_count(new_type)
+            )));
+        }
+        let new = self.instantiate(new_type, name);
+        copy_settings(old.as_ref(), new.as_ref());
+        self.swap_component(index, &old, new)
+    }
+
+    /// Rename a component in place, keeping its id, state, connections and existing references. Errors if the new name is already used by a node or component.
+    pub fn rename(&self, name: &str, new_name: &str) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        if self.check_name(new_name).is_err() {
+            return Err(NetworkError::DuplicateName(new_name.to_string()));
+        }
+        c.set_name(new_name);
+        Ok(())
+    }
+
+    /// Put a new component with the same terminal count in place of the old one at the given index, moving over the id, coordinates, terminal settings and node connections. The new component is wired up before the network is changed, so a failing connection leaves the network as it was.
+    fn swap_component(
+        &self,
+        index: usize,
+        old: &Rc<dyn Component>,
+        new: Rc<dyn Component>,
+    ) -> Result<(), NetworkError> {
+        let connections: Vec<(usize, Rc<Node>)> = old
+            .terminals()
+            .enumerate()
+            .filter_map(|(i, t)| t.borrow().get_node().ok().map(|n| (i, n)))
+            .collect();
+        for (i, n) in connections.iter() {
+            if let Err(e) = new.connect(n.clone(), *i) {
+                new.disconnect_all();
+                return Err(e.into());
+            }
+        }
+
+        new.set_id(old.id());
+        new.set_coordinates(old.coordinates());
+        for (t, new_t) in old.terminals().zip(new.terminals()) {
+            let t = t.borrow();
+            let mut new_t = new_t.borrow_mut();
+            new_t.set_phase(t.phase());
+            new_t.set_ampacity(t.ampacity());
         }
+        old.disconnect_all();
+        for (_, n) in connections {
+            n.add_component(new.clone())?;
+        }
+        if earths(old) {
+            self.ground.detach(Rc::as_ptr(old) as *const ());
+        }
+        if earths(&new) {
+            self.ground.add_component(new.clone())?;
+        }
+        self.components.borrow_mut()[index] = new;
+        Ok(())
     }
 
     /// Return the number of components in the network
@@ -144,7 +855,7 @@ impl Network {
     /// Get a reference to the component with the given name, if it exists in the network
     pub fn get_component(&self, name: &str) -> Result<Rc<dyn Component>, String> {
         let components = self.components.borrow();
-        let component = components.iter().find(|x| x.name() == name);
+        let component = components.iter().find(|x| *x.name() == name);
         match component {
             Some(component) => Ok(component.clone()),
             None => Err(format!(
@@ -155,140 +866,5518 @@ impl Network {
         }
     }
 
-    /// Connect a component terminal to a node. Returns an error if the component or node do not exist, or if the connection fails see [Component::connect]
-    pub fn connect(
-        &self,
-        node_name: &str,
-        component_name: &str,
-        terminal: usize,
-    ) -> Result<(), String> {
-        let n = self.get_node(node_name);
-        let c = self.get_component(component_name);
-        match (n, c) {
-            (Err(_), Err(_)) => Err(format!(
-                "No node with name {} and component with name {} exist in network {}",
-                node_name,
-                component_name,
-                self.name()
-            )),
-            (Err(_), _) => Err(format!(
-                "No node with name {} exists in network {}",
-                node_name,
-                self.name()
-            )),
-            (_, Err(_)) => Err(format!(
-                "No component with name {} exists in network {}",
-                component_name,
-                self.name()
-            )),
-            (Ok(n), Ok(c)) => c.connect(n, terminal),
+    /// Disconnect all components from all nodes, keeping the components and nodes themselves
+    pub fn clear_connections(&self) {
+        for component in self.components.borrow().iter() {
+            component.disconnect_all();
+        }
+        for node in self.nodes.borrow().iter() {
+            node.clear();
         }
     }
 
-    /// Disconnect a component from a node if it is connected. Returns an error if the component or node do not exist
-    pub fn disconnect(&self, node_name: &str, component_name: &str) -> Result<(), String> {
-        let n = self.get_node(node_name);
-        let c = self.get_component(component_name);
-        match (n, c) {
-            (Err(_), Err(_)) => Err(format!(
-                "No node with name {} and component with name {} exist in network {}",
-                node_name,
-                component_name,
-                self.name()
-            )),
-            (Err(_), _) => Err(format!(
-                "No node with name {} exists in network {}",
-                node_name,
-                self.name()
-            )),
-            (_, Err(_)) => Err(format!(
-                "No component with name {} exists in network {}",
-                component_name,
-                self.name()
-            )),
-            (Ok(n), Ok(c)) => c.disconnect(n),
-        }
+    /// Check the structural invariants of every component, returning a description of each violation. Each component must have the terminal count expected for its [ComponentType].
+    pub fn validate_structure(&self) -> Vec<String> {
+        self.components
+            .borrow()
+            .iter()
+            .filter(|c| c.terminals().count() != self.terminal_count(c.r#type()))
+            .map(|c| {
+                format!(
+                    "Component {} of type {} has {} terminals; expected {}",
+                    c.name(),
+                    c.type_name(),
+                    c.terminals().count(),
+                    self.terminal_count(c.r#type())
+                )
+            })
+            .collect()
     }
-}
 
-impl fmt::Display for Network {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Network {}", self.name())
+    /// Run all structural checks, returning a description of each violation; see [Network::validate_structure] and [Network::validate_voltage_consistency].
+    /// Additionally flags components with more than one terminal on the same node, a degenerate short. [Component::connect] rejects such connections, but they can be made on the terminals directly.
+    pub fn validate(&self) -> Vec<String> {
+        let mut violations = self.validate_structure();
+        violations.extend(self.validate_voltage_consistency());
+        for c in self.components.borrow().iter() {
+            let nodes: Vec<Rc<Node>> = c
+                .terminals()
+                .filter_map(|t| t.borrow().get_node().ok())
+                .collect();
+            for (i, n) in nodes.iter().enumerate() {
+                if nodes[..i].iter().any(|other| Rc::ptr_eq(other, n)) {
+                    violations.push(format!(
+                        "Component {} has multiple terminals connected to node {}",
+                        c.name(),
+                        n.name()
+                    ));
+                    break;
+                }
+            }
+        }
+        violations
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Return the components with more than one terminal that are connected on some but not all of their terminals, such as a line with one end unterminated. Analyses treat the free terminals as leading nowhere.
+    pub fn stubs(&self) -> Vec<Rc<dyn Component>> {
+        self.components
+            .borrow()
+            .iter()
+            .filter(|c| {
+                let connected = c
+                    .terminals()
+                    .filter(|t| t.borrow().get_node().is_ok())
+                    .count();
+                c.terminals().count() > 1 && connected > 0 && connected < c.terminals().count()
+            })
+            .cloned()
+            .collect()
+    }
 
-    #[test]
-    fn network_name() {
-        let net = Network::new("net");
-        assert_eq!(net.name(), "net")
+    /// Check that two-terminal components other than transformers connect nodes of the same voltage level, returning a description of each violation. Nodes without a voltage level are not checked.
+    pub fn validate_voltage_consistency(&self) -> Vec<String> {
+        self.components
+            .borrow()
+            .iter()
+            .filter(|c| c.r#type() != ComponentType::Transformer && c.terminals().count() == 2)
+            .filter_map(|c| {
+                let levels: Vec<Option<f64>> = c
+                    .terminals()
+                    .map(|t| t.borrow().get_node().ok().and_then(|n| n.voltage_level()))
+                    .collect();
+                match (levels[0], levels[1]) {
+                    (Some(a), Some(b)) if a != b => Some(format!(
+                        "Component {} of type {} connects voltage levels {} kV and {} kV",
+                        c.name(),
+                        c.r#type(),
+                        a,
+                        b
+                    )),
+                    _ => None,
+                }
+            })
+            .collect()
     }
 
-    #[test]
-    fn network_component() {
-        let net = Network::new("net");
+    /// Flag suspicious but legal patterns; earthing switches that no circuit breaker can isolate, transformers with a single winding connected and disconnectors in series without a circuit breaker in between. Disconnectors are in series if they are joined through other two-terminal components, such as current transformers and lines, at nodes without further branches; single-terminal components at those nodes do not break the series.
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut lints = vec![];
+        let components = self.components.borrow().clone();
+        for c in components.iter() {
+            let connected: Vec<Rc<Node>> = c
+                .terminals()
+                .filter_map(|t| t.borrow().get_node().ok())
+                .collect();
+            match c.r#type() {
+                ComponentType::EarthingSwitch
+                    if !connected.is_empty() && !self.breaker_reachable(&connected[0]) =>
+                {
+                    lints.push(Lint::new(
+                        Severity::Warning,
+                        &c.name(),
+                        format!(
+                            "Earthing switch {} can not be isolated by any circuit breaker",
+                            c.name()
+                        ),
+                    ))
+                }
+                ComponentType::Transformer if connected.len() == 1 => lints.push(Lint::new(
+                    Severity::Warning,
+                    &c.name(),
+                    format!("Transformer {} has only one winding connected", c.name()),
+                )),
+                _ => (),
+            }
+        }
+        let mut pairs: Vec<(Rc<dyn Component>, Rc<dyn Component>)> = vec![];
+        for c in components.iter().filter(|c| is_disconnector(c)) {
+            for n in c.terminals().filter_map(|t| t.borrow().get_node().ok()) {
+                if let Some(other) = series_disconnector(c, n) {
+                    if !pairs
+                        .iter()
+                        .any(|(a, b)| Rc::ptr_eq(a, &other) && Rc::ptr_eq(b, c))
+                    {
+                        pairs.push((c.clone(), other));
+                    }
+                }
+            }
+        }
+        for (a, b) in pairs {
+            lints.push(Lint::new(
+                Severity::Info,
+                &a.name(),
+                format!(
+                    "Disconnectors {} and {} are in series without a circuit breaker in between",
+                    a.name(),
+                    b.name()
+                ),
+            ));
+        }
+        lints
+    }
 
-        assert_eq!(net.component_count(), 0);
-        assert!(net.get_component("cb").is_err());
-        net.create_component::<CircuitBreaker>("cb").unwrap();
-        assert!(net.get_component("cb").unwrap().name() == "cb");
-        assert!(net.create_node("cb").is_err());
-        assert!(net.create_component::<Disconnector>("cb").is_err());
-        assert_eq!(net.component_count(), 1);
+    /// True if a circuit breaker can be reached from the node without passing through another circuit breaker, regardless of switchgear positions
+    fn breaker_reachable(&self, from: &Rc<Node>) -> bool {
+        let mut visited = vec![from.clone()];
+        let mut queue = VecDeque::from(vec![from.clone()]);
+        while let Some(n) = queue.pop_front() {
+            for c in n.components() {
+                if c.r#type() == ComponentType::CircuitBreaker {
+                    return true;
+                }
+                for other in c.other_nodes(&n) {
+                    if !visited.iter().any(|v| Rc::ptr_eq(v, &other)) {
+                        visited.push(other.clone());
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        false
+    }
 
+    /// Return the name and current [PositionState] of every switchgear component in the network
+    pub fn switchgear_states(&self) -> Vec<(String, PositionState)> {
+        self.components
+            .borrow()
+            .iter()
+            .filter_map(|c| match c.position() {
+                Ok(pos) => Some((c.name().clone(), pos.borrow().state())),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    /// Render the network as a textual single-line diagram. Each node is drawn as a bar, followed by the components connected to it with their position and the other nodes they connect to.
+    pub fn to_ascii(&self) -> String {
+        let components = self.components.borrow();
+        let mut out = String::new();
+        for node in self.nodes.borrow().iter() {
+            out += &format!("==== {} ====\n", node.name());
+            for c in components.iter() {
+                let nodes: Vec<Rc<Node>> = c
+                    .terminals()
+                    .filter_map(|t| t.borrow().get_node().ok())
+                    .collect();
+                if !nodes.iter().any(|n| Rc::ptr_eq(n, node)) {
+                    continue;
+                }
+                out += &format!("  |-- {} ({})", c.name(), c.type_name());
+                if c.position().is_ok() {
+                    out += &format!(" [{}]", c.state_summary());
+                }
+                let others: Vec<&str> = nodes
+                    .iter()
+                    .filter(|n| !Rc::ptr_eq(n, node))
+                    .map(|n| n.name().as_str())
+                    .collect();
+                if !others.is_empty() {
+                    out += &format!(" -- {}", others.join(", "));
+                }
+                out += "\n";
+            }
+        }
+        out
+    }
+
+    /// Render the network as a single-line diagram in SVG. Nodes are drawn as bus lines and components as their single-line symbol in a group with class "component", connected to their nodes by lines.
+    /// Elements are colored by energization status, see [Network::node_status]; components take the status of the most energized node they connect to. Unpositioned nodes are placed on a row, unpositioned components between their nodes.
+    pub fn to_svg(&self) -> String {
+        let nodes = self.nodes.borrow();
+        let components = self.components.borrow();
+        let status = self.node_status();
+        let statuses: Vec<EnergyStatus> = nodes
+            .iter()
+            .map(|n| {
+                n.id()
+                    .and_then(|id| status.get(&id).copied())
+                    .unwrap_or(EnergyStatus::Dead)
+            })
+            .collect();
+        let node_positions: Vec<(f64, f64)> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                n.coordinates()
+                    .filter(|(x, y)| x.is_finite() && y.is_finite())
+                    .unwrap_or((100f64 * i as f64 + 50f64, 50f64))
+            })
+            .collect();
+        let mut positions = node_positions.clone();
+        let mut earthing_positions = vec![];
+        let mut out = String::new();
+        for (i, c) in components.iter().enumerate() {
+            let connected = connected_node_indices(&nodes, c);
+            let ends: Vec<(f64, f64)> = connected.iter().map(|j| node_positions[*j]).collect();
+            let position = c
+                .coordinates()
+                .filter(|(x, y)| x.is_finite() && y.is_finite())
+                .unwrap_or(match ends.len() {
+                    0 => (100f64 * i as f64 + 50f64, 150f64),
+                    1 => (ends[0].0, ends[0].1 + 30f64),
+                    n => (
+                        ends.iter().map(|p| p.0).sum::<f64>() / n as f64,
+                        ends.iter().map(|p| p.1).sum::<f64>() / n as f64,
+                    ),
+                });
+            let rank = |s: &EnergyStatus| match s {
+                EnergyStatus::Energized => 3,
+                EnergyStatus::Unknown => 2,
+                EnergyStatus::Earthed => 1,
+                EnergyStatus::Dead => 0,
+            };
+            let component_status = connected
+                .iter()
+                .map(|j| statuses[*j])
+                .max_by_key(rank)
+                .unwrap_or(EnergyStatus::Dead);
+            out += &svg::component(c.as_ref(), position, &ends, component_status);
+            positions.push(position);
+            if earths(c) {
+                earthing_positions.push(position);
+            }
+        }
+        if !earthing_positions.is_empty() {
+            let n = earthing_positions.len() as f64;
+            let x = earthing_positions.iter().map(|p| p.0).sum::<f64>() / n;
+            let y = earthing_positions.iter().map(|p| p.1).fold(0f64, f64::max) + 40f64;
+            out += &svg::ground(self.ground.name(), x, y, &earthing_positions);
+            positions.push((x, y));
+        }
+        for (i, n) in nodes.iter().enumerate() {
+            let (x, y) = node_positions[i];
+            out += &svg::bus(n.name(), x, y, statuses[i]);
+        }
+        let margin = 50f64;
+        let (x0, y0) = positions.first().copied().unwrap_or((0f64, 0f64));
+        let (min_x, max_x, min_y, max_y) =
+            positions
+                .iter()
+                .fold((x0, x0, y0, y0), |(min_x, max_x, min_y, max_y), (x, y)| {
+                    (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+                });
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n  <title>{}</title>\n{}</svg>\n",
+            min_x - margin,
+            min_y - margin,
+            max_x - min_x + 2f64 * margin,
+            max_y - min_y + 2f64 * margin,
+            svg::escape(&self.name),
+            out
+        )
+    }
+
+    /// Get references to all components of the given [ComponentType]
+    pub fn components_of_type(&self, r#type: ComponentType) -> Vec<Rc<dyn Component>> {
+        self.components
+            .borrow()
+            .iter()
+            .filter(|c| c.r#type() == r#type)
+            .cloned()
+            .collect()
+    }
+
+    /// Return the simulation time in seconds
+    pub fn time(&self) -> f64 {
+        self.time.get()
+    }
+
+    /// Advance the simulation time by dt seconds, complete all switchgear transitions whose operating time has elapsed and run due auto-recloses. Completed transitions are recorded in the changelog at the time they completed.
+    pub fn advance_time(&self, dt: f64) {
+        let now = self.time.get() + dt;
+        self.time.set(now);
+        let components = self.components.borrow().clone();
+        let mut completed = vec![];
+        for c in components.iter() {
+            if let Ok(pos) = c.position() {
+                let old = pos.borrow().state();
+                let completes_at = pos.borrow().completes_at();
+                pos.borrow_mut().settle(now);
+                let new = pos.borrow().state();
+                if let (Some(time), true) = (completes_at, old != new) {
+                    completed.push(ChangeEntry::new(
+                        time,
+                        &c.name(),
+                        ChangeValue::Position(old),
+                        ChangeValue::Position(new),
+                    ));
+                }
+            }
+        }
+        completed.sort_by(|a, b| a.time().total_cmp(&b.time()));
+        for entry in completed {
+            self.changelog.borrow_mut().record(entry);
+        }
+        self.log_changes(&components, || {
+            for c in components.iter() {
+                c.tick(now);
+            }
+        });
+    }
+
+    /// Return the switch operations, measurement updates and resets performed through this network in chronological order.
+    /// Recorded are the switching, update and reset methods of the network, such as [Network::open], [Network::close_earth], [Network::update] and [Network::reset], completed transitions and auto-recloses in [Network::advance_time], [Network::apply_updates] and [Network::apply_snapshot]. Operating a component directly is not recorded.
+    pub fn changelog(&self) -> Vec<ChangeEntry> {
+        self.changelog.borrow().entries().clone()
+    }
+
+    /// Remove all entries from the changelog
+    pub fn clear_changelog(&self) {
+        self.changelog.borrow_mut().clear();
+    }
+
+    /// Run f and record the position, earth position and measurement changes it made to the given components in the changelog
+    fn log_changes<T>(&self, components: &[Rc<dyn Component>], f: impl FnOnce() -> T) -> T {
+        let state = |c: &Rc<dyn Component>| {
+            (
+                c.position().ok().map(|p| p.borrow().state()),
+                c.earth_position().ok().map(|p| p.borrow().state()),
+                c.measurement().ok().map(|m| m.borrow().value()),
+            )
+        };
+        let before: Vec<_> = components.iter().map(state).collect();
+        let result = f();
+        let mut log = self.changelog.borrow_mut();
+        for (c, old) in components.iter().zip(before) {
+            let new = state(c);
+            let changes = [
+                (
+                    old.0.map(ChangeValue::Position),
+                    new.0.map(ChangeValue::Position),
+                ),
+                (
+                    old.1.map(ChangeValue::EarthPosition),
+                    new.1.map(ChangeValue::EarthPosition),
+                ),
+                (
+                    old.2.map(ChangeValue::Measurement),
+                    new.2.map(ChangeValue::Measurement),
+                ),
+            ];
+            for (old, new) in changes {
+                if let (Some(old), Some(new)) = (old, new) {
+                    if old != new {
+                        log.record(ChangeEntry::new(self.time(), &c.name(), old, new));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Open the named switchgear and record the change in the changelog, see [Component::open]. The operation is checked like [Network::validate_switching] first.
+    pub fn open(&self, name: &str) -> Result<(), NetworkError> {
+        self.validate_switching(&[SwitchOp::Open(name.to_string())])?;
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.open())
+    }
+
+    /// Close the named switchgear and record the change in the changelog, see [Component::close]. The operation is checked like [Network::validate_switching] first.
+    pub fn close(&self, name: &str) -> Result<(), NetworkError> {
+        self.validate_switching(&[SwitchOp::Close(name.to_string())])?;
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.close())
+    }
+
+    /// Close the integrated earthing switch of the named component and record the change in the changelog, see [Component::close_earth]
+    pub fn close_earth(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.close_earth())
+    }
+
+    /// Open the integrated earthing switch of the named component and record the change in the changelog, see [Component::open_earth]
+    pub fn open_earth(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.open_earth())
+    }
+
+    /// Update the measurement of the named component at the current simulation time and record the change in the changelog, see [Component::update]
+    pub fn update(&self, name: &str, value: f64) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        self.record_update(&c, value)
+    }
+
+    /// Return the named component to its default state and record the changes in the changelog, see [Component::reset]
+    pub fn reset(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.reset());
+        Ok(())
+    }
+
+    /// Trip the switchgear with the given name by protection at the current simulation time, see [Component::protection_trip]
+    pub fn protection_trip(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.protection_trip(self.time()))?;
+        Ok(())
+    }
+
+    /// Set the number of operations after which switchgear is due for maintenance, None to disable
+    pub fn set_maintenance_threshold(&self, operations: Option<u32>) {
+        self.maintenance_threshold.set(operations);
+    }
+
+    /// Return the maintenance threshold in operations, if set
+    pub fn maintenance_threshold(&self) -> Option<u32> {
+        self.maintenance_threshold.get()
+    }
+
+    /// Return the name and operation count of every switchgear component whose operation count has reached the maintenance threshold. Empty if no threshold is set.
+    pub fn due_for_maintenance(&self) -> Vec<(String, u32)> {
+        let threshold = match self.maintenance_threshold() {
+            Some(threshold) => threshold,
+            None => return vec![],
+        };
+        self.components
+            .borrow()
+            .iter()
+            .filter_map(|c| {
+                let count = c.operation_count().ok()?;
+                match count >= threshold {
+                    true => Some((c.name().clone(), count)),
+                    false => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Start closing the switchgear with the given name at the current simulation time. The operation is checked like [Network::validate_switching] first.
+    pub fn begin_close(&self, name: &str) -> Result<(), NetworkError> {
+        self.validate_switching(&[SwitchOp::Close(name.to_string())])?;
+        let c = self.get_component(name)?;
+        let pos = c.position()?;
+        self.log_changes(std::slice::from_ref(&c), || {
+            pos.borrow_mut().begin_close(self.time())
+        })?;
+        Ok(())
+    }
+
+    /// Start opening the switchgear with the given name at the current simulation time. The operation is checked like [Network::validate_switching] first.
+    pub fn begin_open(&self, name: &str) -> Result<(), NetworkError> {
+        self.validate_switching(&[SwitchOp::Open(name.to_string())])?;
+        let c = self.get_component(name)?;
+        let pos = c.position()?;
+        self.log_changes(std::slice::from_ref(&c), || {
+            pos.borrow_mut().begin_open(self.time())
+        })?;
+        Ok(())
+    }
+
+    /// Dry-run a switching sequence without changing the network. Each operation must be a valid switchgear transition allowed by [Component::check_open] or [Component::check_close], disconnectors may only be operated while all circuit breakers sharing a node with them are open, and earthing switches may not close onto an energized node. A valid sequence returns the warnings for its legal but unsafe operations, see [Network::switching_warnings].
+    pub fn validate_switching(&self, ops: &[SwitchOp]) -> Result<Vec<Lint>, NetworkError> {
+        let mut snapshot: HashMap<String, PositionState> =
+            self.switchgear_states().into_iter().collect();
+        for op in ops {
+            let name = op.component();
+            let state = match snapshot.get(name) {
+                Some(state) => *state,
+                None if self.get_component(name).is_ok() => {
+                    return Err(NetworkError::Unsupported(format!(
+                        "Component {} is not switchgear",
+                        name
+                    )))
+                }
+                None => return Err(NetworkError::NotFound(name.clone())),
+            };
+            if state == op.target() {
+                return Err(NetworkError::Other(format!(
+                    "Switchgear {} is already {:?}",
+                    name, state
+                )));
+            }
+            let c = self.get_component(name)?;
+            match op {
+                SwitchOp::Open(_) => c.check_open()?,
+                SwitchOp::Close(_) => c.check_close()?,
+            }
+            if c.r#type() == ComponentType::EarthingSwitch && op.target() == PositionState::Closed {
+                let energized = self.energization_with(|x| conducts_in(&snapshot, x));
+                let nodes = self.nodes.borrow();
+                for i in connected_node_indices(&nodes, &c) {
+                    if energized[i] {
+                        return Err(NetworkError::Interlock(format!(
+                            "Earthing switch {} can not close onto energized node {}",
+                            name,
+                            nodes[i].name()
+                        )));
+                    }
+                }
+            }
+            if c.r#type() == ComponentType::Disconnector
+                || c.r#type() == ComponentType::DisconnectorEarth
+            {
+                for cb in self.adjacent_components(&c) {
+                    if cb.r#type() == ComponentType::CircuitBreaker
+                        && snapshot.get(&*cb.name()) == Some(&PositionState::Closed)
+                    {
+                        return Err(NetworkError::Interlock(format!(
+                            "Disconnector {} can not be operated while circuit breaker {} is closed",
+                            name,
+                            cb.name()
+                        )));
+                    }
+                }
+            }
+            snapshot.insert(name.clone(), op.target());
+        }
+        Ok(self.switching_warnings(ops))
+    }
+
+    /// Flag the operations of a switching sequence that are legal but unsafe, without changing the network. Closing a disconnector or disconnector with integrated earthing switch between two energized nodes that are not already connected through conducting components makes a connection across a potential difference.
+    /// Circuit breakers and other components are exempt. Each operation is checked in the state left by the operations before it; use [Network::validate_switching] to check that the sequence can be executed at all.
+    pub fn switching_warnings(&self, ops: &[SwitchOp]) -> Vec<Lint> {
+        let mut snapshot: HashMap<String, PositionState> =
+            self.switchgear_states().into_iter().collect();
+        let mut warnings = vec![];
+        for op in ops {
+            let c = match self.get_component(op.component()) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            let is_disconnector = c.r#type() == ComponentType::Disconnector
+                || c.r#type() == ComponentType::DisconnectorEarth;
+            if is_disconnector && op.target() == PositionState::Closed {
+                let conducts = |x: &Rc<dyn Component>| conducts_in(&snapshot, x);
+                let ends = connected_node_indices(&self.nodes.borrow(), &c);
+                if let [a, b] = ends[..] {
+                    let energized = self.energization_with(conducts);
+                    let mut seeds = vec![false; energized.len()];
+                    seeds[a] = true;
+                    if energized[a] && energized[b] && !self.spread(seeds, &[], conducts)[b] {
+                        let nodes = self.nodes.borrow();
+                        warnings.push(Lint::new(
+                            Severity::Warning,
+                            &c.name(),
+                            format!(
+                                "Closing disconnector {} connects separately energized nodes {} and {}",
+                                c.name(),
+                                nodes[a].name(),
+                                nodes[b].name()
+                            ),
+                        ));
+                    }
+                }
+            }
+            snapshot.insert(op.component().clone(), op.target());
+        }
+        warnings
+    }
+
+    /// Return whether the given operation would currently succeed, without performing it. The operation is checked like [Network::validate_switching], so the same locks and interlocks apply as when switching. Errors if the component does not exist or is not switchgear.
+    pub fn can_operate(&self, op: &SwitchOp) -> Result<bool, NetworkError> {
+        match self.validate_switching(std::slice::from_ref(op)) {
+            Ok(_) => Ok(true),
+            Err(NetworkError::NotFound(e)) => Err(NetworkError::NotFound(e)),
+            Err(NetworkError::Unsupported(e)) => Err(NetworkError::Unsupported(e)),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Execute a switching sequence with all-or-nothing semantics. The sequence is validated with [Network::validate_switching] first; if any operation fails while applying, all operations are rolled back. Returns the switching warnings of the executed sequence.
+    pub fn switch_atomic(&self, ops: &[SwitchOp]) -> Result<Vec<Lint>, NetworkError> {
+        let warnings = self.validate_switching(ops)?;
+        let mut components: Vec<Rc<dyn Component>> = vec![];
+        for op in ops {
+            let c = self.get_component(op.component())?;
+            if !components.iter().any(|x| Rc::ptr_eq(x, &c)) {
+                components.push(c);
+            }
+        }
+        self.log_changes(&components, || self.apply_switching(ops))?;
+        Ok(warnings)
+    }
+
+    /// Apply a validated switching sequence, rolling back on failure
+    fn apply_switching(&self, ops: &[SwitchOp]) -> Result<(), NetworkError> {
+        let mut backup = vec![];
+        for op in ops {
+            let c = self.get_component(op.component())?;
+            if !backup.iter().any(|(b, _, _)| Rc::ptr_eq(b, &c)) {
+                let position = c.position()?.borrow().clone();
+                let earth = c.earth_position().ok().map(|e| e.borrow().clone());
+                backup.push((c, position, earth));
+            }
+        }
+        for op in ops {
+            let c = self.get_component(op.component())?;
+            let result = match op {
+                SwitchOp::Open(_) => c.open(),
+                SwitchOp::Close(_) => c.close(),
+            };
+            if let Err(e) = result {
+                for (c, position, earth) in backup {
+                    *c.position()?.borrow_mut() = position;
+                    if let Some(earth) = earth {
+                        *c.earth_position()?.borrow_mut() = earth;
+                    }
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the other components connected to any of the nodes the given component is connected to
+    fn adjacent_components(&self, c: &Rc<dyn Component>) -> Vec<Rc<dyn Component>> {
+        let nodes: Vec<Rc<Node>> = c
+            .terminals()
+            .filter_map(|t| t.borrow().get_node().ok())
+            .collect();
+        self.components
+            .borrow()
+            .iter()
+            .filter(|other| !Rc::ptr_eq(other, c))
+            .filter(|other| {
+                other.terminals().any(|t| match t.borrow().get_node() {
+                    Ok(n) => nodes.iter().any(|x| Rc::ptr_eq(x, &n)),
+                    Err(_) => false,
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Reduce the network to a bus-branch model. Nodes joined by closed switchgear or jumpers are collapsed into a single bus, other components connecting multiple nodes become branches between buses.
+    pub fn reduce(&self) -> BusBranchModel {
+        let nodes = self.nodes.borrow();
+        let mut parent: Vec<usize> = (0..nodes.len()).collect();
+
+        let mut branches = vec![];
+        for c in self.components.borrow().iter() {
+            let connected = connected_node_indices(&nodes, c);
+            let joins = match c.position() {
+                Ok(pos) => Some(pos.borrow().is_closed()),
+                Err(_) if c.r#type() == ComponentType::Jumper => Some(true),
+                Err(_) => None,
+            };
+            match joins {
+                Some(true) => {
+                    for pair in connected.windows(2) {
+                        let (a, b) = (
+                            find_root(&mut parent, pair[0]),
+                            find_root(&mut parent, pair[1]),
+                        );
+                        parent[a] = b;
+                    }
+                }
+                Some(false) => (),
+                None => {
+                    if connected.len() > 1 {
+                        branches.push((c.clone(), connected));
+                    }
+                }
+            }
+        }
+
+        let root_of_node: Vec<usize> = (0..nodes.len())
+            .map(|i| find_root(&mut parent, i))
+            .collect();
+        BusBranchModel::new(&nodes, &root_of_node, branches)
+    }
+
+    /// Convert the network into a petgraph [Graph]. Nodes become vertices and components become edges between the nodes they connect; components with more than two connected terminals add an edge for every pair of connected nodes, components with a single connected terminal add no edge.
+    #[cfg(feature = "petgraph")]
+    pub fn to_petgraph(&self) -> Graph<NodeRef, ComponentRef> {
+        let mut graph = Graph::new();
+        let nodes = self.nodes.borrow();
+        let indices: Vec<_> = nodes.iter().map(|n| graph.add_node(n.clone())).collect();
+        for c in self.components.borrow().iter() {
+            let connected = connected_node_indices(&nodes, c);
+            for (i, a) in connected.iter().enumerate() {
+                for b in connected[i + 1..].iter() {
+                    graph.add_edge(indices[*a], indices[*b], c.clone());
+                }
+            }
+        }
+        graph
+    }
+
+    /// Get references to all nodes energized from a source node. Energization passes through components connecting multiple nodes if they conduct, see [Component::conducts].
+    pub fn energized_nodes(&self) -> Vec<Rc<Node>> {
+        let energized = self.energization();
+        self.nodes
+            .borrow()
+            .iter()
+            .zip(energized)
+            .filter(|(_, e)| *e)
+            .map(|(n, _)| n.clone())
+            .collect()
+    }
+
+    /// Return the [EnergyStatus] of every node by id. Energized takes precedence over earthed.
+    pub fn node_status(&self) -> HashMap<NodeId, EnergyStatus> {
+        let transit_conducts = |c: &Rc<dyn Component>| {
+            c.conducts() || c.position().is_ok_and(|p| p.borrow().is_in_transit())
+        };
+        let energized = self.energization();
+        let earthed = self.spread(self.earthing_points(false), &[], |c| c.conducts());
+        let maybe_energized = self.energization_with(transit_conducts);
+        let maybe_earthed = self.spread(self.earthing_points(true), &[], transit_conducts);
+        self.nodes
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, n)| {
+                let status = if energized[i] {
+                    EnergyStatus::Energized
+                } else if earthed[i] {
+                    EnergyStatus::Earthed
+                } else if maybe_energized[i] || maybe_earthed[i] {
+                    EnergyStatus::Unknown
+                } else {
+                    EnergyStatus::Dead
+                };
+                Some((n.id()?, status))
+            })
+            .collect()
+    }
+
+    /// Return for each node whether it is directly earthed by a closed earthing switch or integrated earthing switch, optionally also counting earthing switches in transit as closed
+    fn earthing_points(&self, include_transit: bool) -> Vec<bool> {
+        let closed = |p: &RefCell<SwitchgearPosition>| {
+            let p = p.borrow();
+            p.is_closed() || (include_transit && p.is_in_transit())
+        };
+        let nodes = self.nodes.borrow();
+        let mut earthed = vec![false; nodes.len()];
+        for c in self.ground.components().iter() {
+            let node = if c.earth_position().is_ok_and(closed) {
+                c.terminal(1).ok().and_then(|t| t.borrow().get_node().ok())
+            } else if c.r#type() == ComponentType::EarthingSwitch && c.position().is_ok_and(closed)
+            {
+                c.terminal(0).ok().and_then(|t| t.borrow().get_node().ok())
+            } else {
+                None
+            };
+            if let Some(i) = node.and_then(|n| nodes.iter().position(|x| Rc::ptr_eq(x, &n))) {
+                earthed[i] = true;
+            }
+        }
+        earthed
+    }
+
+    /// Get references to all energized nodes that are earthed to system earth, see [EarthKind], by a closed earthing switch or integrated earthing switch
+    pub fn grounded_energized_nodes(&self) -> Vec<Rc<Node>> {
+        let earthed: Vec<Rc<Node>> = self
+            .ground
+            .components()
+            .iter()
+            .filter(|c| c.is_system_earth())
+            .filter_map(earthed_node)
+            .collect();
+        self.energized_nodes()
+            .into_iter()
+            .filter(|n| earthed.iter().any(|e| Rc::ptr_eq(e, n)))
+            .collect()
+    }
+
+    /// Return the names of the circuit breakers whose individual opening would de-energize the node with the given name, i.e. its single points of failure. Returns an empty list if the node does not exist or is not energized.
+    pub fn n_minus_1(&self, node: &str) -> Vec<String> {
+        let index = match self.nodes.borrow().iter().position(|n| n.name() == node) {
+            Some(i) => i,
+            None => return vec![],
+        };
+        if !self.energization()[index] {
+            return vec![];
+        }
+        self.components_of_type(ComponentType::CircuitBreaker)
+            .iter()
+            .filter(|cb| cb.conducts())
+            .filter(|cb| !self.energization_with(|c| !Rc::ptr_eq(c, cb) && c.conducts())[index])
+            .map(|cb| cb.name().clone())
+            .collect()
+    }
+
+    /// True if the named node would still be energized with the named components out of service, generalizing [Network::n_minus_1] to any number of outages. Unknown component names are ignored; returns false if the node does not exist.
+    pub fn energized_excluding(&self, node: &str, excluded: &[&str]) -> bool {
+        let index = match self.nodes.borrow().iter().position(|n| n.name() == node) {
+            Some(i) => i,
+            None => return false,
+        };
+        self.energization_with(|c| c.conducts() && !excluded.contains(&c.name().as_str()))[index]
+    }
+
+    /// Return the name of the source node closest to the named node along the paths energization takes, and the number of components in between. Like [Network::energized_nodes] this follows [Component::feeds] through conducting components and honours energization overrides. Returns None if the node does not exist or no source supplies it.
+    pub fn nearest_source(&self, node: &str) -> Option<(String, usize)> {
+        let target = self.nodes.borrow().iter().position(|n| n.name() == node)?;
+        let (sources, blocked) = self.energization_seeds();
+        let (source, distance) = self.reach(sources, &blocked, |c| c.conducts())[target]?;
+        let name = self.nodes.borrow()[source].name().clone();
+        Some((name, distance))
+    }
+
+    /// Return the components reachable from the named node in breadth-first order, regardless of switchgear position, so components closer to the node come first. Components attached to the same node keep the order in which they were connected. Empty if the node does not exist.
+    pub fn order_from_source(&self, source: &str) -> Vec<Rc<dyn Component>> {
+        let nodes = self.nodes.borrow();
+        let start = match nodes.iter().position(|n| n.name() == source) {
+            Some(start) => start,
+            None => return vec![],
+        };
+        let mut visited = vec![false; nodes.len()];
+        visited[start] = true;
+        let mut queue = VecDeque::from(vec![start]);
+        let mut order: Vec<Rc<dyn Component>> = vec![];
+        while let Some(i) = queue.pop_front() {
+            for c in nodes[i].components() {
+                if order.iter().any(|x| Rc::ptr_eq(x, &c)) {
+                    continue;
+                }
+                for j in connected_node_indices(&nodes, &c) {
+                    if !visited[j] {
+                        visited[j] = true;
+                        queue.push_back(j);
+                    }
+                }
+                order.push(c);
+            }
+        }
+        order
+    }
+
+    /// Return every simple path of conducting components between the two named nodes that passes through at most max_len components, with no node visited twice. Paths are found depth-first in connection order; a node has a single empty path to itself. Empty if either node does not exist.
+    pub fn all_paths(&self, from: &str, to: &str, max_len: usize) -> Vec<Vec<Rc<dyn Component>>> {
+        let nodes = self.nodes.borrow();
+        let (start, end) = match (
+            nodes.iter().position(|n| n.name() == from),
+            nodes.iter().position(|n| n.name() == to),
+        ) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return vec![],
+        };
+        let mut paths = vec![];
+        let mut visited = vec![false; nodes.len()];
+        visited[start] = true;
+        extend_paths(
+            &nodes,
+            start,
+            end,
+            max_len,
+            &mut visited,
+            &mut vec![],
+            &mut paths,
+        );
+        paths
+    }
+
+    /// Return the path of conducting components between the two named nodes with the lowest total impedance in ohm, and that total. Each component weighs the magnitude of its [Component::impedance]; switchgear, jumpers and components without an impedance set weigh nothing.
+    /// Returns None if either node does not exist or they are not connected.
+    pub fn shortest_impedance_path(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Option<(Vec<Rc<dyn Component>>, f64)> {
+        let nodes = self.nodes.borrow();
+        let start = nodes.iter().position(|n| n.name() == from)?;
+        let end = nodes.iter().position(|n| n.name() == to)?;
+        let mut distance: Vec<Option<f64>> = vec![None; nodes.len()];
+        let mut previous: Vec<Option<(usize, Rc<dyn Component>)>> = vec![None; nodes.len()];
+        let mut done = vec![false; nodes.len()];
+        distance[start] = Some(0f64);
+        loop {
+            let i = (0..nodes.len())
+                .filter(|i| !done[*i])
+                .filter_map(|i| Some((i, distance[i]?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))?
+                .0;
+            if i == end {
+                break;
+            }
+            done[i] = true;
+            let d = distance[i].unwrap();
+            for c in nodes[i].components().iter().filter(|c| c.conducts()) {
+                let weight = c
+                    .impedance()
+                    .ok()
+                    .and_then(|z| z.get())
+                    .map_or(0f64, |(r, x)| r.hypot(x));
+                for j in connected_node_indices(&nodes, c) {
+                    if distance[j].is_none_or(|dj| d + weight < dj) {
+                        distance[j] = Some(d + weight);
+                        previous[j] = Some((i, c.clone()));
+                    }
+                }
+            }
+        }
+        let mut path = vec![];
+        let mut i = end;
+        while let Some((from, c)) = previous[i].clone() {
+            path.push(c);
+            i = from;
+        }
+        path.reverse();
+        Some((path, distance[end]?))
+    }
+
+    /// Return each source feeding a fault at the named node, with the path of conducting components through which it contributes. Sources are nodes marked as source and nodes with a generator attached, as in [Network::island_sources]; the path is the one with the lowest impedance, see [Network::shortest_impedance_path].
+    /// Sources are listed in node order, a faulted source node contributes through an empty path. Empty if the node does not exist or no source reaches it.
+    pub fn fault_contributions(&self, faulted_node: &str) -> Vec<(String, Vec<Rc<dyn Component>>)> {
+        let sources: Vec<String> = self
+            .island_sources()
+            .into_values()
+            .flatten()
+            .map(|n| n.name().clone())
+            .collect();
+        let nodes = self.nodes.borrow().clone();
+        nodes
+            .iter()
+            .filter(|n| sources.contains(n.name()))
+            .filter_map(|n| {
+                let (path, _) = self.shortest_impedance_path(n.name(), faulted_node)?;
+                Some((n.name().clone(), path))
+            })
+            .collect()
+    }
+
+    /// Return the number of switchgear components between the two named components, along the path crossing the fewest of them regardless of their position. Returns None if either component does not exist or they are not connected.
+    pub fn switches_between(&self, a: &str, b: &str) -> Option<usize> {
+        let a = self.get_component(a).ok()?;
+        let b = self.get_component(b).ok()?;
+        if Rc::ptr_eq(&a, &b) {
+            return Some(0);
+        }
+        let nodes = self.nodes.borrow();
+        let mut switches: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut queue = VecDeque::new();
+        for i in connected_node_indices(&nodes, &a) {
+            switches[i] = Some(0);
+            queue.push_back(i);
+        }
+        while let Some(i) = queue.pop_front() {
+            let count = switches[i].unwrap();
+            for c in nodes[i].components() {
+                if Rc::ptr_eq(&c, &b) {
+                    return Some(count);
+                }
+                if Rc::ptr_eq(&c, &a) {
+                    continue;
+                }
+                let is_switch = c.position().is_ok();
+                for j in connected_node_indices(&nodes, &c) {
+                    let next = count + is_switch as usize;
+                    if switches[j].is_none_or(|s| next < s) {
+                        switches[j] = Some(next);
+                        // 0-1 breadth-first search; free edges go to the front to keep the queue ordered
+                        match is_switch {
+                            true => queue.push_back(j),
+                            false => queue.push_front(j),
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Return the zone of components around the component with the given name, i.e. all components reachable from it without passing through non-conducting components or source nodes. The open switches bounding the zone are part of it. Returns an empty list if the component does not exist.
+    pub fn isolated_zone(&self, around_component: &str) -> Vec<Rc<dyn Component>> {
+        let start = match self.get_component(around_component) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let components = self.components.borrow();
+        let mut zone = vec![start.clone()];
+        let mut queue: Vec<Rc<Node>> = start
+            .terminals()
+            .filter_map(|t| t.borrow().get_node().ok())
+            .collect();
+        let mut visited: Vec<Rc<Node>> = vec![];
+        while let Some(node) = queue.pop() {
+            if node.is_source() || visited.iter().any(|n| Rc::ptr_eq(n, &node)) {
+                continue;
+            }
+            visited.push(node.clone());
+            for c in components.iter().filter(|c| is_connected_to(c, &node)) {
+                if zone.iter().any(|x| Rc::ptr_eq(x, c)) {
+                    continue;
+                }
+                zone.push(c.clone());
+                if c.conducts() {
+                    queue.extend(c.terminals().filter_map(|t| t.borrow().get_node().ok()));
+                }
+            }
+        }
+        zone
+    }
+
+    /// Return for each node whether it is energized from a source node through conducting components
+    fn energization(&self) -> Vec<bool> {
+        self.energization_with(|c| c.conducts())
+    }
+
+    /// Start an [EnergizationWalk] at the node with the given name
+    pub fn energization_walk(&self, from: &str) -> Result<EnergizationWalk, NetworkError> {
+        Ok(EnergizationWalk::new(self.get_node(from)?))
+    }
+
+    /// Return for each node whether it is energized from a source node, through the components for which the given function returns true. Nodes with an energization override are energized or de-energized as forced, see [Node::set_energization_override].
+    fn energization_with(&self, conducts: impl Fn(&Rc<dyn Component>) -> bool) -> Vec<bool> {
+        let (sources, blocked) = self.energization_seeds();
+        self.spread(sources, &blocked, conducts)
+    }
+
+    /// Return for each node whether energization starts there and whether it is blocked, from the source flags and energization overrides
+    fn energization_seeds(&self) -> (Vec<bool>, Vec<bool>) {
+        self.nodes
+            .borrow()
+            .iter()
+            .map(|n| match n.energization_override() {
+                Some(state) => (state, !state),
+                None => (n.is_source(), false),
+            })
+            .unzip()
+    }
+
+    /// Return for each node whether it is reachable from the given seed nodes, through the components for which the given function returns true, in the directions given by [Component::feeds]. Blocked nodes are never reached.
+    fn spread(
+        &self,
+        seeds: Vec<bool>,
+        blocked: &[bool],
+        conducts: impl Fn(&Rc<dyn Component>) -> bool,
+    ) -> Vec<bool> {
+        self.reach(seeds, blocked, conducts)
+            .iter()
+            .map(|r| r.is_some())
+            .collect()
+    }
+
+    /// Breadth-first [Network::spread], returning for each reached node the index of the seed it is reached from first and the number of components in between. Ties go to the seed with the lowest index.
+    fn reach(
+        &self,
+        seeds: Vec<bool>,
+        blocked: &[bool],
+        conducts: impl Fn(&Rc<dyn Component>) -> bool,
+    ) -> Vec<Option<(usize, usize)>> {
+        let nodes = self.nodes.borrow();
+        let index = |c: &Rc<dyn Component>, t: usize| {
+            let node = c.terminal(t).ok()?.borrow().get_node().ok()?;
+            nodes.iter().position(|n| Rc::ptr_eq(n, &node))
+        };
+        let mut feeds: Vec<(usize, usize)> = vec![];
+        for c in self.components.borrow().iter().filter(|c| conducts(c)) {
+            for (from, to) in c.feeds() {
+                if let (Some(from), Some(to)) = (index(c, from), index(c, to)) {
+                    feeds.push((from, to));
+                }
+            }
+        }
+        let mut reached: Vec<Option<(usize, usize)>> = (0..nodes.len())
+            .map(|i| seeds[i].then_some((i, 0)))
+            .collect();
+        let mut queue: VecDeque<usize> = (0..nodes.len()).filter(|i| seeds[*i]).collect();
+        while let Some(i) = queue.pop_front() {
+            let (seed, distance) = reached[i].unwrap();
+            for (_, j) in feeds.iter().filter(|(from, _)| *from == i) {
+                if reached[*j].is_none() && blocked.get(*j) != Some(&true) {
+                    reached[*j] = Some((seed, distance + 1));
+                    queue.push_back(*j);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Find the structural rings in the network, ignoring switchgear positions. Returns a cycle basis; one cycle of nodes for every component that closes a loop, so a ring yields one cycle and a radial network none. Parallel components between two nodes form a cycle of those two nodes.
+    pub fn find_cycles(&self) -> Vec<Vec<Rc<Node>>> {
+        let nodes = self.nodes.borrow();
+        let mut edges: Vec<(usize, usize)> = vec![];
+        for c in self.components.borrow().iter() {
+            for pair in connected_node_indices(&nodes, c).windows(2) {
+                if pair[0] != pair[1] {
+                    edges.push((pair[0], pair[1]));
+                }
+            }
+        }
+        // Breadth-first spanning forest, every edge outside of it closes a cycle
+        let mut parent: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut depth: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut tree = vec![false; edges.len()];
+        for root in 0..nodes.len() {
+            if depth[root].is_some() {
+                continue;
+            }
+            depth[root] = Some(0);
+            let mut queue = VecDeque::from(vec![root]);
+            while let Some(i) = queue.pop_front() {
+                for (e, (a, b)) in edges.iter().enumerate() {
+                    let j = match (*a == i, *b == i) {
+                        (true, _) => *b,
+                        (_, true) => *a,
+                        _ => continue,
+                    };
+                    if depth[j].is_none() {
+                        depth[j] = Some(depth[i].unwrap() + 1);
+                        parent[j] = Some(i);
+                        tree[e] = true;
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        let mut cycles = vec![];
+        for (a, b) in edges
+            .iter()
+            .zip(&tree)
+            .filter(|(_, t)| !**t)
+            .map(|(e, _)| e)
+        {
+            let (mut a, mut b) = (*a, *b);
+            let mut from_a = vec![a];
+            let mut from_b = vec![b];
+            while a != b {
+                if depth[a] >= depth[b] {
+                    a = parent[a].unwrap();
+                    from_a.push(a);
+                } else {
+                    b = parent[b].unwrap();
+                    from_b.push(b);
+                }
+            }
+            from_b.pop();
+            from_a.extend(from_b.into_iter().rev());
+            cycles.push(from_a.iter().map(|i| nodes[*i].clone()).collect());
+        }
+        cycles
+    }
+
+    /// Return the at most n nodes with the most attached components, see [Node::degree]. Nodes of equal degree keep their insertion order.
+    pub fn busiest_nodes(&self, n: usize) -> Vec<Rc<Node>> {
+        let mut nodes = self.nodes.borrow().clone();
+        nodes.sort_by_key(|node| std::cmp::Reverse(node.degree()));
+        nodes.truncate(n);
+        nodes
+    }
+
+    /// Group the nodes into islands; sets of nodes connected through conducting components, see [Component::conducts]. Islands are ordered by their first node.
+    pub fn islands(&self) -> Vec<Vec<Rc<Node>>> {
+        let nodes = self.nodes.borrow();
+        let mut parent: Vec<usize> = (0..nodes.len()).collect();
+        for c in self.components.borrow().iter().filter(|c| c.conducts()) {
+            for pair in connected_node_indices(&nodes, c).windows(2) {
+                let (a, b) = (
+                    find_root(&mut parent, pair[0]),
+                    find_root(&mut parent, pair[1]),
+                );
+                parent[a] = b;
+            }
+        }
+        let mut roots: Vec<usize> = vec![];
+        let mut islands: Vec<Vec<Rc<Node>>> = vec![];
+        for (i, n) in nodes.iter().enumerate() {
+            let root = find_root(&mut parent, i);
+            match roots.iter().position(|r| *r == root) {
+                Some(island) => islands[island].push(n.clone()),
+                None => {
+                    roots.push(root);
+                    islands.push(vec![n.clone()]);
+                }
+            }
+        }
+        islands
+    }
+
+    /// Map the index of every island in [Network::islands] to its source nodes; nodes marked as source and nodes with a generator attached. Blackout islands map to an empty list.
+    pub fn island_sources(&self) -> HashMap<usize, Vec<Rc<Node>>> {
+        self.islands()
+            .into_iter()
+            .enumerate()
+            .map(|(i, island)| {
+                let sources = island
+                    .into_iter()
+                    .filter(|n| {
+                        n.is_source()
+                            || n.components()
+                                .iter()
+                                .any(|c| c.r#type() == ComponentType::Generator)
+                    })
+                    .collect();
+                (i, sources)
+            })
+            .collect()
+    }
+
+    /// Group the components that are connected between the exact same pair of nodes, in either direction. Only groups of two or more parallel components are returned, in insertion order of their first component.
+    pub fn parallel_groups(&self) -> Vec<Vec<Rc<dyn Component>>> {
+        let nodes = self.nodes.borrow();
+        let mut pairs: Vec<(usize, usize)> = vec![];
+        let mut groups: Vec<Vec<Rc<dyn Component>>> = vec![];
+        for c in self.components.borrow().iter() {
+            let mut indices = connected_node_indices(&nodes, c);
+            indices.sort_unstable();
+            indices.dedup();
+            if indices.len() != 2 {
+                continue;
+            }
+            let pair = (indices[0], indices[1]);
+            match pairs.iter().position(|p| *p == pair) {
+                Some(group) => groups[group].push(c.clone()),
+                None => {
+                    pairs.push(pair);
+                    groups.push(vec![c.clone()]);
+                }
+            }
+        }
+        groups.retain(|g| g.len() > 1);
+        groups
+    }
+
+    /// Return the net active power injection in MW at every energized node by id; the output of attached generators minus the demand of attached loads
+    pub fn power_balance(&self) -> HashMap<NodeId, f64> {
+        let energized = self.energization();
+        self.nodes
+            .borrow()
+            .iter()
+            .zip(energized)
+            .filter(|(_, e)| *e)
+            .filter_map(|(n, _)| Some((n.id()?, self.injection_at(n))))
+            .collect()
+    }
+
+    /// Return the index into [Network::islands] and the mismatch in MW of every energized island whose generation and load do not balance
+    pub fn unbalanced_islands(&self) -> Vec<(usize, f64)> {
+        let balance = self.power_balance();
+        self.islands()
+            .iter()
+            .enumerate()
+            .filter(|(_, island)| {
+                island
+                    .iter()
+                    .any(|n| n.id().is_some_and(|id| balance.contains_key(&id)))
+            })
+            .map(|(i, island)| (i, island.iter().map(|n| self.injection_at(n)).sum::<f64>()))
+            .filter(|(_, mismatch)| mismatch.abs() > 1e-9)
+            .collect()
+    }
+
+    /// Return the total demand in MW of the loads fed through the named component. The component is treated as a cut point; loads are summed on the sides that can not reach a source node through other conducting components, or beyond the first terminal if no side reaches a source. Returns 0 if the component does not exist.
+    pub fn downstream_load(&self, component: &str) -> f64 {
+        let cut = match self.get_component(component) {
+            Ok(c) => c,
+            Err(_) => return 0f64,
+        };
+        let conducts = |c: &Rc<dyn Component>| !Rc::ptr_eq(c, &cut) && c.conducts();
+        let sides: Vec<Vec<bool>> = {
+            let nodes = self.nodes.borrow();
+            connected_node_indices(&nodes, &cut)
+                .into_iter()
+                .map(|i| {
+                    let mut seeds = vec![false; nodes.len()];
+                    seeds[i] = true;
+                    seeds
+                })
+                .collect()
+        };
+        let sides: Vec<Vec<bool>> = sides
+            .into_iter()
+            .map(|seeds| self.spread(seeds, &[], conducts))
+            .collect();
+        let sources: Vec<bool> = self.nodes.borrow().iter().map(|n| n.is_source()).collect();
+        let supplied =
+            |side: &Vec<bool>| side.iter().zip(&sources).any(|(s, source)| *s && *source);
+        let mut downstream = vec![false; sources.len()];
+        let any_supplied = sides.iter().any(supplied);
+        for (i, side) in sides.iter().enumerate() {
+            if (any_supplied && !supplied(side)) || (!any_supplied && i > 0) {
+                for (d, s) in downstream.iter_mut().zip(side) {
+                    *d |= *s;
+                }
+            }
+        }
+        let nodes = self.nodes.borrow();
+        self.components_of_type(ComponentType::Load)
+            .iter()
+            .filter(|load| {
+                connected_node_indices(&nodes, load)
+                    .iter()
+                    .any(|i| downstream[*i])
+            })
+            .filter_map(|load| load.active_power().ok().map(|p| p.get()))
+            .sum()
+    }
+
+    /// Return the net active power injection of the components attached to the given node
+    fn injection_at(&self, node: &Rc<Node>) -> f64 {
+        self.components
+            .borrow()
+            .iter()
+            .filter(|c| is_connected_to(c, node))
+            .map(|c| c.injection())
+            .sum()
+    }
+
+    /// Return the name, current value and [Unit] of every measurement in the network
+    pub fn measurements(&self) -> Vec<(String, f64, Unit)> {
+        self.components
+            .borrow()
+            .iter()
+            .filter_map(|c| match c.measurement() {
+                Ok(m) => {
+                    let m = m.borrow();
+                    Some((c.name().clone(), m.value(), m.unit()))
+                }
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    /// Apply a batch of measurement updates, recording each sample at the current network time. Errors without applying anything if a component does not exist or has no measurement. Values rejected by the measurement limits are skipped and the first rejection is returned after the rest of the batch is applied.
+    pub fn apply_updates(&self, updates: &[MeasurementUpdate]) -> Result<(), NetworkError> {
+        let mut targets = vec![];
+        for update in updates {
+            let c = match self.get_component(&update.component) {
+                Ok(c) => c,
+                Err(_) => return Err(NetworkError::NotFound(update.component.clone())),
+            };
+            if c.measurement().is_err() {
+                return Err(NetworkError::Unsupported(format!(
+                    "Component {} has no measurement",
+                    update.component
+                )));
+            }
+            targets.push(c);
+        }
+        let mut result = Ok(());
+        for (c, update) in targets.iter().zip(updates) {
+            if let (Err(e), Ok(())) = (self.record_update(c, update.value), &result) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// Update the measurement of the component at the current network time and record the change in the changelog
+    fn record_update(&self, c: &Rc<dyn Component>, value: f64) -> Result<(), NetworkError> {
+        let mut m = c
+            .measurement()
+            .map_err(NetworkError::Unsupported)?
+            .borrow_mut();
+        let old = m.value();
+        m.update_at(self.time(), value)?;
+        self.changelog.borrow_mut().record(ChangeEntry::new(
+            self.time(),
+            &c.name(),
+            ChangeValue::Measurement(old),
+            ChangeValue::Measurement(m.value()),
+        ));
+        Ok(())
+    }
+
+    /// Apply a full station state, moving switchgear to the given positions and updating measurements at the current network time. Elements already in the given position are left alone.
+    /// Switching goes through [Network::open], [Network::close] and their earth counterparts, so locks and the interlocks of [Network::validate_switching] apply. Earthing switches are opened first and closed last, so a disconnector with integrated earthing switch can move from earth to line in one snapshot.
+    /// Every element is attempted; the errors of the elements that could not be applied, such as unknown names, components without position or measurement, locked or interlocked switchgear and positions in transit, are collected and returned in the order the elements are applied, by name within each step.
+    pub fn apply_snapshot(&self, snapshot: &StationSnapshot) -> Result<(), Vec<NetworkError>> {
+        let mut errors = vec![];
+        let sorted = |map: &'_ HashMap<String, PositionState>| {
+            let mut states: Vec<(String, PositionState)> =
+                map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+            states.sort_by(|a, b| a.0.cmp(&b.0));
+            states
+        };
+        let earth_positions = sorted(&snapshot.earth_positions);
+        let (earth_closes, earth_opens): (Vec<_>, Vec<_>) = earth_positions
+            .into_iter()
+            .partition(|(_, state)| *state == PositionState::Closed);
+        let transit = |name: &str| {
+            Err(NetworkError::Unsupported(format!(
+                "Component {} can not be set in transit",
+                name
+            )))
+        };
+        let apply_earth = |name: &str, state: PositionState| {
+            self.try_get_component(name).and_then(|c| {
+                let earth = c.earth_position().map_err(NetworkError::Unsupported)?;
+                if earth.borrow().state() == state {
+                    return Ok(());
+                }
+                self.log_changes(std::slice::from_ref(&c), || match state {
+                    PositionState::Open => c.open_earth(),
+                    PositionState::Closed => c.close_earth(),
+                    PositionState::Transit => transit(name),
+                })
+            })
+        };
+
+        for (name, state) in earth_opens.iter() {
+            if let Err(e) = apply_earth(name, *state) {
+                errors.push(e);
+            }
+        }
+        for (name, state) in sorted(&snapshot.positions) {
+            let result = self.try_get_component(&name).and_then(|c| {
+                let pos = c.position().map_err(NetworkError::Unsupported)?;
+                if pos.borrow().state() == state {
+                    return Ok(());
+                }
+                match state {
+                    PositionState::Open => self.open(&name),
+                    PositionState::Closed => self.close(&name),
+                    PositionState::Transit => transit(&name),
+                }
+            });
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        for (name, state) in earth_closes.iter() {
+            if let Err(e) = apply_earth(name, *state) {
+                errors.push(e);
+            }
+        }
+        let mut measurements: Vec<(&String, &f64)> = snapshot.measurements.iter().collect();
+        measurements.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in measurements {
+            if let Err(e) = self
+                .try_get_component(name)
+                .and_then(|c| self.record_update(&c, *value))
+            {
+                errors.push(e);
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// Return the rated components whose measured current exceeds their rating, with the ratio of current to rating. Current transformers use their own measurement, lines and transformers that of an adjacent current transformer. Measurements of bad quality are ignored.
+    pub fn overloaded(&self) -> Vec<(String, f64)> {
+        let components = self.components.borrow().clone();
+        components
+            .iter()
+            .filter_map(|c| {
+                let rating = c.rating().ok()?.get()?;
+                let value = self.measured_current(c)?;
+                if value > rating {
+                    Some((c.name().clone(), value / rating))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Return the components with a terminal whose ampacity is below the current through the component, with the terminal index, current and ampacity in A. The current is the measured current if known, see [Network::overloaded], otherwise the current rating.
+    pub fn ampacity_violations(&self) -> Vec<(String, usize, f64, f64)> {
+        let components = self.components.borrow().clone();
+        let mut violations = vec![];
+        for c in components.iter() {
+            let current = match self.measured_current(c).or_else(|| c.rating().ok()?.get()) {
+                Some(current) => current,
+                None => continue,
+            };
+            for (i, t) in c.terminals().enumerate() {
+                if let Some(ampacity) = t.borrow().ampacity() {
+                    if current > ampacity {
+                        violations.push((c.name().clone(), i, current, ampacity));
+                    }
+                }
+            }
+        }
+        violations
+    }
+
+    /// Return the good quality current measurement of a component in A; its own measurement for current transformers, otherwise that of an adjacent current transformer
+    fn measured_current(&self, c: &Rc<dyn Component>) -> Option<f64> {
+        let current = |c: &Rc<dyn Component>| -> Option<f64> {
+            let m = c.measurement().ok()?.borrow();
+            if m.unit() == Unit::Ampere && m.quality() == Quality::Good {
+                Some(m.value())
+            } else {
+                None
+            }
+        };
+        current(c).or_else(|| self.adjacent_components(c).iter().find_map(current))
+    }
+
+    /// Hash the topology; the node names and the name, type and terminal connections of every component. Measurement values and switchgear positions are excluded, see [Network::state_hash]. Independent of insertion order, so structurally identical networks hash equal, and stable between runs and builds.
+    pub fn topology_hash(&self) -> u64 {
+        let mut lines: Vec<String> = self
+            .nodes
+            .borrow()
+            .iter()
+            .map(|n| format!("node {}", n.name()))
+            .collect();
+        for c in self.components.borrow().iter() {
+            let terminals: Vec<String> = c
+                .terminals()
+                .map(|t| match t.borrow().get_node() {
+                    Ok(n) => n.name().clone(),
+                    Err(_) => String::new(),
+                })
+                .collect();
+            lines.push(format!(
+                "component {} {:?} {:?}",
+                c.name(),
+                c.r#type(),
+                terminals
+            ));
+        }
+        hash_sorted(lines)
+    }
+
+    /// Hash the state; the switchgear and earthing positions of every component. Independent of insertion order.
+    pub fn state_hash(&self) -> u64 {
+        let lines = self
+            .components
+            .borrow()
+            .iter()
+            .map(|c| {
+                let position = c.position().map(|p| p.borrow().state()).ok();
+                let earth = c.earth_position().map(|p| p.borrow().state()).ok();
+                format!("{} {:?} {:?}", c.name(), position, earth)
+            })
+            .collect();
+        hash_sorted(lines)
+    }
+
+    /// Compare the network with another version of it and report the differences; added and removed nodes and components, changed switchgear positions and changed measurement values
+    pub fn diff(&self, other: &Network) -> NetworkDiff {
+        let node_names = |net: &Network| -> Vec<String> {
+            net.nodes
+                .borrow()
+                .iter()
+                .map(|n| n.name().clone())
+                .collect()
+        };
+        let component_names = |net: &Network| -> Vec<String> {
+            net.components
+                .borrow()
+                .iter()
+                .map(|c| c.name().clone())
+                .collect()
+        };
+        let missing = |from: &Vec<String>, with: &Vec<String>| -> Vec<String> {
+            from.iter().filter(|n| !with.contains(n)).cloned().collect()
+        };
+        let (old_nodes, new_nodes) = (node_names(self), node_names(other));
+        let (old_components, new_components) = (component_names(self), component_names(other));
+
+        let new_states = other.switchgear_states();
+        let changed_positions = self
+            .switchgear_states()
+            .into_iter()
+            .filter_map(|(name, old)| {
+                let (_, new) = new_states.iter().find(|(n, _)| *n == name)?;
+                if old != *new {
+                    Some((name, old, *new))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let new_measurements = other.measurements();
+        let changed_measurements = self
+            .measurements()
+            .into_iter()
+            .filter_map(|(name, old, _)| {
+                let (_, new, _) = new_measurements.iter().find(|(n, _, _)| *n == name)?;
+                if old != *new {
+                    Some((name, old, *new))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        NetworkDiff::new(
+            missing(&new_nodes, &old_nodes),
+            missing(&old_nodes, &new_nodes),
+            missing(&new_components, &old_components),
+            missing(&old_components, &new_components),
+            changed_positions,
+            changed_measurements,
+        )
+    }
+
+    /// True if both networks have the same nodes and components by name, each component has the same type and terminal connections by node name, and all switchgear positions, integrated earthing switch positions and measurement values are equal.
+    /// Elements are matched by name, not by [Rc] identity, and the order in which they were added and the network names are ignored.
+    pub fn structurally_eq(&self, other: &Network) -> bool {
+        if !self.diff(other).is_empty() {
+            return false;
+        }
+        let earth = |c: &Rc<dyn Component>| c.earth_position().ok().map(|e| e.borrow().state());
+        let terminals = |c: &Rc<dyn Component>| -> Vec<Option<String>> {
+            c.terminals()
+                .map(|t| t.borrow().get_node().ok().map(|n| n.name().clone()))
+                .collect()
+        };
+        self.components
+            .borrow()
+            .iter()
+            .all(|c| match other.get_component(&c.name()) {
+                Ok(o) => {
+                    c.type_name() == o.type_name()
+                        && terminals(c) == terminals(&o)
+                        && earth(c) == earth(&o)
+                }
+                Err(_) => false,
+            })
+    }
+
+    /// Serialize the network, including all node and component settings, states and connections, to a json document. The output is deterministic; nodes and components are ordered by id and metadata by key.
+    pub fn to_json(&self) -> String {
+        let entry = |key: &str, value: JsonValue| (key.to_string(), value);
+        let metadata = |m: &HashMap<String, String>| {
+            let mut keys: Vec<&String> = m.keys().collect();
+            keys.sort();
+            JsonValue::Object(
+                keys.into_iter()
+                    .map(|k| (k.clone(), m[k].as_str().into()))
+                    .collect(),
+            )
+        };
+        let pair = |p: Option<(f64, f64)>| match p {
+            Some((x, y)) => JsonValue::Array(vec![x.into(), y.into()]),
+            None => JsonValue::Null,
+        };
+        let phase = |p: Option<Phase>| JsonValue::from(p.map(|p| format!("{:?}", p)));
+        let mut nodes = self.nodes.borrow().clone();
+        nodes.sort_by_key(|n| n.id());
+        let nodes = nodes
+            .iter()
+            .map(|n| {
+                JsonValue::Object(vec![
+                    entry("name", n.name().as_str().into()),
+                    entry("phase", phase(n.phase())),
+                    entry("enforce_phasing", n.enforces_phasing().into()),
+                    entry("voltage_level", n.voltage_level().into()),
+                    entry("source", n.is_source().into()),
+                    entry("energization_override", n.energization_override().into()),
+                    entry("metadata", metadata(&n.metadata().borrow())),
+                    entry("coordinates", pair(n.coordinates())),
+                ])
+            })
+            .collect();
+        let custom_types = self
+            .custom_types
+            .borrow()
+            .iter()
+            .skip(1)
+            .map(|t| {
+                JsonValue::Object(vec![
+                    entry("name", t.name().as_str().into()),
+                    entry("terminals", (t.terminals() as f64).into()),
+                    entry("conducts", t.conducts().into()),
+                ])
+            })
+            .collect();
+        let mut components = self.components.borrow().clone();
+        components.sort_by_key(|c| c.id());
+        let components = components
+            .iter()
+            .map(|c| {
+                let terminals = c
+                    .terminals()
+                    .map(|t| {
+                        let t = t.borrow();
+                        JsonValue::Object(vec![
+                            entry("node", t.get_node().ok().map(|n| n.name().clone()).into()),
+                            entry("phase", phase(t.phase())),
+                            entry("ampacity", t.ampacity().into()),
+                        ])
+                    })
+                    .collect();
+                let mut fields = vec![
+                    entry("name", c.name().as_str().into()),
+                    entry("type", c.type_name().into()),
+                    entry("metadata", metadata(&c.metadata().borrow())),
+                    entry("coordinates", pair(c.coordinates())),
+                    entry("terminals", JsonValue::Array(terminals)),
+                ];
+                if let Ok(pos) = c.position() {
+                    fields.push(entry("position", pos.borrow().to_json()));
+                }
+                if let Ok(earth) = c.earth_position() {
+                    fields.push(entry("earth_position", earth.borrow().to_json()));
+                }
+                if let Ok(ar) = c.auto_reclose() {
+                    let ar = ar.borrow().as_ref().map(|ar| ar.to_json());
+                    fields.push(entry("auto_reclose", ar.unwrap_or(JsonValue::Null)));
+                }
+                if let Ok(m) = c.measurement() {
+                    fields.push(entry("measurement", m.borrow().to_json()));
+                }
+                if let Ok(policy) = c.policy() {
+                    fields.push(entry("policy", format!("{:?}", policy.get()).into()));
+                }
+                if let Ok(kind) = c.earth_kind() {
+                    fields.push(entry("earth_kind", format!("{:?}", kind.get()).into()));
+                }
+                if let Ok(p) = c.active_power() {
+                    fields.push(entry("active_power", p.get().into()));
+                }
+                if let Ok(rating) = c.rating() {
+                    fields.push(entry("rating", rating.get().into()));
+                }
+                if let Ok(impedance) = c.impedance() {
+                    fields.push(entry("impedance", pair(impedance.get())));
+                }
+                JsonValue::Object(fields)
+            })
+            .collect();
+        JsonValue::Object(vec![
+            entry("name", self.name.as_str().into()),
+            entry("time", self.time.get().into()),
+            entry(
+                "maintenance_threshold",
+                self.maintenance_threshold().map(|t| t as f64).into(),
+            ),
+            entry("custom_types", JsonValue::Array(custom_types)),
+            entry("nodes", JsonValue::Array(nodes)),
+            entry("components", JsonValue::Array(components)),
+        ])
+        .to_string()
+    }
+
+    /// Deserialize a network from a json document written by [Network::to_json]
+    pub fn from_json(json: &str) -> Result<Network, NetworkError> {
+        let json = JsonValue::parse(json).map_err(NetworkError::Parse)?;
+        from_json_value(&json).map_err(NetworkError::Parse)
+    }
+
+    /// Save the network as json to the file at the given path
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), NetworkError> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Load a network from a json file written by [Network::save]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Network, NetworkError> {
+        Network::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Export the id, type, coordinates and state of every node and component as json for a rendering layer. Unpositioned elements have null coordinates and are listed separately so a layout engine can place them; coordinates that are not finite count as unpositioned, since json has no NaN or infinity.
+    pub fn layout_json(&self) -> String {
+        let mut elements = vec![];
+        let mut unpositioned = vec![];
+        let mut add = |id: &str, r#type: String, coordinates: Option<(f64, f64)>, state: String| {
+            let coordinates = coordinates.filter(|(x, y)| x.is_finite() && y.is_finite());
+            if coordinates.is_none() {
+                unpositioned.push(JsonValue::from(id));
+            }
+            elements.push(JsonValue::Object(vec![
+                ("id".to_string(), id.into()),
+                ("type".to_string(), r#type.into()),
+                ("x".to_string(), coordinates.map(|(x, _)| x).into()),
+                ("y".to_string(), coordinates.map(|(_, y)| y).into()),
+                ("state".to_string(), state.into()),
+            ]));
+        };
+        for n in self.nodes.borrow().iter() {
+            add(
+                n.name().as_str(),
+                "Node".to_string(),
+                n.coordinates(),
+                "n/a".to_string(),
+            );
+        }
+        for c in self.components.borrow().iter() {
+            add(&c.name(), c.type_name(), c.coordinates(), c.state_summary());
+        }
+        JsonValue::Object(vec![
+            ("elements".to_string(), JsonValue::Array(elements)),
+            ("unpositioned".to_string(), JsonValue::Array(unpositioned)),
+        ])
+        .to_string()
+    }
+
+    /// Return the positioned component closest to the given point, or None if no component has coordinates. Ties go to the component added first.
+    pub fn nearest_component(&self, x: f64, y: f64) -> Option<Rc<dyn Component>> {
+        let mut nearest: Option<(f64, Rc<dyn Component>)> = None;
+        for c in self.components.borrow().iter() {
+            if let Some((cx, cy)) = c.coordinates() {
+                let distance = (cx - x).hypot(cy - y);
+                if nearest.as_ref().is_none_or(|(d, _)| distance < *d) {
+                    nearest = Some((distance, c.clone()));
+                }
+            }
+        }
+        nearest.map(|(_, c)| c)
+    }
+
+    /// Assign coordinates to all nodes and components using a force-directed layout, see [force_directed]. Components are placed as vertices connected to the nodes on their terminals, existing coordinates are used as starting positions.
+    pub fn auto_layout(&self, iterations: usize) {
+        let nodes = self.nodes.borrow();
+        let components = self.components.borrow();
+        let mut positions: Vec<Option<(f64, f64)>> =
+            nodes.iter().map(|n| n.coordinates()).collect();
+        let mut edges = vec![];
+        for c in components.iter() {
+            positions.push(c.coordinates());
+            for i in connected_node_indices(&nodes, c) {
+                edges.push((i, positions.len() - 1));
+            }
+        }
+        let layout = force_directed(&positions, &edges, iterations);
+        for (n, p) in nodes.iter().zip(layout.iter()) {
+            n.set_coordinates(Some(*p));
+        }
+        for (c, p) in components.iter().zip(layout[nodes.len()..].iter()) {
+            c.set_coordinates(Some(*p));
+        }
+    }
+
+    /// Connect a component terminal to a node. Returns an error if the component or node do not exist, or if the connection fails see [Component::connect]
+    pub fn connect(
+        &self,
+        node_name: &str,
+        component_name: &str,
+        terminal: usize,
+    ) -> Result<(), String> {
+        let n = self.get_node(node_name);
+        let c = self.get_component(component_name);
+        match (n, c) {
+            (Err(_), Err(_)) => Err(format!(
+                "No node with name {} and component with name {} exist in network {}",
+                node_name,
+                component_name,
+                self.name()
+            )),
+            (Err(_), _) => Err(format!(
+                "No node with name {} exists in network {}",
+                node_name,
+                self.name()
+            )),
+            (_, Err(_)) => Err(format!(
+                "No component with name {} exists in network {}",
+                component_name,
+                self.name()
+            )),
+            (Ok(n), Ok(c)) => {
+                c.connect(n.clone(), terminal)?;
+                n.add_component(c)
+            }
+        }
+    }
+
+    /// Get a reference to a node like [Network::get_node], but return [NetworkError::Busy] instead of panicking if the node list is borrowed
+    pub fn try_get_node(&self, name: &str) -> Result<Rc<Node>, NetworkError> {
+        match self.nodes.try_borrow() {
+            Ok(nodes) => match nodes.iter().find(|x| x.name() == name) {
+                Some(node) => Ok(node.clone()),
+                None => Err(NetworkError::NotFound(format!(
+                    "Node with name {} does not exist in network {}",
+                    name,
+                    self.name()
+                ))),
+            },
+            Err(_) => Err(NetworkError::Busy(format!(
+                "Nodes of network {} are already borrowed",
+                self.name()
+            ))),
+        }
+    }
+
+    /// Get a reference to a component like [Network::get_component], but return [NetworkError::Busy] instead of panicking if the component list is borrowed
+    pub fn try_get_component(&self, name: &str) -> Result<Rc<dyn Component>, NetworkError> {
+        match self.components.try_borrow() {
+            Ok(components) => match components.iter().find(|x| *x.name() == name) {
+                Some(component) => Ok(component.clone()),
+                None => Err(NetworkError::NotFound(format!(
+                    "Component with name {} does not exist in network {}",
+                    name,
+                    self.name()
+                ))),
+            },
+            Err(_) => Err(NetworkError::Busy(format!(
+                "Components of network {} are already borrowed",
+                self.name()
+            ))),
+        }
+    }
+
+    /// Connect a component terminal to a node like [Network::connect], but return [NetworkError::Busy] instead of panicking on a conflicting borrow
+    pub fn try_connect(
+        &self,
+        node_name: &str,
+        component_name: &str,
+        terminal: usize,
+    ) -> Result<(), NetworkError> {
+        let n = self.try_get_node(node_name)?;
+        let c = self.try_get_component(component_name)?;
+        n.try_add_component(c.clone())?;
+        if let Err(e) = c.try_connect(n.clone(), terminal) {
+            n.remove_component(c)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Open the named switchgear, returning [NetworkError::Busy] instead of panicking on a conflicting borrow, see [Component::try_open]
+    pub fn try_open(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.try_get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.try_open())
+    }
+
+    /// Close the named switchgear, returning [NetworkError::Busy] instead of panicking on a conflicting borrow, see [Component::try_close]
+    pub fn try_close(&self, name: &str) -> Result<(), NetworkError> {
+        let c = self.try_get_component(name)?;
+        self.log_changes(std::slice::from_ref(&c), || c.try_close())
+    }
+
+    /// Disconnect a component from a node if it is connected. Returns an error if the component or node do not exist
+    pub fn disconnect(&self, node_name: &str, component_name: &str) -> Result<(), String> {
+        let n = self.get_node(node_name);
+        let c = self.get_component(component_name);
+        match (n, c) {
+            (Err(_), Err(_)) => Err(format!(
+                "No node with name {} and component with name {} exist in network {}",
+                node_name,
+                component_name,
+                self.name()
+            )),
+            (Err(_), _) => Err(format!(
+                "No node with name {} exists in network {}",
+                node_name,
+                self.name()
+            )),
+            (_, Err(_)) => Err(format!(
+                "No component with name {} exists in network {}",
+                component_name,
+                self.name()
+            )),
+            (Ok(n), Ok(c)) => c.disconnect(n).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Split csv text into its non-empty rows with their line number, skipping the given header on the first line. Errors if a row does not have the given number of fields.
+fn csv_rows<'a>(
+    csv: &'a str,
+    header: &'static str,
+    columns: usize,
+) -> impl Iterator<Item = Result<(usize, Vec<&'a str>), NetworkError>> {
+    csv.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter(move |(i, line)| !(*i == 0 && line.replace(' ', "") == header))
+        .map(move |(i, line)| {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() == columns {
+                Ok((i + 1, fields))
+            } else {
+                Err(NetworkError::Parse(format!(
+                    "Line {}: expected {} fields, found {}",
+                    i + 1,
+                    columns,
+                    fields.len()
+                )))
+            }
+        })
+}
+
+/// Build a network from a parsed json document, see [Network::from_json]
+fn from_json_value(json: &JsonValue) -> Result<Network, String> {
+    let string = |v: &JsonValue, key: &str| -> Result<String, String> {
+        match v.field(key)?.as_str() {
+            Some(s) => Ok(s.to_string()),
+            None => Err(format!("Invalid json: {} is not a string", key)),
+        }
+    };
+    let array = |v: &JsonValue, key: &str| -> Result<Vec<JsonValue>, String> {
+        match v.field(key)?.as_array() {
+            Some(a) => Ok(a.clone()),
+            None => Err(format!("Invalid json: {} is not an array", key)),
+        }
+    };
+    let phase = |v: &JsonValue| -> Result<Option<Phase>, String> {
+        match v.field("phase")?.as_str() {
+            None => Ok(None),
+            Some("A") => Ok(Some(Phase::A)),
+            Some("B") => Ok(Some(Phase::B)),
+            Some("C") => Ok(Some(Phase::C)),
+            Some(p) => Err(format!("Invalid json: unknown phase {}", p)),
+        }
+    };
+    let metadata = |v: &JsonValue| -> HashMap<String, String> {
+        v.get("metadata")
+            .and_then(|m| m.as_object())
+            .map(|m| {
+                m.iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let coordinates = |v: &JsonValue| -> Option<(f64, f64)> {
+        let c = v.get("coordinates")?.as_array()?;
+        Some((c.first()?.as_f64()?, c.get(1)?.as_f64()?))
+    };
+    let flag = |v: &JsonValue, key: &str| v.get(key).and_then(|v| v.as_bool()) == Some(true);
+
+    let net = Network::new(&string(json, "name")?);
+    net.time
+        .set(json.get("time").and_then(|t| t.as_f64()).unwrap_or(0f64));
+    net.set_maintenance_threshold(
+        json.get("maintenance_threshold")
+            .and_then(|t| t.as_f64())
+            .map(|t| t as u32),
+    );
+    for n in array(json, "nodes")? {
+        let name = string(&n, "name")?;
+        net.create_node(&name)?;
+        let node = net.get_node(&name)?;
+        node.set_phase(phase(&n)?);
+        node.set_enforce_phasing(flag(&n, "enforce_phasing"));
+        node.set_voltage_level(n.get("voltage_level").and_then(|v| v.as_f64()));
+        node.set_source(flag(&n, "source"));
+        node.set_energization_override(n.get("energization_override").and_then(|o| o.as_bool()));
+        *node.metadata().borrow_mut() = metadata(&n);
+        node.set_coordinates(coordinates(&n));
+    }
+    for t in json
+        .get("custom_types")
+        .and_then(|t| t.as_array())
+        .cloned()
+        .unwrap_or_default()
+    {
+        let terminals = match t.field("terminals")?.as_f64() {
+            Some(terminals) => terminals as usize,
+            None => return Err("Invalid json: terminals is not a number".to_string()),
+        };
+        net.register_custom_type(&string(&t, "name")?, terminals, flag(&t, "conducts"))
+            .map_err(|e| e.to_string())?;
+    }
+    for c in array(json, "components")? {
+        let name = string(&c, "name")?;
+        let r#type = net
+            .parse_type(&string(&c, "type")?)
+            .map_err(|e| e.to_string())?;
+        if net.check_name(&name).is_err() {
+            return Err(format!("Invalid json: duplicate name {}", name));
+        }
+        let component = net.instantiate(r#type, &name);
+        *component.metadata().borrow_mut() = metadata(&c);
+        component.set_coordinates(coordinates(&c));
+        if let (Some(json), Ok(pos)) = (c.get("position"), component.position()) {
+            *pos.borrow_mut() = SwitchgearPosition::from_json(json)?;
+        }
+        if let (Some(json), Ok(earth)) = (c.get("earth_position"), component.earth_position()) {
+            *earth.borrow_mut() = SwitchgearPosition::from_json(json)?;
+        }
+        if let (Some(json), Ok(ar)) = (c.get("auto_reclose"), component.auto_reclose()) {
+            if !json.is_null() {
+                *ar.borrow_mut() = Some(AutoReclose::from_json(json)?);
+            }
+        }
+        if let (Some(json), Ok(m)) = (c.get("measurement"), component.measurement()) {
+            *m.borrow_mut() = Measurement::from_json(json)?;
+        }
+        if let (Some(json), Ok(policy)) = (c.get("policy"), component.policy()) {
+            policy.set(match json.as_str() {
+                Some("MagneticCoupling") => EnergizationPolicy::MagneticCoupling,
+                Some("GalvanicIsolation") => EnergizationPolicy::GalvanicIsolation,
+                _ => {
+                    return Err(format!(
+                        "Invalid json: unknown energization policy {}",
+                        json
+                    ))
+                }
+            });
+        }
+        if let (Some(json), Ok(kind)) = (c.get("earth_kind"), component.earth_kind()) {
+            kind.set(match json.as_str() {
+                Some("SystemEarth") => EarthKind::SystemEarth,
+                Some("MaintenanceEarth") => EarthKind::MaintenanceEarth,
+                _ => return Err(format!("Invalid json: unknown earth kind {}", json)),
+            });
+        }
+        if let (Some(json), Ok(p)) = (c.get("active_power"), component.active_power()) {
+            p.set(json.as_f64().unwrap_or(0f64));
+        }
+        if let (Some(json), Ok(rating)) = (c.get("rating"), component.rating()) {
+            rating.set(json.as_f64());
+        }
+        if let (Some(json), Ok(impedance)) = (c.get("impedance"), component.impedance()) {
+            impedance.set(
+                json.as_array()
+                    .and_then(|z| Some((z.first()?.as_f64()?, z.get(1)?.as_f64()?))),
+            );
+        }
+        net.register(component.clone());
+        for (i, t) in array(&c, "terminals")?.iter().enumerate() {
+            {
+                let mut terminal = component.terminal(i)?.borrow_mut();
+                terminal.set_phase(phase(t)?);
+                terminal.set_ampacity(t.get("ampacity").and_then(|a| a.as_f64()));
+            }
+            if let Some(node) = t.field("node")?.as_str() {
+                net.connect(node, &name, i)?;
+            }
+        }
+    }
+    Ok(net)
+}
+
+/// True for disconnectors, with or without integrated earthing switch
+fn is_disconnector(c: &Rc<dyn Component>) -> bool {
+    c.r#type() == ComponentType::Disconnector || c.r#type() == ComponentType::DisconnectorEarth
+}
+
+/// Follow the series chain leaving the disconnector at the given node, through two-terminal components other than circuit breakers, and return the disconnector it ends in. None if the chain ends in a circuit breaker, a branching node or an open end.
+fn series_disconnector(from: &Rc<dyn Component>, mut node: Rc<Node>) -> Option<Rc<dyn Component>> {
+    let mut previous = from.clone();
+    loop {
+        let through: Vec<Rc<dyn Component>> = node
+            .components()
+            .into_iter()
+            .filter(|c| !Rc::ptr_eq(c, &previous) && c.terminals().count() > 1)
+            .collect();
+        let next = match &through[..] {
+            [next] => next.clone(),
+            _ => return None,
+        };
+        if Rc::ptr_eq(&next, from)
+            || next.r#type() == ComponentType::CircuitBreaker
+            || next.terminals().count() != 2
+        {
+            return None;
+        }
+        if is_disconnector(&next) {
+            return Some(next);
+        }
+        node = next.other_node(&node)?;
+        previous = next;
+    }
+}
+
+/// True if the component conducts when the switchgear is in the position states of the given snapshot; components not in it use their current state
+fn conducts_in(snapshot: &HashMap<String, PositionState>, c: &Rc<dyn Component>) -> bool {
+    match snapshot.get(&*c.name()) {
+        Some(state) if c.position().is_ok() => *state == PositionState::Closed,
+        _ => c.conducts(),
+    }
+}
+
+/// Hash the given lines after sorting them, using 64-bit FNV-1a so the hash is stable across Rust versions and platforms. Each line is terminated by a 0 byte so line boundaries are part of the hash.
+fn hash_sorted(mut lines: Vec<String>) -> u64 {
+    lines.sort();
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for line in &lines {
+        for byte in line.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Return the indices in the given node list of the nodes connected to the component terminals, in terminal order
+fn connected_node_indices(nodes: &[Rc<Node>], c: &Rc<dyn Component>) -> Vec<usize> {
+    c.terminals()
+        .filter_map(|t| t.borrow().get_node().ok())
+        .filter_map(|n| nodes.iter().position(|x| Rc::ptr_eq(x, &n)))
+        .collect()
+}
+
+/// Depth-first step of [Network::all_paths]; extend the path ending at node i through each conducting component to unvisited nodes, recording it when the end is reached
+fn extend_paths(
+    nodes: &[Rc<Node>],
+    i: usize,
+    end: usize,
+    max_len: usize,
+    visited: &mut Vec<bool>,
+    path: &mut Vec<Rc<dyn Component>>,
+    paths: &mut Vec<Vec<Rc<dyn Component>>>,
+) {
+    if i == end {
+        paths.push(path.clone());
+        return;
+    }
+    if path.len() == max_len {
+        return;
+    }
+    for c in nodes[i].components().iter().filter(|c| c.conducts()) {
+        for j in connected_node_indices(nodes, c) {
+            if visited[j] {
+                continue;
+            }
+            visited[j] = true;
+            path.push(c.clone());
+            extend_paths(nodes, j, end, max_len, visited, path, paths);
+            path.pop();
+            visited[j] = false;
+        }
+    }
+}
+
+/// True if the component can connect a node to ground; earthing switches and disconnectors with integrated earthing switch
+fn earths(c: &Rc<dyn Component>) -> bool {
+    c.r#type() == ComponentType::EarthingSwitch || c.earth_position().is_ok()
+}
+
+/// Return the node earthed by the component; the node of an earthing switch while it is closed, or the earthing side of a disconnector with integrated earthing switch while it is closed to earth
+fn earthed_node(c: &Rc<dyn Component>) -> Option<Rc<Node>> {
+    let terminal = if c.earth_position().is_ok_and(|p| p.borrow().is_closed()) {
+        1
+    } else if c.r#type() == ComponentType::EarthingSwitch
+        && c.position().is_ok_and(|p| p.borrow().is_closed())
+    {
+        0
+    } else {
+        return None;
+    };
+    c.terminal(terminal).ok()?.borrow().get_node().ok()
+}
+
+/// True if any terminal of the component is connected to the given node
+fn is_connected_to(c: &Rc<dyn Component>, node: &Rc<Node>) -> bool {
+    c.terminal_index_of_node(node).is_some()
+}
+
+/// Find the root of a set in a union-find parent list, compressing the path along the way
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Network {}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::measurement::DerivedMeasurement;
+    use crate::terminal::Terminal;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    /// Earthing switch with one terminal too many
+    struct MalformedEarthingSwitch {
+        name: RefCell<String>,
+        metadata: RefCell<HashMap<String, String>>,
+        coordinates: Cell<Option<(f64, f64)>>,
+        id: Cell<Option<u32>>,
+        terminals: [RefCell<Terminal>; 2],
+    }
+
+    impl Component for MalformedEarthingSwitch {
+        fn new(name: &str) -> MalformedEarthingSwitch {
+            MalformedEarthingSwitch {
+                name: RefCell::new(name.to_string()),
+                metadata: RefCell::new(HashMap::new()),
+                coordinates: Cell::new(None),
+                id: Cell::new(None),
+                terminals: [RefCell::new(Terminal::new()), RefCell::new(Terminal::new())],
+            }
+        }
+
+        fn r#type(&self) -> ComponentType {
+            ComponentType::EarthingSwitch
+        }
+
+        fn name_cell(&self, _: Key) -> &RefCell<String> {
+            &self.name
+        }
+
+        fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+            &self.metadata
+        }
+
+        fn coordinates_cell(&self, _: Key) -> &Cell<Option<(f64, f64)>> {
+            &self.coordinates
+        }
+
+        fn id_cell(&self, _: Key) -> &Cell<Option<u32>> {
+            &self.id
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn terminal(&self, index: usize) -> Result<&RefCell<Terminal>, String> {
+            self.terminals
+                .get(index)
+                .ok_or_else(|| "No such terminal".to_string())
+        }
+    }
+
+    #[test]
+    fn network_name() {
+        let net = Network::new("net");
+        assert_eq!(net.name().as_str(), "net")
+    }
+
+    #[test]
+    fn network_component() {
+        let net = Network::new("net");
+
+        assert_eq!(net.component_count(), 0);
+        assert!(net.get_component("cb").is_err());
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        assert!(net.get_component("cb").unwrap().name().as_str() == "cb");
+        assert!(net.create_node("cb").is_err());
+        assert!(net.create_component::<Disconnector>("cb").is_err());
+        assert_eq!(net.component_count(), 1);
+
+        net.create_component::<Disconnector>("ds").unwrap();
+        assert!(net.get_component("ds").unwrap().name().as_str() == "ds");
+        assert_eq!(net.component_count(), 2);
+
+        net.remove_component("cb").unwrap();
+        assert!(net.get_component("cb").is_err());
+        assert_eq!(net.component_count(), 1);
+        assert!(net.remove_component("cb").is_err());
+
+        net.remove_component("ds").unwrap();
+        assert_eq!(net.component_count(), 0);
+    }
+
+    #[test]
+    fn network_remove_connected_component() {
+        let net = Network::from_edges(&[("n1", ComponentType::CircuitBreaker, "n2")]).unwrap();
+        let cb = net.get_component("component_0").unwrap();
+        let n1 = net.get_node("n1").unwrap();
+        let n2 = net.get_node("n2").unwrap();
+
+        net.remove_component("component_0").unwrap();
+        assert!(net.get_component("component_0").is_err());
+        assert!(cb.terminals().all(|t| t.borrow().get_node().is_err()));
+        assert!(n1.remove_component(cb.clone()).is_err());
+        assert!(n2.remove_component(cb).is_err());
+        assert_eq!(
+            net.remove_component("component_0"),
+            Err(NetworkError::NotFound("component_0".to_string()))
+        );
+    }
+
+    #[test]
+    fn network_node() {
+        let net = Network::new("net");
+
+        assert_eq!(net.node_count(), 0);
+        assert!(net.get_node("node").is_err());
+        net.create_node("node").unwrap();
+        assert!(net.get_node("node").unwrap().name() == "node");
+        assert!(net.create_component::<CircuitBreaker>("node").is_err());
+        assert!(net.create_node("node").is_err());
+        assert_eq!(net.node_count(), 1);
+
+        net.create_node("node2").unwrap();
+        assert!(net.get_node("node2").unwrap().name() == "node2");
+        assert_eq!(net.node_count(), 2);
+
+        net.remove_node("node").unwrap();
+        assert!(net.get_node("node").is_err());
+        assert!(net.remove_node("node").is_err());
+        assert_eq!(net.node_count(), 1);
+
+        net.remove_node("node2").unwrap();
+        assert_eq!(net.node_count(), 0);
+    }
+
+    #[test]
+    fn network_merge_nodes() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::CircuitBreaker, "n2"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.connect("b", "vt", 0).unwrap();
+
+        // The disconnector spans both nodes, merging them would short it
+        assert!(net.merge_nodes("a", "b").is_err());
+        assert_eq!(net.node_count(), 4);
+        let b = net.get_node("b").unwrap();
+        assert_eq!(b.degree(), 3);
+        let vt = net.get_component("vt").unwrap();
+        assert!(vt
+            .terminals()
+            .all(|t| Rc::ptr_eq(&t.borrow().get_node().unwrap(), &b)));
+
+        net.remove_component("component_1").unwrap();
+        let a = net.merge_nodes("a", "b").unwrap();
+        assert!(Rc::ptr_eq(&a, &net.get_node("a").unwrap()));
+        assert!(net.get_node("b").is_err());
+        assert_eq!(net.node_count(), 3);
+        assert_eq!(a.degree(), 3);
+        for name in ["component_0", "component_2", "vt"] {
+            let c = net.get_component(name).unwrap();
+            assert!(c
+                .terminals()
+                .any(|t| Rc::ptr_eq(&t.borrow().get_node().unwrap(), &a)));
+            assert!(a.add_component(c).is_err());
+        }
+
+        assert!(net.merge_nodes("a", "a").is_err());
+        assert!(matches!(
+            net.merge_nodes("a", "b"),
+            Err(NetworkError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn network_nodes_at_level() {
+        let net = Network::new("net");
+        for (name, kv) in [
+            ("hv1", Some(150f64)),
+            ("mv", Some(50f64)),
+            ("hv2", Some(150f64)),
+            ("x", None),
+        ] {
+            net.create_node(name).unwrap();
+            net.get_node(name).unwrap().set_voltage_level(kv);
+        }
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("hv1", "tf", 0).unwrap();
+        net.connect("mv", "tf", 1).unwrap();
+
+        let hv: Vec<String> = net
+            .nodes_at_level(150f64)
+            .iter()
+            .map(|n| n.name().clone())
+            .collect();
+        assert_eq!(hv, vec!["hv1", "hv2"]);
+        assert_eq!(net.nodes_at_level(50f64)[0].name().as_str(), "mv");
+        assert!(net.nodes_at_level(400f64).is_empty());
+    }
+
+    #[test]
+    fn network_connect() {
+        let net = Network::new("net");
+        net.create_node("node").unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+
+        assert!(net.connect("does not", "exist", 0).is_err());
+        assert!(net.disconnect("node", "cb").is_err());
+        assert!(net.connect("node", "cb", 2).is_err());
+        assert!(net.connect("node", "cb", 0).is_ok());
+        assert!(net.connect("node", "cb", 1).is_err());
+        assert!(net.disconnect("node", "cb").is_ok());
+        assert!(net.connect("node", "cb", 1).is_ok());
+    }
+
+    #[test]
+    fn network_disconnect_clears_node() {
+        let net = Network::new("net");
+        net.create_node("node").unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        let n = net.get_node("node").unwrap();
+        let cb = net.get_component("cb").unwrap();
+
+        net.connect("node", "cb", 0).unwrap();
+        assert!(n.add_component(cb.clone()).is_err());
+        net.disconnect("node", "cb").unwrap();
+        assert!(cb.terminal(0).unwrap().borrow().get_node().is_err());
+        assert!(n.remove_component(cb).is_err());
+    }
+
+    #[test]
+    fn network_clear_connections() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        let n2 = net.get_node("n2").unwrap();
+        // Both components are already children of n2
+        assert!(n2
+            .add_component(net.get_component("component_0").unwrap())
+            .is_err());
+        assert!(n2
+            .add_component(net.get_component("component_1").unwrap())
+            .is_err());
+
+        net.clear_connections();
+        assert_eq!(net.node_count(), 3);
+        assert_eq!(net.component_count(), 2);
+        for name in ["component_0", "component_1"] {
+            let c = net.get_component(name).unwrap();
+            assert!(c.terminals().all(|t| t.borrow().get_node().is_err()));
+            assert!(n2.remove_component(c).is_err());
+        }
+        assert!(net.connect("n1", "component_1", 0).is_ok());
+    }
+
+    #[test]
+    fn network_components_of_type() {
+        let net = Network::new("net");
+        net.create_component::<CircuitBreaker>("cb1").unwrap();
+        net.create_component::<Disconnector>("ds").unwrap();
+        net.create_component::<CircuitBreaker>("cb2").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+
+        let cbs = net.components_of_type(ComponentType::CircuitBreaker);
+        assert_eq!(cbs.len(), 2);
+        assert!(Rc::ptr_eq(&cbs[0], &net.get_component("cb1").unwrap()));
+        assert!(Rc::ptr_eq(&cbs[1], &net.get_component("cb2").unwrap()));
+        assert_eq!(net.components_of_type(ComponentType::Disconnector).len(), 1);
+        assert_eq!(
+            net.components_of_type(ComponentType::Transformer)[0]
+                .name()
+                .as_str(),
+            "tf"
+        );
+        assert!(net
+            .components_of_type(ComponentType::VoltageTransformer)
+            .is_empty());
+    }
+
+    #[test]
+    fn network_to_ascii() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "n1"),
+            ("n1", ComponentType::CircuitBreaker, "line"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.connect("line", "vt", 0).unwrap();
+        net.get_component("component_1").unwrap().close().unwrap();
+
+        let ascii = net.to_ascii();
+        assert_eq!(ascii, net.to_ascii());
+        assert!(ascii.contains("==== bus ===="));
+        assert!(ascii.contains("component_0 (Disconnector) [open] -- n1"));
+        assert!(ascii.contains("component_1 (CircuitBreaker) [closed] -- line"));
+        assert!(ascii.contains("component_1 (CircuitBreaker) [closed] -- n1"));
+        assert!(ascii.contains("vt (Voltage Transformer)\n"));
+    }
+
+    #[test]
+    fn network_from_edges() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+            ("n3", ComponentType::CircuitBreaker, "n4"),
+            ("n4", ComponentType::Disconnector, "n5"),
+            ("n5", ComponentType::CircuitBreaker, "n1"),
+        ])
+        .unwrap();
+
+        assert_eq!(net.node_count(), 5);
+        assert_eq!(net.component_count(), 5);
+        let ring = ["n1", "n2", "n3", "n4", "n5", "n1"];
+        for i in 0..5 {
+            let c = net.get_component(&format!("component_{}", i)).unwrap();
+            for (t, node) in ring[i..i + 2].iter().enumerate() {
+                let n = c.terminal(t).unwrap().borrow().get_node().unwrap();
+                assert!(Rc::ptr_eq(&n, &net.get_node(node).unwrap()));
+            }
+        }
+        assert_eq!(
+            net.get_component("component_1").unwrap().r#type(),
+            ComponentType::Disconnector
+        );
+
+        assert!(matches!(
+            Network::from_edges(&[("n1", ComponentType::EarthingSwitch, "n2")]),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert!(Network::from_edges(&[("n1", ComponentType::CircuitBreaker, "n1")]).is_err());
+    }
+
+    #[test]
+    fn network_validate_switching() {
+        let net = Network::new("net");
+        for node in ["bus", "n1", "n2", "line"] {
+            net.create_node(node).unwrap();
+        }
+        net.create_component::<Disconnector>("ds1").unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<Disconnector>("ds2").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        for (node, c, t) in [
+            ("bus", "ds1", 0),
+            ("n1", "ds1", 1),
+            ("n1", "cb", 0),
+            ("n2", "cb", 1),
+            ("n2", "ds2", 0),
+            ("line", "ds2", 1),
+        ] {
+            net.connect(node, c, t).unwrap();
+        }
+
+        let close = |name: &str| SwitchOp::Close(name.to_string());
+        let open = |name: &str| SwitchOp::Open(name.to_string());
+
+        // Legal: disconnectors are operated with the breaker open
+        assert!(net
+            .validate_switching(&[
+                close("ds1"),
+                close("ds2"),
+                close("cb"),
+                open("cb"),
+                open("ds2"),
+            ])
+            .is_ok());
+
+        // Illegal: ds2 is closed under load
+        assert!(matches!(
+            net.validate_switching(&[close("ds1"), close("cb"), close("ds2")]),
+            Err(NetworkError::Interlock(_))
+        ));
+
+        // Invalid operations
+        assert!(net.validate_switching(&[open("ds1")]).is_err());
+        assert!(matches!(
+            net.validate_switching(&[close("vt")]),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert!(matches!(
+            net.validate_switching(&[close("nope")]),
+            Err(NetworkError::NotFound(_))
+        ));
+
+        // The network itself is not changed by validation
+        assert!(net
+            .switchgear_states()
+            .iter()
+            .all(|(_, state)| *state == PositionState::Open));
+    }
+
+    #[test]
+    fn network_sectionalized_busbar() {
+        let net = Network::new("station");
+        net.create_sectionalized_busbar("bb", 2, ComponentType::CircuitBreaker)
+            .unwrap();
+        net.create_node("feeder").unwrap();
+        net.create_component::<Line>("line").unwrap();
+        net.connect("bb_2", "line", 0).unwrap();
+        net.connect("feeder", "line", 1).unwrap();
+        assert_eq!(net.node_count(), 3);
+        let bs = net.get_component("bb_s1").unwrap();
+        assert_eq!(bs.r#type(), ComponentType::CircuitBreaker);
+
+        // Open section switch, the sections are independent buses
+        let model = net.reduce();
+        assert_eq!(model.bus_count(), 3);
+        assert_ne!(model.bus_of("bb_1"), model.bus_of("bb_2"));
+
+        // Closed section switch, the busbar is one bus
+        bs.close().unwrap();
+        let model = net.reduce();
+        assert_eq!(model.bus_count(), 2);
+        assert_eq!(model.bus_of("bb_1"), model.bus_of("bb_2"));
+        assert_eq!(
+            model.branches()[0].buses(),
+            &vec![
+                model.bus_of("bb_1").unwrap(),
+                model.bus_of("feeder").unwrap()
+            ]
+        );
+
+        net.create_sectionalized_busbar("bb2", 3, ComponentType::Disconnector)
+            .unwrap();
+        assert_eq!(net.get_node("bb2_3").unwrap().degree(), 1);
+        assert_eq!(net.get_node("bb2_2").unwrap().degree(), 2);
+        assert!(matches!(
+            net.create_sectionalized_busbar("bb", 2, ComponentType::CircuitBreaker),
+            Err(NetworkError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            net.create_sectionalized_busbar("bb3", 2, ComponentType::Line),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert!(matches!(
+            net.create_sectionalized_busbar("bb3", 0, ComponentType::Disconnector),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert_eq!(net.node_count(), 6);
+    }
+
+    #[test]
+    fn network_reduce() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "n1"),
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "hv"),
+        ])
+        .unwrap();
+        net.create_node("mv").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("hv", "tf", 0).unwrap();
+        net.connect("mv", "tf", 1).unwrap();
+        net.connect("n1", "es", 0).unwrap();
+        net.get_component("es").unwrap().close().unwrap();
+
+        // All switches open, every node is a separate bus
+        let model = net.reduce();
+        assert_eq!(model.bus_count(), 5);
+        assert_eq!(model.branches().len(), 1);
+        assert_eq!(model.branches()[0].component().name().as_str(), "tf");
+        let hv = model.bus_of("hv").unwrap();
+        let mv = model.bus_of("mv").unwrap();
+        assert_eq!(model.branches()[0].buses(), &vec![hv, mv]);
+
+        // Close the bay, bus to hv collapse into one bus
+        for name in ["component_0", "component_1", "component_2"] {
+            net.get_component(name).unwrap().close().unwrap();
+        }
+        let model = net.reduce();
+        assert_eq!(model.bus_count(), 2);
+        assert_eq!(model.bus_of("bus"), model.bus_of("hv"));
+        assert_ne!(model.bus_of("hv"), model.bus_of("mv"));
+        assert_eq!(model.buses()[model.bus_of("n1").unwrap()].nodes().len(), 4);
+
+        // Partially open
+        net.get_component("component_1").unwrap().open().unwrap();
+        let model = net.reduce();
+        assert_eq!(model.bus_count(), 3);
+        assert_eq!(model.bus_of("bus"), model.bus_of("n1"));
+        assert_eq!(model.bus_of("n2"), model.bus_of("hv"));
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn network_to_petgraph() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        net.create_node("n4").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        for (node, t) in [("n1", 0), ("n3", 1), ("n4", 2)] {
+            net.connect(node, "tf", t).unwrap();
+        }
+        net.connect("n4", "vt", 0).unwrap();
+
+        let graph = net.to_petgraph();
+        assert_eq!(graph.node_count(), 4);
+        // 2 two-terminal components, 3 transformer winding pairs
+        assert_eq!(graph.edge_count(), 5);
+        assert_eq!(
+            graph.edge_weights().filter(|c| *c.name() == "tf").count(),
+            3
+        );
+        assert!(graph.edge_weights().all(|c| *c.name() != "vt"));
+    }
+
+    #[test]
+    fn network_energized_nodes() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "n1"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("n1", "tf", 0).unwrap();
+        net.connect("n2", "tf", 1).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        let names = |nodes: Vec<Rc<Node>>| -> Vec<String> {
+            nodes.iter().map(|n| n.name().clone()).collect()
+        };
+
+        assert_eq!(names(net.energized_nodes()), vec!["src"]);
+        net.get_component("component_0").unwrap().close().unwrap();
+        assert_eq!(names(net.energized_nodes()), vec!["src", "n1", "n2"]);
+        net.get_component("component_1").unwrap().close().unwrap();
+        assert_eq!(names(net.energized_nodes()), vec!["src", "n1", "n2", "n3"]);
+    }
+
+    #[test]
+    fn network_transformer_policy() {
+        let net = Network::new("net");
+        net.create_node("hv").unwrap();
+        net.create_node("mv").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("hv", "tf", 0).unwrap();
+        net.connect("mv", "tf", 1).unwrap();
+        let tf = net.get_component("tf").unwrap();
+
+        // Energized from the hv side, the primary winding feeds the mv side under both policies
+        net.get_node("hv").unwrap().set_source(true);
+        assert_eq!(net.energized_nodes().len(), 2);
+        tf.policy()
+            .unwrap()
+            .set(EnergizationPolicy::GalvanicIsolation);
+        assert_eq!(net.energized_nodes().len(), 2);
+
+        // Back-fed from the mv side, only magnetic coupling energizes the primary
+        net.get_node("hv").unwrap().set_source(false);
+        net.get_node("mv").unwrap().set_source(true);
+        assert_eq!(net.energized_nodes()[0].name().as_str(), "mv");
+        assert_eq!(net.energized_nodes().len(), 1);
+        tf.policy()
+            .unwrap()
+            .set(EnergizationPolicy::MagneticCoupling);
+        assert_eq!(net.energized_nodes().len(), 2);
+    }
+
+    #[test]
+    fn network_energized_excluding() {
+        let ring = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+            ("c", ComponentType::CircuitBreaker, "src"),
+        ])
+        .unwrap();
+        ring.get_node("src").unwrap().set_source(true);
+        for i in 0..4 {
+            ring.get_component(&format!("component_{}", i))
+                .unwrap()
+                .close()
+                .unwrap();
+        }
+        assert!(ring.energized_excluding("b", &[]));
+        assert!(ring.energized_excluding("b", &["component_0"]));
+        assert!(ring.energized_excluding("b", &["component_0", "nope"]));
+        // Losing both breakers next to the source cuts off the ring
+        assert!(!ring.energized_excluding("b", &["component_0", "component_3"]));
+        // Losing both breakers next to b isolates only b
+        assert!(!ring.energized_excluding("b", &["component_1", "component_2"]));
+        assert!(ring.energized_excluding("a", &["component_1", "component_2"]));
+        // The network itself is not changed
+        assert_eq!(ring.energized_nodes().len(), 4);
+        assert!(!ring.energized_excluding("missing", &[]));
+    }
+
+    #[test]
+    fn network_n_minus_1() {
+        let ring = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+            ("c", ComponentType::CircuitBreaker, "src"),
+        ])
+        .unwrap();
+        ring.get_node("src").unwrap().set_source(true);
+        for i in 0..4 {
+            ring.get_component(&format!("component_{}", i))
+                .unwrap()
+                .close()
+                .unwrap();
+        }
+        assert!(ring.n_minus_1("b").is_empty());
+        assert!(ring.n_minus_1("src").is_empty());
+
+        // Opening the ring makes the remaining path critical
+        ring.get_component("component_3").unwrap().open().unwrap();
+        assert_eq!(ring.n_minus_1("b"), vec!["component_0", "component_1"]);
+
+        let radial = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+        ])
+        .unwrap();
+        radial.get_node("src").unwrap().set_source(true);
+        assert!(radial.n_minus_1("c").is_empty());
+        for i in 0..3 {
+            radial
+                .get_component(&format!("component_{}", i))
+                .unwrap()
+                .close()
+                .unwrap();
+        }
+        assert_eq!(radial.n_minus_1("c"), vec!["component_0", "component_2"]);
+        assert_eq!(radial.n_minus_1("a"), vec!["component_0"]);
+        assert!(radial.n_minus_1("nope").is_empty());
+    }
+
+    #[test]
+    fn network_auto_reclose() {
+        let net = Network::from_edges(&[("src", ComponentType::CircuitBreaker, "line")]).unwrap();
+        let cb = net.get_component("component_0").unwrap();
+        cb.set_auto_reclose(Some(AutoReclose::new(2, 1f64)))
+            .unwrap();
+        cb.close().unwrap();
+        let locked_out = || {
+            cb.auto_reclose()
+                .unwrap()
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .is_locked_out()
+        };
+
+        for _ in 0..2 {
+            net.protection_trip("component_0").unwrap();
+            assert!(!cb.conducts());
+            net.advance_time(0.5);
+            assert!(!cb.conducts());
+            net.advance_time(0.5);
+            assert!(cb.conducts());
+            assert!(!locked_out());
+        }
+
+        net.protection_trip("component_0").unwrap();
+        net.advance_time(10f64);
+        assert!(!cb.conducts());
+        assert!(locked_out());
+
+        // Without auto-reclose the breaker stays open
+        cb.set_auto_reclose(None).unwrap();
+        cb.close().unwrap();
+        net.protection_trip("component_0").unwrap();
+        net.advance_time(10f64);
+        assert!(!cb.conducts());
+        assert!(net
+            .get_component("component_0")
+            .unwrap()
+            .duplicate("copy")
+            .auto_reclose()
+            .unwrap()
+            .borrow()
+            .is_none());
+    }
+
+    #[test]
+    fn network_tie() {
+        let net = Network::from_edges(&[("src", ComponentType::CircuitBreaker, "a")]).unwrap();
+        net.create_node("b").unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.get_component("component_0").unwrap().close().unwrap();
+        assert_eq!(net.energized_nodes().len(), 2);
+
+        let jumper = net.tie("a", "b").unwrap();
+        assert_eq!(jumper.name().as_str(), "jumper_a_b");
+        assert!(jumper.conducts());
+        assert_eq!(net.energized_nodes().len(), 3);
+        assert_eq!(net.reduce().bus_count(), 1);
+        assert_eq!(net.islands().len(), 1);
+
+        assert!(matches!(
+            net.tie("a", "b"),
+            Err(NetworkError::DuplicateName(_))
+        ));
+        assert!(net.tie("a", "a").is_err());
+
+        // A failed tie leaves nothing behind, so it can be retried
+        let count = net.component_count();
+        assert!(net.tie("a", "c").is_err());
+        assert_eq!(net.component_count(), count);
+        assert_eq!(net.get_node("a").unwrap().degree(), 2);
+        net.create_node("c").unwrap();
+        net.tie("a", "c").unwrap();
+        assert_eq!(net.get_node("c").unwrap().degree(), 1);
+    }
+
+    #[test]
+    fn network_lint() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+            ("x", ComponentType::Line, "y"),
+        ])
+        .unwrap();
+        net.create_component::<EarthingSwitch>("es_ok").unwrap();
+        net.connect("bus", "es_ok", 0).unwrap();
+        net.create_component::<EarthingSwitch>("es_bad").unwrap();
+        net.connect("y", "es_bad", 0).unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("c", "tf", 0).unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.connect("c", "vt", 0).unwrap();
+
+        let lints = net.lint();
+        let flagged: Vec<(Severity, &str)> = lints
+            .iter()
+            .map(|l| (l.severity(), l.component().as_str()))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (Severity::Warning, "es_bad"),
+                (Severity::Warning, "tf"),
+                (Severity::Info, "component_0"),
+            ]
+        );
+        assert!(lints[2].message().contains("component_1"));
+        assert!(lints[1].to_string().starts_with("Warning: "));
+
+        // Shunts and series components other than breakers do not separate the disconnectors
+        net.create_component::<VoltageTransformer>("vt_a").unwrap();
+        net.connect("a", "vt_a", 0).unwrap();
+        net.disconnect("a", "component_1").unwrap();
+        net.create_node("a2").unwrap();
+        net.connect("a2", "component_1", 0).unwrap();
+        net.create_component::<CurrentTransformer>("ct").unwrap();
+        net.connect("a", "ct", 0).unwrap();
+        net.connect("a2", "ct", 1).unwrap();
+        let lints = net.lint();
+        assert_eq!(lints.len(), 3);
+        assert!(lints[2].message().contains("component_1"));
+
+        // Fixing the model clears the lints
+        net.connect("x", "tf", 1).unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.connect("x", "cb", 0).unwrap();
+        assert_eq!(net.lint().len(), 1);
+        net.replace_component("ct", ComponentType::CircuitBreaker)
+            .unwrap();
+        assert!(net.lint().is_empty());
+    }
+
+    #[test]
+    fn network_switching_warnings() {
+        let net = Network::from_edges(&[
+            ("src1", ComponentType::CircuitBreaker, "a"),
+            ("src2", ComponentType::CircuitBreaker, "b"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("a", ComponentType::CircuitBreaker, "b"),
+        ])
+        .unwrap();
+        for name in ["src1", "src2"] {
+            net.get_node(name).unwrap().set_source(true);
+        }
+        for name in ["component_0", "component_1"] {
+            net.get_component(name).unwrap().close().unwrap();
+        }
+        let close = |name: &str| SwitchOp::Close(name.to_string());
+        let open = |name: &str| SwitchOp::Open(name.to_string());
+
+        let warnings = net.switching_warnings(&[close("component_2")]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity(), Severity::Warning);
+        assert_eq!(warnings[0].component(), "component_2");
+
+        // Breakers are exempt, and once they tie the islands the disconnector closes at equal potential
+        assert!(net.switching_warnings(&[close("component_3")]).is_empty());
+        assert!(net
+            .switching_warnings(&[close("component_3"), close("component_2")])
+            .is_empty());
+        // With one side de-energized there is no potential difference
+        assert!(net
+            .switching_warnings(&[open("component_1"), close("component_2")])
+            .is_empty());
+
+        // Valid sequences return their warnings when validated and executed
+        let net = Network::from_edges(&[
+            ("src1", ComponentType::Line, "a"),
+            ("src2", ComponentType::Line, "b"),
+            ("a", ComponentType::Disconnector, "b"),
+        ])
+        .unwrap();
+        for name in ["src1", "src2"] {
+            net.get_node(name).unwrap().set_source(true);
+        }
+        let ops = [close("component_2")];
+        let warnings = net.validate_switching(&ops).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings, net.switching_warnings(&ops));
+        assert_eq!(net.switch_atomic(&ops).unwrap(), warnings);
+        assert!(net.get_component("component_2").unwrap().conducts());
+    }
+
+    #[test]
+    fn network_can_operate() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "bus"),
+            ("bus", ComponentType::DisconnectorEarth, "feeder"),
+        ])
+        .unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("bus", "es", 0).unwrap();
+        let cb = net.get_component("component_0").unwrap();
+        let close = |name: &str| net.can_operate(&SwitchOp::Close(name.to_string()));
+
+        // Earthing a live bus is interlocked
+        cb.close().unwrap();
+        assert!(!close("es").unwrap());
+        assert!(!close("component_1").unwrap());
+        assert!(!close("component_0").unwrap());
+
+        // The switching paths apply the same checks
+        assert!(matches!(
+            net.switch_atomic(&[SwitchOp::Close("es".to_string())]),
+            Err(NetworkError::Interlock(_))
+        ));
+        assert!(matches!(net.close("es"), Err(NetworkError::Interlock(_))));
+        let mut snapshot = StationSnapshot::default();
+        snapshot
+            .positions
+            .insert("es".to_string(), PositionState::Closed);
+        assert!(matches!(
+            net.apply_snapshot(&snapshot).unwrap_err()[..],
+            [NetworkError::Interlock(_)]
+        ));
+        assert!(!net.get_component("es").unwrap().conducts());
+
+        // Once isolated the earthing switch may close
+        assert!(net
+            .can_operate(&SwitchOp::Open("component_0".to_string()))
+            .unwrap());
+        cb.open().unwrap();
+        assert!(close("es").unwrap());
+        assert!(close("component_1").unwrap());
+        assert!(!net.get_component("es").unwrap().conducts());
+
+        // Integrated earth blocks closing the disconnector
+        net.get_component("component_1")
+            .unwrap()
+            .close_earth()
+            .unwrap();
+        assert!(!close("component_1").unwrap());
+        assert!(matches!(
+            net.switch_atomic(&[SwitchOp::Close("component_1".to_string())]),
+            Err(NetworkError::Interlock(_))
+        ));
+        net.get_component("es").unwrap().lock().unwrap();
+        assert!(!close("es").unwrap());
+
+        assert!(matches!(close("missing"), Err(NetworkError::NotFound(_))));
+        net.create_component::<Line>("line").unwrap();
+        assert!(matches!(close("line"), Err(NetworkError::Unsupported(_))));
+    }
+
+    #[test]
+    fn network_switch_atomic() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "n1"),
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::DisconnectorEarth, "line"),
+        ])
+        .unwrap();
+        let close = |name: &str| SwitchOp::Close(name.to_string());
+        let open = |name: &str| SwitchOp::Open(name.to_string());
+        let states = || net.switchgear_states();
+        let initial = states();
+
+        // Rejected during validation, nothing applied
+        assert!(matches!(
+            net.switch_atomic(&[
+                close("component_0"),
+                close("component_1"),
+                close("component_2")
+            ]),
+            Err(NetworkError::Interlock(_))
+        ));
+        assert_eq!(states(), initial);
+
+        // Rejected while applying, the earlier operations are rolled back
+        let dse = net.get_component("component_2").unwrap();
+        dse.close_earth().unwrap();
+        assert!(net
+            .switch_atomic(&[close("component_0"), close("component_2")])
+            .is_err());
+        assert_eq!(states(), initial);
+        assert!(dse.earth_position().unwrap().borrow().is_closed());
+
+        dse.open_earth().unwrap();
+        net.switch_atomic(&[
+            close("component_0"),
+            close("component_2"),
+            close("component_1"),
+        ])
+        .unwrap();
+        assert!(states().iter().all(|(_, s)| *s == PositionState::Closed));
+        net.switch_atomic(&[open("component_1")]).unwrap();
+        assert!(!net.get_component("component_1").unwrap().conducts());
+
+        net.get_component("component_1").unwrap().lock().unwrap();
+        assert!(matches!(
+            net.switch_atomic(&[close("component_1")]),
+            Err(NetworkError::Locked(_))
+        ));
+    }
+
+    #[test]
+    fn network_replace_component() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "n1"),
+            ("n1", ComponentType::Disconnector, "line"),
+        ])
+        .unwrap();
+        let ds = net.get_component("component_0").unwrap();
+        ds.set_meta("bay", "A1");
+        ds.close().unwrap();
+        let id = ds.id();
+
+        net.replace_component("component_0", ComponentType::CircuitBreaker)
+            .unwrap();
+        let cb = net.get_component("component_0").unwrap();
+        assert_eq!(cb.r#type(), ComponentType::CircuitBreaker);
+        assert_eq!(cb.id(), id);
+        assert_eq!(cb.get_meta("bay"), Some("A1".to_string()));
+        assert!(cb.conducts());
+        assert_eq!(cb.terminal_node_name(0).unwrap(), Some("bus".to_string()));
+        assert_eq!(cb.terminal_node_name(1).unwrap(), Some("n1".to_string()));
+        assert_eq!(net.component_count(), 2);
+        for node in ["bus", "n1"] {
+            let children = net.get_node(node).unwrap().components();
+            assert!(children.iter().any(|c| Rc::ptr_eq(c, &cb)));
+            assert!(!children.iter().any(|c| Rc::ptr_eq(c, &ds)));
+        }
+        assert!(!ds.is_terminal_connected(0).unwrap());
+
+        // Every setting both types have is kept
+        let line = net.add_edge("line", ComponentType::Line, "far").unwrap();
+        line.set_rating(Some(800f64)).unwrap();
+        net.replace_component(&line.name(), ComponentType::CurrentTransformer)
+            .unwrap();
+        let ct = net.get_component("component_2").unwrap();
+        assert_eq!(ct.rating().unwrap().get(), Some(800f64));
+
+        // A failing connection leaves the old component in place
+        let a = net.get_node("bus").unwrap();
+        cb.disconnect_terminal(1).unwrap();
+        cb.with_terminal(1, |t| t.connect(a.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(net
+            .replace_component("component_0", ComponentType::Disconnector)
+            .is_err());
+        assert!(Rc::ptr_eq(&net.get_component("component_0").unwrap(), &cb));
+        assert_eq!(cb.terminal_node_name(0).unwrap(), Some("bus".to_string()));
+        assert_eq!(cb.terminal_node_name(1).unwrap(), Some("bus".to_string()));
+        assert!(a.components().iter().any(|c| Rc::ptr_eq(c, &cb)));
+
+        assert!(matches!(
+            net.replace_component("component_1", ComponentType::EarthingSwitch),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert_eq!(
+            net.get_component("component_1").unwrap().r#type(),
+            ComponentType::Disconnector
+        );
+        assert!(net.replace_component("nope", ComponentType::Line).is_err());
+    }
+
+    #[test]
+    fn network_downstream_load() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+        ])
+        .unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        for (c, node, mw) in [
+            ("load_a", "a", 10f64),
+            ("load_b", "b", 20f64),
+            ("load_c", "c", 5f64),
+        ] {
+            net.create_component::<Load>(c).unwrap();
+            net.connect(node, c, 0).unwrap();
+            net.get_component(c)
+                .unwrap()
+                .active_power()
+                .unwrap()
+                .set(mw);
+        }
+        let ds = net.get_component("component_2").unwrap();
+        ds.close().unwrap();
+        assert_eq!(net.downstream_load("component_0"), 35f64);
+        assert_eq!(net.downstream_load("component_1"), 25f64);
+        assert_eq!(net.downstream_load("component_2"), 5f64);
+
+        // Opening a downstream switch removes the loads behind it
+        ds.open().unwrap();
+        assert_eq!(net.downstream_load("component_0"), 30f64);
+        assert_eq!(net.downstream_load("component_2"), 5f64);
+        assert_eq!(net.downstream_load("missing"), 0f64);
+    }
+
+    #[test]
+    fn network_island_sources() {
+        let net = Network::from_edges(&[
+            ("grid", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Line, "c"),
+        ])
+        .unwrap();
+        net.get_node("grid").unwrap().set_source(true);
+        net.create_component::<Generator>("gen").unwrap();
+        net.connect("a", "gen", 0).unwrap();
+        net.get_component("component_0").unwrap().close().unwrap();
+
+        let names = |nodes: &Vec<Rc<Node>>| -> Vec<String> {
+            nodes.iter().map(|n| n.name().clone()).collect()
+        };
+        let sources = net.island_sources();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(names(&sources[&0]), vec!["grid", "a"]);
+        // Nodes b and c form a blackout island
+        assert!(sources[&1].is_empty());
+        assert_eq!(names(&net.islands()[1]), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn network_power_balance() {
+        let net = Network::from_edges(&[
+            ("g1", ComponentType::CircuitBreaker, "l1"),
+            ("g2", ComponentType::CircuitBreaker, "l2"),
+        ])
+        .unwrap();
+        for (c, node, mw) in [("gen1", "g1", 100f64), ("gen2", "g2", 50f64)] {
+            net.create_component::<Generator>(c).unwrap();
+            net.connect(node, c, 0).unwrap();
+            net.get_component(c)
+                .unwrap()
+                .active_power()
+                .unwrap()
+                .set(mw);
+            net.get_node(node).unwrap().set_source(true);
+        }
+        for (c, node, mw) in [("load1", "l1", 100f64), ("load2", "l2", 80f64)] {
+            net.create_component::<Load>(c).unwrap();
+            net.connect(node, c, 0).unwrap();
+            net.get_component(c)
+                .unwrap()
+                .active_power()
+                .unwrap()
+                .set(mw);
+        }
+        net.get_component("component_0").unwrap().close().unwrap();
+        net.get_component("component_1").unwrap().close().unwrap();
+
+        let id = |name: &str| net.get_node(name).unwrap().id().unwrap();
+        let balance = net.power_balance();
+        assert_eq!(balance.len(), 4);
+        assert_eq!(balance[&id("g1")], 100f64);
+        assert_eq!(balance[&id("l1")], -100f64);
+        assert_eq!(balance[&id("l2")], -80f64);
+
+        // The first island is balanced, the second lacks 30 MW
+        assert_eq!(net.islands().len(), 2);
+        assert_eq!(net.unbalanced_islands(), vec![(1, -30f64)]);
+
+        // De-energized nodes are not part of the balance
+        net.get_component("component_1").unwrap().open().unwrap();
+        assert!(!net.power_balance().contains_key(&id("l2")));
+        assert_eq!(net.islands().len(), 3);
+        assert_eq!(net.unbalanced_islands(), vec![(1, 50f64)]);
+    }
+
+    #[test]
+    fn network_from_csv() {
+        let components = "name,type\ncb,CBR\nds,Disconnector\n\nvt,Voltage Transformer\n";
+        let connections =
+            "component,terminal,node\ncb,0,bus\ncb,1,n1\nds, 0, n1\nds,1,line\nvt,0,line";
+        let net = Network::from_csv(components, connections).unwrap();
+        assert_eq!(net.component_count(), 3);
+        assert_eq!(net.node_count(), 3);
+        assert_eq!(
+            net.get_component("ds").unwrap().r#type(),
+            ComponentType::Disconnector
+        );
+        let n1 = net.get_node("n1").unwrap();
+        assert_eq!(n1.components().len(), 2);
+        let cb = net.get_component("cb").unwrap();
+        assert_eq!(cb.terminal_node_name(0).unwrap(), Some("bus".to_string()));
+        assert!(Rc::ptr_eq(
+            &cb.other_node(&n1).unwrap(),
+            &net.get_node("bus").unwrap()
+        ));
+
+        // Without header rows
+        assert!(Network::from_csv("cb,CircuitBreaker", "cb,1,n").is_ok());
+
+        let error = |components: &str, connections: &str| match Network::from_csv(
+            components,
+            connections,
+        ) {
+            Err(NetworkError::Parse(msg)) => msg,
+            _ => panic!("expected a parse error"),
+        };
+        assert!(error("name,type\ncb,CBR\nx,Garbage", "").starts_with("Line 3:"));
+        assert!(error("cb,CBR,extra", "").starts_with("Line 1:"));
+        assert!(error("cb,CBR\ncb,DIS", "").starts_with("Line 2:"));
+        assert!(error("cb,CBR", "cb,0,n\ncb,x,m").starts_with("Line 2:"));
+        assert!(error("cb,CBR", "cb,2,n").starts_with("Line 1:"));
+        assert!(error("cb,CBR", "nope,0,n").starts_with("Line 1:"));
+    }
+
+    #[test]
+    fn network_ids() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        let mut ids: Vec<u32> = net.nodes.borrow().iter().map(|n| n.id().unwrap()).collect();
+        ids.extend(net.components.borrow().iter().map(|c| c.id().unwrap()));
+        let count = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+
+        let cb = net.get_component("component_0").unwrap();
+        let id = cb.id().unwrap();
+        assert!(Rc::ptr_eq(&net.component_by_id(id).unwrap(), &cb));
+        let n2 = net.get_node("n2").unwrap();
+        assert!(Rc::ptr_eq(&net.node_by_id(n2.id().unwrap()).unwrap(), &n2));
+        assert!(net.node_by_id(id).is_err());
+        assert!(cb.duplicate("copy").id().is_none());
+
+        // Ids are not reused after removal
+        net.remove_component("component_0").unwrap();
+        assert!(matches!(
+            net.component_by_id(id),
+            Err(NetworkError::NotFound(_))
+        ));
+        net.create_component::<CircuitBreaker>("component_0")
+            .unwrap();
+        let new_id = net.get_component("component_0").unwrap().id().unwrap();
+        assert!(!ids.contains(&new_id));
+    }
+
+    #[test]
+    fn network_accept() {
+        #[derive(Default)]
+        struct Counter {
+            visits: HashMap<String, usize>,
+            nodes: usize,
+            components: usize,
+        }
+
+        impl NetworkVisitor for Counter {
+            fn visit_node(&mut self, node: &Node) {
+                self.nodes += 1;
+                *self.visits.entry(node.name().clone()).or_default() += 1;
+            }
+
+            fn visit_component(&mut self, component: &dyn Component) {
+                self.components += 1;
+                *self.visits.entry(component.name().clone()).or_default() += 1;
+            }
+        }
+
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        let mut counter = Counter::default();
+        net.accept(&mut counter);
+        assert_eq!(counter.nodes, 3);
+        assert_eq!(counter.components, 3);
+        assert_eq!(counter.visits.len(), 6);
+        assert!(counter.visits.values().all(|v| *v == 1));
+
+        // Visitors only need to implement the callbacks they use
+        struct Nothing;
+        impl NetworkVisitor for Nothing {}
+        net.accept(&mut Nothing);
+    }
+
+    #[test]
+    fn network_isolated_zone() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+            ("c", ComponentType::CircuitBreaker, "d"),
+        ])
+        .unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("b", "es", 0).unwrap();
+        net.create_node("upstream").unwrap();
+        net.create_component::<CircuitBreaker>("upstream_cb")
+            .unwrap();
+        net.connect("upstream", "upstream_cb", 0).unwrap();
+        net.connect("src", "upstream_cb", 1).unwrap();
+        net.get_component("upstream_cb").unwrap().close().unwrap();
+        let zone = |name: &str| -> Vec<String> {
+            let mut names: Vec<String> = net
+                .isolated_zone(name)
+                .iter()
+                .map(|c| c.name().clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Both disconnectors open, the earthing switch sits between them
+        assert_eq!(zone("es"), vec!["component_1", "component_2", "es"]);
+
+        // Closing a disconnector expands the zone up to the next open point
+        net.get_component("component_2").unwrap().close().unwrap();
+        assert_eq!(
+            zone("es"),
+            vec!["component_1", "component_2", "component_3", "es"]
+        );
+
+        // The zone does not extend into the source
+        net.get_component("component_1").unwrap().close().unwrap();
+        net.get_component("component_0").unwrap().close().unwrap();
+        assert_eq!(
+            zone("es"),
+            vec![
+                "component_0",
+                "component_1",
+                "component_2",
+                "component_3",
+                "es"
+            ]
+        );
+        assert_eq!(zone("upstream_cb"), vec!["upstream_cb"]);
+
+        net.get_component("component_2").unwrap().open().unwrap();
+        assert_eq!(
+            zone("es"),
+            vec!["component_0", "component_1", "component_2", "es"]
+        );
+        assert!(net.isolated_zone("nope").is_empty());
+    }
+
+    #[test]
+    fn network_try_borrow() {
+        let net = Network::from_edges(&[("a", ComponentType::CircuitBreaker, "b")]).unwrap();
+        let c = net.get_component("component_0").unwrap();
+        let a = net.get_node("a").unwrap();
+        net.disconnect("a", "component_0").unwrap();
+
+        // A held terminal borrow is reported and leaves the node untouched
+        let guard = c.terminal(0).unwrap().borrow_mut();
+        assert!(matches!(
+            net.try_connect("a", "component_0", 0),
+            Err(NetworkError::Busy(_))
+        ));
+        drop(guard);
+        assert!(a.components().is_empty());
+        net.try_connect("a", "component_0", 0).unwrap();
+        assert_eq!(a.components().len(), 1);
+
+        let guard = c.position().unwrap().borrow();
+        assert!(matches!(
+            net.try_close("component_0"),
+            Err(NetworkError::Busy(_))
+        ));
+        drop(guard);
+        net.try_close("component_0").unwrap();
+        net.try_open("component_0").unwrap();
+        assert!(matches!(
+            net.try_get_node("x"),
+            Err(NetworkError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn network_nearest_source() {
+        let net = Network::from_edges(&[
+            ("src1", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::Line, "c"),
+            ("c", ComponentType::CircuitBreaker, "src2"),
+            ("x", ComponentType::Line, "y"),
+        ])
+        .unwrap();
+        for source in ["src1", "src2"] {
+            net.get_node(source).unwrap().set_source(true);
+        }
+        assert_eq!(net.nearest_source("a"), None);
+
+        let cb1 = net.get_component("component_0").unwrap();
+        let cb2 = net.get_component("component_3").unwrap();
+        cb1.close().unwrap();
+        cb2.close().unwrap();
+        assert_eq!(net.nearest_source("a"), Some(("src1".to_string(), 1)));
+        assert_eq!(net.nearest_source("c"), Some(("src2".to_string(), 1)));
+        assert_eq!(net.nearest_source("b"), Some(("src1".to_string(), 2)));
+        assert_eq!(net.nearest_source("src2"), Some(("src2".to_string(), 0)));
+
+        // Only conducting paths are considered
+        cb2.open().unwrap();
+        assert_eq!(net.nearest_source("c"), Some(("src1".to_string(), 3)));
+        assert_eq!(net.nearest_source("x"), None);
+        assert_eq!(net.nearest_source("missing"), None);
+
+        // Forced de-energized nodes are not passed, forced energized nodes supply like sources
+        net.get_node("b")
+            .unwrap()
+            .set_energization_override(Some(false));
+        assert_eq!(net.nearest_source("c"), None);
+        net.get_node("b")
+            .unwrap()
+            .set_energization_override(Some(true));
+        assert_eq!(net.nearest_source("c"), Some(("b".to_string(), 1)));
+    }
+
+    #[test]
+    fn network_nearest_source_directed() {
+        let net = Network::from_edges(&[
+            ("far", ComponentType::Line, "x"),
+            ("x", ComponentType::Line, "t"),
+        ])
+        .unwrap();
+        net.create_node("near").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("t", "tf", 0).unwrap();
+        net.connect("near", "tf", 1).unwrap();
+        net.get_node("near").unwrap().set_source(true);
+        net.get_node("far").unwrap().set_source(true);
+        assert_eq!(net.nearest_source("t"), Some(("near".to_string(), 1)));
+
+        // A galvanically isolating transformer only feeds from its primary winding on t
+        net.get_component("tf")
+            .unwrap()
+            .policy()
+            .unwrap()
+            .set(EnergizationPolicy::GalvanicIsolation);
+        assert_eq!(net.nearest_source("t"), Some(("far".to_string(), 2)));
+        assert_eq!(net.nearest_source("near"), Some(("near".to_string(), 0)));
+    }
+
+    #[test]
+    fn network_order_from_source() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("a", ComponentType::Line, "c"),
+            ("b", ComponentType::CircuitBreaker, "d"),
+            ("src", ComponentType::Line, "e"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.connect("c", "vt", 0).unwrap();
+        net.create_component::<CircuitBreaker>("elsewhere").unwrap();
+
+        let order: Vec<String> = net
+            .order_from_source("src")
+            .iter()
+            .map(|c| c.name().clone())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                "component_0",
+                "component_4",
+                "component_1",
+                "component_2",
+                "component_3",
+                "vt"
+            ]
+        );
+        assert!(net.order_from_source("missing").is_empty());
+    }
+
+    #[test]
+    fn network_all_paths() {
+        // a - b - d and a - c - d, with a direct a - d link and an open cross link b - c
+        let net = Network::from_edges(&[
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::Line, "d"),
+            ("a", ComponentType::Line, "c"),
+            ("c", ComponentType::Line, "d"),
+            ("a", ComponentType::Line, "d"),
+            ("b", ComponentType::Disconnector, "c"),
+        ])
+        .unwrap();
+        let names = |paths: Vec<Vec<Rc<dyn Component>>>| -> Vec<Vec<String>> {
+            paths
+                .iter()
+                .map(|p| p.iter().map(|c| c.name().clone()).collect())
+                .collect()
+        };
+
+        assert_eq!(
+            names(net.all_paths("a", "d", 2)),
+            vec![
+                vec!["component_0", "component_1"],
+                vec!["component_2", "component_3"],
+                vec!["component_4"],
+            ]
+        );
+        assert_eq!(names(net.all_paths("a", "d", 1)), vec![vec!["component_4"]]);
+
+        // Closing the cross link adds the paths through it
+        net.get_component("component_5").unwrap().close().unwrap();
+        assert_eq!(net.all_paths("a", "d", 2).len(), 3);
+        assert_eq!(
+            names(net.all_paths("a", "d", 3)),
+            vec![
+                vec!["component_0", "component_1"],
+                vec!["component_0", "component_5", "component_3"],
+                vec!["component_2", "component_3"],
+                vec!["component_2", "component_5", "component_1"],
+                vec!["component_4"],
+            ]
+        );
+        assert_eq!(net.all_paths("a", "a", 3).len(), 1);
+        assert!(net.all_paths("a", "missing", 3).is_empty());
+    }
+
+    #[test]
+    fn network_shortest_impedance_path() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::Line, "d"),
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+            ("c", ComponentType::Line, "d"),
+        ])
+        .unwrap();
+        net.create_node("island").unwrap();
+        for (line, z) in [
+            ("component_0", (6f64, 8f64)),
+            ("component_1", (1f64, 2f64)),
+            ("component_3", (2f64, 1f64)),
+        ] {
+            net.get_component(line)
+                .unwrap()
+                .set_impedance(Some(z))
+                .unwrap();
+        }
+        let cb = net.get_component("component_2").unwrap();
+        cb.close().unwrap();
+        let names = |path: &[Rc<dyn Component>]| -> Vec<String> {
+            path.iter().map(|c| c.name().clone()).collect()
+        };
+
+        // The direct line has 10 ohm, the three-hop route 2 * sqrt(5)
+        let (path, z) = net.shortest_impedance_path("a", "d").unwrap();
+        assert_eq!(
+            names(&path),
+            vec!["component_1", "component_2", "component_3"]
+        );
+        assert!((z - 2f64 * 5f64.sqrt()).abs() < 1e-9);
+
+        cb.open().unwrap();
+        let (path, z) = net.shortest_impedance_path("a", "d").unwrap();
+        assert_eq!(names(&path), vec!["component_0"]);
+        assert_eq!(z, 10f64);
+        assert!(net.shortest_impedance_path("a", "island").is_none());
+        assert!(net.shortest_impedance_path("a", "missing").is_none());
+
+        // The impedance is saved with the network
+        let loaded = Network::from_json(&net.to_json()).unwrap();
+        assert_eq!(
+            loaded
+                .get_component("component_0")
+                .unwrap()
+                .impedance()
+                .unwrap()
+                .get(),
+            Some((6f64, 8f64))
+        );
+    }
+
+    #[test]
+    fn network_fault_contributions() {
+        // Grid infeed at one end, a generator at the other, the fault on the bus in between
+        let net = Network::from_edges(&[
+            ("grid", ComponentType::CircuitBreaker, "bus"),
+            ("bus", ComponentType::Line, "gen_bus"),
+            ("bus", ComponentType::CircuitBreaker, "spur"),
+            ("other", ComponentType::CircuitBreaker, "spur"),
+        ])
+        .unwrap();
+        net.get_node("grid").unwrap().set_source(true);
+        net.get_node("other").unwrap().set_source(true);
+        net.create_component::<Generator>("gen").unwrap();
+        net.connect("gen_bus", "gen", 0).unwrap();
+        net.get_component("component_0").unwrap().close().unwrap();
+        net.get_component("component_3").unwrap().close().unwrap();
+
+        let contributions: Vec<(String, Vec<String>)> = net
+            .fault_contributions("bus")
+            .into_iter()
+            .map(|(source, path)| (source, path.iter().map(|c| c.name().clone()).collect()))
+            .collect();
+        // The source behind the open breaker does not contribute
+        assert_eq!(
+            contributions,
+            vec![
+                ("grid".to_string(), vec!["component_0".to_string()]),
+                ("gen_bus".to_string(), vec!["component_1".to_string()]),
+            ]
+        );
+        assert_eq!(net.fault_contributions("grid")[0].1.len(), 0);
+        assert!(net.fault_contributions("missing").is_empty());
+    }
+
+    #[test]
+    fn network_switches_between() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::CircuitBreaker, "c"),
+            ("c", ComponentType::CircuitBreaker, "d"),
+            ("d", ComponentType::Line, "e"),
+            ("e", ComponentType::CircuitBreaker, "f"),
+            ("f", ComponentType::Line, "g"),
+            ("x", ComponentType::Line, "y"),
+        ])
+        .unwrap();
+        assert_eq!(net.switches_between("component_0", "component_3"), Some(2));
+        assert_eq!(net.switches_between("component_3", "component_0"), Some(2));
+        assert_eq!(net.switches_between("component_0", "component_5"), Some(3));
+        assert_eq!(net.switches_between("component_1", "component_2"), Some(0));
+        assert_eq!(net.switches_between("component_0", "component_0"), Some(0));
+        assert_eq!(net.switches_between("component_0", "component_6"), None);
+        assert_eq!(net.switches_between("component_0", "missing"), None);
+
+        // The path crossing the fewest switches is taken
+        net.create_component::<Line>("bypass").unwrap();
+        net.connect("b", "bypass", 0).unwrap();
+        net.connect("e", "bypass", 1).unwrap();
+        assert_eq!(net.switches_between("component_0", "component_5"), Some(1));
+    }
+
+    #[test]
+    fn network_orphan_nodes() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+        ])
+        .unwrap();
+        assert!(net.orphan_nodes().is_empty());
+        net.create_node("d").unwrap();
+        net.disconnect("c", "component_1").unwrap();
+        let names = |nodes: Vec<Rc<Node>>| -> Vec<String> {
+            nodes.iter().map(|n| n.name().clone()).collect()
+        };
+        assert_eq!(names(net.orphan_nodes()), vec!["c", "d"]);
+
+        assert_eq!(net.prune_orphans(), vec!["c", "d"]);
+        assert!(net.orphan_nodes().is_empty());
+        assert_eq!(net.node_count(), 2);
+        assert!(net.get_node("c").is_err());
+        assert!(net.prune_orphans().is_empty());
+    }
+
+    #[test]
+    fn network_energization_walk() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("b", ComponentType::Line, "c"),
+            ("src", ComponentType::Disconnector, "d"),
+        ])
+        .unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("a", "tf", 0).unwrap();
+        net.connect("b", "tf", 1).unwrap();
+        net.get_component("component_0").unwrap().close().unwrap();
+        let names = |walk: &mut dyn Iterator<Item = WalkItem>| -> Vec<String> {
+            walk.map(|item| match item {
+                WalkItem::Node(n) => n.name().clone(),
+                WalkItem::Component(c) => c.name().clone(),
+            })
+            .collect()
+        };
+        assert_eq!(
+            names(&mut net.energization_walk("src").unwrap()),
+            vec!["src", "component_0", "a", "tf", "b", "component_1", "c"]
+        );
+
+        // The walk can be stopped at the first transformer
+        let mut walk = net
+            .energization_walk("src")
+            .unwrap()
+            .take_while(|item| match item {
+                WalkItem::Component(c) => c.r#type() != ComponentType::Transformer,
+                WalkItem::Node(_) => true,
+            });
+        assert_eq!(names(&mut walk), vec!["src", "component_0", "a"]);
+
+        // Open switchgear stops the walk
+        net.get_component("component_0").unwrap().open().unwrap();
+        assert_eq!(
+            names(&mut net.energization_walk("src").unwrap()),
+            vec!["src"]
+        );
+        assert!(net.energization_walk("x").is_err());
+    }
+
+    #[test]
+    fn network_add_bay() {
+        let mut bay = Bay::new("feeder");
+        bay.add_terminal("bus").unwrap();
+        bay.add_terminal("line").unwrap();
+        bay.add_node("a").unwrap();
+        bay.add_node("b").unwrap();
+        bay.add_component("ds", ComponentType::Disconnector, &["bus", "a"])
+            .unwrap();
+        bay.add_component("cb", ComponentType::CircuitBreaker, &["a", "b"])
+            .unwrap();
+        bay.add_component("ct", ComponentType::CurrentTransformer, &["b", "line"])
+            .unwrap();
+        assert!(matches!(
+            bay.add_node("cb"),
+            Err(NetworkError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            bay.add_component("vt", ComponentType::VoltageTransformer, &["c"]),
+            Err(NetworkError::NotFound(_))
+        ));
+        assert!(matches!(
+            bay.add_component("vt", ComponentType::VoltageTransformer, &["a", "b"]),
+            Err(NetworkError::Unsupported(_))
+        ));
+
+        let net = Network::new("station");
+        for node in ["bus", "line_1", "line_2"] {
+            net.create_node(node).unwrap();
+        }
+        net.add_bay(&bay, "bay1", &[("bus", "bus"), ("line", "line_1")])
+            .unwrap();
+        net.add_bay(&bay, "bay2", &[("bus", "bus"), ("line", "line_2")])
+            .unwrap();
+        assert_eq!(net.node_count(), 7);
+        assert_eq!(net.component_count(), 6);
+        assert_eq!(net.get_node("bus").unwrap().degree(), 2);
+        let node_of = |c: &str, i: usize| {
+            net.get_component(c)
+                .unwrap()
+                .terminal_node_name(i)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(node_of("bay1_ds", 1), "bay1_a");
+        assert_eq!(node_of("bay2_cb", 0), "bay2_a");
+        assert_eq!(node_of("bay1_ct", 1), "line_1");
+        assert_eq!(node_of("bay2_ct", 1), "line_2");
+
+        // Instances are independent
+        net.get_component("bay1_cb").unwrap().close().unwrap();
+        assert!(!net.get_component("bay2_cb").unwrap().conducts());
+
+        // Failed instantiations add nothing
+        assert!(matches!(
+            net.add_bay(&bay, "bay1", &[("bus", "bus"), ("line", "line_1")]),
+            Err(NetworkError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            net.add_bay(&bay, "bay3", &[("bus", "bus")]),
+            Err(NetworkError::NotFound(_))
+        ));
+        assert!(net
+            .add_bay(&bay, "bay3", &[("bus", "bus"), ("line", "line_3")])
+            .is_err());
+        assert_eq!(net.node_count(), 7);
+        assert_eq!(net.component_count(), 6);
+
+        // A connection failing halfway through removes what was already added
+        let mut tie = Bay::new("tie");
+        tie.add_terminal("x").unwrap();
+        tie.add_terminal("y").unwrap();
+        tie.add_node("m").unwrap();
+        tie.add_component("ds", ComponentType::Disconnector, &["x", "m"])
+            .unwrap();
+        tie.add_component("js", ComponentType::Jumper, &["x", "y"])
+            .unwrap();
+        assert!(net
+            .add_bay(&tie, "tie1", &[("x", "bus"), ("y", "bus")])
+            .is_err());
+        assert_eq!(net.node_count(), 7);
+        assert_eq!(net.component_count(), 6);
+        assert_eq!(net.get_node("bus").unwrap().degree(), 2);
+        assert!(net.get_component("tie1_ds").is_err());
+    }
+
+    #[test]
+    fn network_topology_hash() {
+        let a = Network::from_edges(&[
+            ("bus", ComponentType::CircuitBreaker, "feeder"),
+            ("feeder", ComponentType::Disconnector, "line"),
+        ])
+        .unwrap();
+        let b = Network::new("other");
+        for node in ["line", "feeder", "bus"] {
+            b.create_node(node).unwrap();
+        }
+        b.create_component::<Disconnector>("component_1").unwrap();
+        b.create_component::<CircuitBreaker>("component_0").unwrap();
+        b.connect("feeder", "component_1", 0).unwrap();
+        b.connect("line", "component_1", 1).unwrap();
+        b.connect("bus", "component_0", 0).unwrap();
+        b.connect("feeder", "component_0", 1).unwrap();
+        assert_eq!(a.topology_hash(), b.topology_hash());
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        // Switching only changes the state hash
+        let hash = a.topology_hash();
+        a.get_component("component_0").unwrap().close().unwrap();
+        assert_eq!(a.topology_hash(), hash);
+        assert_ne!(a.state_hash(), b.state_hash());
+
+        // Connections and types change the topology hash
+        a.disconnect("line", "component_1").unwrap();
+        assert_ne!(a.topology_hash(), hash);
+        a.connect("line", "component_1", 1).unwrap();
+        assert_eq!(a.topology_hash(), hash);
+        a.replace_component("component_1", ComponentType::Line)
+            .unwrap();
+        assert_ne!(a.topology_hash(), hash);
+
+        // FNV-1a, so hashes can be stored and compared between builds
+        assert_eq!(hash_sorted(vec!["a".to_string()]), 0x089b_e207_b544_f1e4);
+    }
+
+    #[test]
+    fn network_rename() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+        ])
+        .unwrap();
+        let cb = net.get_component("component_0").unwrap();
+        cb.close().unwrap();
+        cb.terminal(1)
+            .unwrap()
+            .borrow_mut()
+            .set_ampacity(Some(630.0));
+        let id = cb.id();
+
+        net.rename("component_0", "cb1").unwrap();
+        assert!(net.get_component("component_0").is_err());
+
+        // Renamed in place, so the id and existing references stay valid
+        assert!(Rc::ptr_eq(&net.get_component("cb1").unwrap(), &cb));
+        assert_eq!(cb.name().as_str(), "cb1");
+        assert_eq!(cb.id(), id);
+        assert!(Rc::ptr_eq(&net.component_by_id(id.unwrap()).unwrap(), &cb));
+        assert!(cb.conducts());
+        assert_eq!(cb.terminal(1).unwrap().borrow().ampacity(), Some(630.0));
+        assert_eq!(cb.terminal_node_name(0).unwrap(), Some("a".to_string()));
+        for node in ["a", "b"] {
+            let names: Vec<String> = net
+                .get_node(node)
+                .unwrap()
+                .components()
+                .iter()
+                .map(|c| c.name().clone())
+                .collect();
+            assert!(names.contains(&"cb1".to_string()));
+            assert!(!names.contains(&"component_0".to_string()));
+        }
+        assert_eq!(net.energized_nodes().len(), 0);
+
+        // Collisions with components and nodes are rejected
+        assert_eq!(
+            net.rename("cb1", "component_1"),
+            Err(NetworkError::DuplicateName("component_1".to_string()))
+        );
+        assert!(matches!(
+            net.rename("cb1", "b"),
+            Err(NetworkError::DuplicateName(_))
+        ));
+        assert!(net.rename("component_0", "cb2").is_err());
+        assert!(net.get_component("cb1").is_ok());
+
+        // Derived measurements keep following their renamed sources
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        let vt = net.get_component("vt").unwrap();
+        let derived = DerivedMeasurement::new(vt.clone(), vt, Unit::Kilovolt, |a, b| a + b);
+        net.rename("vt", "vt1").unwrap();
+        net.get_component("vt1").unwrap().update(10f64).unwrap();
+        assert_eq!(derived.value(), Ok(20f64));
+    }
+
+    #[test]
+    fn network_find_cycles() {
+        let ring = Network::from_edges(&[
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Line, "c"),
+            ("c", ComponentType::CircuitBreaker, "d"),
+            ("d", ComponentType::Line, "a"),
+            ("d", ComponentType::Line, "spur"),
+        ])
+        .unwrap();
+        let cycles = ring.find_cycles();
+        assert_eq!(cycles.len(), 1);
+        let mut names: Vec<String> = cycles[0].iter().map(|n| n.name().clone()).collect();
+        names.sort();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+
+        let radial = Network::from_edges(&[
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Line, "c"),
+            ("b", ComponentType::Line, "d"),
+        ])
+        .unwrap();
+        assert!(radial.find_cycles().is_empty());
+
+        // Parallel lines form a two node ring
+        radial.create_component::<Line>("parallel").unwrap();
+        radial.connect("b", "parallel", 0).unwrap();
+        radial.connect("d", "parallel", 1).unwrap();
+        let cycles = radial.find_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), 2);
+    }
+
+    #[test]
+    fn network_busiest_nodes() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::CircuitBreaker, "bus"),
+            ("b", ComponentType::CircuitBreaker, "bus"),
+            ("c", ComponentType::CircuitBreaker, "bus"),
+            ("c", ComponentType::Disconnector, "d"),
+        ])
+        .unwrap();
+        let degree = |name: &str| net.get_node(name).unwrap().degree();
+        assert_eq!(degree("bus"), 3);
+        assert_eq!(degree("c"), 2);
+        assert_eq!(degree("a"), 1);
+
+        let names = |n: usize| -> Vec<String> {
+            net.busiest_nodes(n)
+                .iter()
+                .map(|n| n.name().clone())
+                .collect()
+        };
+        assert_eq!(names(2), vec!["bus", "c"]);
+        assert_eq!(names(4), vec!["bus", "c", "a", "b"]);
+        assert_eq!(names(10).len(), 5);
+        assert!(names(0).is_empty());
+    }
+
+    #[test]
+    fn network_parallel_groups() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::Line, "b"),
+            ("b", ComponentType::Line, "c"),
+            ("c", ComponentType::Line, "b"),
+        ])
+        .unwrap();
+        let groups: Vec<Vec<String>> = net
+            .parallel_groups()
+            .iter()
+            .map(|g| g.iter().map(|c| c.name().clone()).collect())
+            .collect();
+        assert_eq!(groups, vec![vec!["component_1", "component_2"]]);
+
+        // A single line between two nodes is not parallel to anything
+        let net = Network::from_edges(&[("a", ComponentType::Line, "b")]).unwrap();
+        assert!(net.parallel_groups().is_empty());
+    }
+
+    #[test]
+    fn network_macro() {
+        let net = crate::network! {
+            name: "station",
+            nodes: ["bus", "feeder"],
+            components: [
+                CircuitBreaker("cb1") => ["bus", "feeder"],
+                EarthingSwitch("es1") => ["feeder"],
+                VoltageTransformer("vt1") => ["feeder"],
+            ],
+        }
+        .unwrap();
+
+        let expected = Network::new("station");
+        for node in ["bus", "feeder"] {
+            expected.create_node(node).unwrap();
+        }
+        expected.create_component::<CircuitBreaker>("cb1").unwrap();
+        expected.connect("bus", "cb1", 0).unwrap();
+        expected.connect("feeder", "cb1", 1).unwrap();
+        expected.create_component::<EarthingSwitch>("es1").unwrap();
+        expected.connect("feeder", "es1", 0).unwrap();
+        expected
+            .create_component::<VoltageTransformer>("vt1")
+            .unwrap();
+        expected.connect("feeder", "vt1", 0).unwrap();
+        assert_eq!(net.name(), expected.name());
+        assert_eq!(net.to_json(), expected.to_json());
+
+        // Errors from the underlying calls are returned
+        let duplicate = crate::network! {
+            name: "station",
+            nodes: ["bus"],
+            components: [
+                CircuitBreaker("cb1") => ["bus"],
+                Disconnector("cb1") => ["bus"],
+            ],
+        };
+        assert!(duplicate.is_err());
+    }
+
+    #[test]
+    fn network_energization_override() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+        ])
+        .unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.get_component("component_1").unwrap().close().unwrap();
+        let energized = || -> Vec<String> {
+            net.energized_nodes()
+                .iter()
+                .map(|n| n.name().clone())
+                .collect()
+        };
+        assert_eq!(energized(), vec!["src"]);
+
+        // A dead node forced energized also energizes what it conducts to
+        let a = net.get_node("a").unwrap();
+        a.set_energization_override(Some(true));
+        assert_eq!(energized(), vec!["src", "a", "b"]);
+        a.set_energization_override(None);
+        assert_eq!(energized(), vec!["src"]);
+
+        // A node forced dead blocks energization
+        net.get_component("component_0").unwrap().close().unwrap();
+        a.set_energization_override(Some(false));
+        assert_eq!(energized(), vec!["src"]);
+        a.set_energization_override(None);
+        assert_eq!(energized(), vec!["src", "a", "b"]);
+    }
+
+    #[test]
+    fn network_node_status() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+            ("c", ComponentType::DisconnectorEarth, "d"),
+        ])
+        .unwrap();
+        net.create_node("e").unwrap();
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("b", "es", 0).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.get_component("component_0").unwrap().close().unwrap();
+        net.get_component("es").unwrap().close().unwrap();
+        net.get_component("component_3")
+            .unwrap()
+            .close_earth()
+            .unwrap();
+        let ds = net.get_component("component_2").unwrap();
+        ds.position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(2f64));
+        ds.position()
+            .unwrap()
+            .borrow_mut()
+            .begin_close(0f64)
+            .unwrap();
+
+        let status = |name: &str| {
+            let id = net.get_node(name).unwrap().id().unwrap();
+            net.node_status()[&id]
+        };
+        assert_eq!(status("src"), EnergyStatus::Energized);
+        assert_eq!(status("a"), EnergyStatus::Energized);
+        assert_eq!(status("b"), EnergyStatus::Earthed);
+        assert_eq!(status("c"), EnergyStatus::Unknown);
+        assert_eq!(status("d"), EnergyStatus::Earthed);
+        assert_eq!(status("e"), EnergyStatus::Dead);
+        assert_eq!(net.node_status().len(), 6);
+
+        net.advance_time(2f64);
+        assert_eq!(status("c"), EnergyStatus::Earthed);
+        net.get_component("component_3")
+            .unwrap()
+            .open_earth()
+            .unwrap();
+        net.get_component("es").unwrap().open().unwrap();
+        assert_eq!(status("d"), EnergyStatus::Dead);
+        net.get_component("component_1").unwrap().close().unwrap();
+        assert_eq!(status("c"), EnergyStatus::Energized);
+    }
+
+    #[test]
+    fn network_grounded_energized_nodes() {
+        let net = Network::from_edges(&[("src", ComponentType::CircuitBreaker, "n1")]).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("n1", "es", 0).unwrap();
+        let es = net.get_component("es").unwrap();
+        let cb = net.get_component("component_0").unwrap();
+
+        // Earthing a dead node is fine
+        es.close().unwrap();
+        assert!(net.grounded_energized_nodes().is_empty());
+
+        // Energizing the earthed node is a fault
+        cb.close().unwrap();
+        let grounded = net.grounded_energized_nodes();
+        assert_eq!(grounded.len(), 1);
+        assert_eq!(grounded[0].name().as_str(), "n1");
+
+        es.open().unwrap();
+        assert!(net.grounded_energized_nodes().is_empty());
+
+        // Integrated earthing switches earth their second terminal
+        net.create_node("n2").unwrap();
+        net.create_component::<DisconnectorEarth>("dse").unwrap();
+        net.connect("n1", "dse", 0).unwrap();
+        net.connect("n2", "dse", 1).unwrap();
+        let dse = net.get_component("dse").unwrap();
+        dse.close_earth().unwrap();
+        assert!(net.grounded_energized_nodes().is_empty());
+        net.get_node("n2").unwrap().set_source(true);
+        let grounded = net.grounded_energized_nodes();
+        assert_eq!(grounded.len(), 1);
+        assert_eq!(grounded[0].name().as_str(), "n2");
+        dse.earth_kind().unwrap().set(EarthKind::MaintenanceEarth);
+        assert!(net.grounded_energized_nodes().is_empty());
+    }
+
+    #[test]
+    fn network_earth_kind() {
+        let net = Network::from_edges(&[("src", ComponentType::CircuitBreaker, "n1")]).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("n1", "es", 0).unwrap();
+        let es = net.get_component("es").unwrap();
+        assert!(es.is_system_earth());
+        es.earth_kind().unwrap().set(EarthKind::MaintenanceEarth);
+        assert!(!es.is_system_earth());
+
+        // A closed maintenance earth on an energized node is not flagged
+        es.close().unwrap();
+        net.get_component("component_0").unwrap().close().unwrap();
+        assert!(net.grounded_energized_nodes().is_empty());
+        es.earth_kind().unwrap().set(EarthKind::SystemEarth);
+        assert_eq!(net.grounded_energized_nodes().len(), 1);
+
+        es.earth_kind().unwrap().set(EarthKind::MaintenanceEarth);
+        let copy = Network::from_json(&net.to_json()).unwrap();
+        let copy_es = copy.get_component("es").unwrap();
+        assert_eq!(
+            copy_es.earth_kind().unwrap().get(),
+            EarthKind::MaintenanceEarth
+        );
+        assert!(net
+            .get_component("component_0")
+            .unwrap()
+            .earth_kind()
+            .is_err());
+    }
+
+    #[test]
+    fn network_begin_close_interlock() {
+        let net = Network::new("net");
+        net.create_component::<DisconnectorEarth>("dse").unwrap();
+        let dse = net.get_component("dse").unwrap();
+        dse.position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(1f64));
+        dse.close_earth().unwrap();
+
+        // A timed close is interlocked like an instantaneous one
+        assert!(matches!(
+            net.begin_close("dse"),
+            Err(NetworkError::Interlock(_))
+        ));
+        assert!(dse.position().unwrap().borrow().is_open());
+        dse.open_earth().unwrap();
+        net.begin_close("dse").unwrap();
+        assert!(dse.position().unwrap().borrow().is_in_transit());
+
+        dse.lock().unwrap();
+        assert!(matches!(
+            net.begin_open("dse"),
+            Err(NetworkError::Locked(_))
+        ));
+    }
+
+    #[test]
+    fn network_advance_time() {
+        let net = Network::new("net");
+        net.create_component::<CircuitBreaker>("cb").unwrap();
         net.create_component::<Disconnector>("ds").unwrap();
-        assert!(net.get_component("ds").unwrap().name() == "ds");
-        assert_eq!(net.component_count(), 2);
+        let cb = net.get_component("cb").unwrap();
+        cb.position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(0.06));
+        net.get_component("ds")
+            .unwrap()
+            .position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(5f64));
 
-        net.remove_component("cb").unwrap();
-        assert!(net.get_component("cb").is_err());
-        assert_eq!(net.component_count(), 1);
-        assert!(net.remove_component("cb").is_err());
+        net.advance_time(1f64);
+        net.begin_close("cb").unwrap();
+        net.begin_close("ds").unwrap();
+        assert!(net.begin_close("cb").is_err());
+        assert!(net.begin_open("nope").is_err());
+        for _ in 0..5 {
+            net.advance_time(0.01);
+            assert_eq!(
+                cb.position().unwrap().borrow().state(),
+                PositionState::Transit
+            );
+        }
+        net.advance_time(0.01);
+        assert!(cb.position().unwrap().borrow().is_closed());
+        assert_eq!(
+            net.switchgear_states()[1],
+            ("ds".to_string(), PositionState::Transit)
+        );
+        net.advance_time(5f64);
+        assert_eq!(
+            net.switchgear_states(),
+            vec![
+                ("cb".to_string(), PositionState::Closed),
+                ("ds".to_string(), PositionState::Closed)
+            ]
+        );
+    }
 
-        net.remove_component("ds").unwrap();
-        assert_eq!(net.component_count(), 0);
+    #[test]
+    fn network_apply_snapshot() {
+        let net = Network::new("station");
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<Disconnector>("ds").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.get_component("ds").unwrap().close().unwrap();
+        let mut snapshot = StationSnapshot::default();
+        for (name, state) in [
+            ("cb", PositionState::Closed),
+            ("ds", PositionState::Closed),
+            ("missing", PositionState::Open),
+            ("vt", PositionState::Open),
+        ] {
+            snapshot.positions.insert(name.to_string(), state);
+        }
+        snapshot.measurements.insert("vt".to_string(), 150.3);
+        snapshot.measurements.insert("gone".to_string(), 1f64);
+
+        let errors = net.apply_snapshot(&snapshot).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], NetworkError::NotFound(_)));
+        assert!(matches!(errors[1], NetworkError::Unsupported(_)));
+        assert!(matches!(errors[2], NetworkError::NotFound(_)));
+
+        // The known elements are still applied
+        assert_eq!(
+            net.switchgear_states(),
+            vec![
+                ("cb".to_string(), PositionState::Closed),
+                ("ds".to_string(), PositionState::Closed)
+            ]
+        );
+        assert_eq!(net.get_component("vt").unwrap().value().unwrap(), 150.3);
+        assert_eq!(net.changelog().len(), 2);
+
+        snapshot.positions.retain(|name, _| name == "cb");
+        snapshot.measurements.retain(|name, _| name == "vt");
+        assert_eq!(net.apply_snapshot(&snapshot), Ok(()));
+
+        // Interlocks apply, earthing switches open before and close after the main contacts
+        net.create_component::<DisconnectorEarth>("dse").unwrap();
+        let dse = net.get_component("dse").unwrap();
+        dse.close_earth().unwrap();
+        let mut snapshot = StationSnapshot::default();
+        snapshot
+            .positions
+            .insert("dse".to_string(), PositionState::Closed);
+        assert!(matches!(
+            net.apply_snapshot(&snapshot).unwrap_err()[..],
+            [NetworkError::Interlock(_)]
+        ));
+        assert!(dse.position().unwrap().borrow().is_open());
+        snapshot
+            .earth_positions
+            .insert("dse".to_string(), PositionState::Open);
+        assert_eq!(net.apply_snapshot(&snapshot), Ok(()));
+        assert!(dse.conducts());
+        assert!(dse.earth_position().unwrap().borrow().is_open());
+
+        let mut snapshot = StationSnapshot::default();
+        snapshot
+            .positions
+            .insert("dse".to_string(), PositionState::Open);
+        snapshot
+            .earth_positions
+            .insert("dse".to_string(), PositionState::Closed);
+        snapshot
+            .earth_positions
+            .insert("cb".to_string(), PositionState::Closed);
+        let errors = net.apply_snapshot(&snapshot).unwrap_err();
+        assert!(matches!(errors[..], [NetworkError::Unsupported(_)]));
+        assert!(dse.earth_position().unwrap().borrow().is_closed());
     }
 
     #[test]
-    fn network_node() {
+    fn network_changelog() {
         let net = Network::new("net");
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<Disconnector>("ds").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        let cb = net.get_component("cb").unwrap();
+        cb.position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(0.06));
 
-        assert_eq!(net.node_count(), 0);
-        assert!(net.get_node("node").is_err());
-        net.create_node("node").unwrap();
-        assert!(net.get_node("node").unwrap().name() == "node");
-        assert!(net.create_component::<CircuitBreaker>("node").is_err());
-        assert!(net.create_node("node").is_err());
-        assert_eq!(net.node_count(), 1);
+        net.switch_atomic(&[SwitchOp::Close("ds".to_string())])
+            .unwrap();
+        net.advance_time(1f64);
+        net.begin_close("cb").unwrap();
+        net.advance_time(0.1);
+        net.apply_updates(&[MeasurementUpdate {
+            component: "vt".to_string(),
+            value: 150f64,
+        }])
+        .unwrap();
+        net.protection_trip("cb").unwrap();
+        // Direct operations and failed operations are not recorded
+        net.get_component("ds").unwrap().open().unwrap();
+        assert!(net.try_close("vt").is_err());
 
-        net.create_node("node2").unwrap();
-        assert!(net.get_node("node2").unwrap().name() == "node2");
-        assert_eq!(net.node_count(), 2);
+        use crate::changelog::ChangeValue::*;
+        let log: Vec<(f64, String, ChangeValue, ChangeValue)> = net
+            .changelog()
+            .iter()
+            .map(|e| (e.time(), e.element().clone(), e.old_value(), e.new_value()))
+            .collect();
+        assert_eq!(
+            log,
+            vec![
+                (
+                    0f64,
+                    "ds".to_string(),
+                    Position(PositionState::Open),
+                    Position(PositionState::Closed)
+                ),
+                (
+                    1f64,
+                    "cb".to_string(),
+                    Position(PositionState::Open),
+                    Position(PositionState::Transit)
+                ),
+                (
+                    1.06,
+                    "cb".to_string(),
+                    Position(PositionState::Transit),
+                    Position(PositionState::Closed)
+                ),
+                (
+                    1.1,
+                    "vt".to_string(),
+                    Measurement(0f64),
+                    Measurement(150f64)
+                ),
+                (
+                    1.1,
+                    "cb".to_string(),
+                    Position(PositionState::Closed),
+                    Position(PositionState::Open)
+                ),
+            ]
+        );
+        assert_eq!(net.changelog()[0].to_string(), "0: ds Open -> Closed");
 
-        net.remove_node("node").unwrap();
-        assert!(net.get_node("node").is_err());
-        assert!(net.remove_node("node").is_err());
-        assert_eq!(net.node_count(), 1);
+        net.clear_changelog();
+        assert!(net.changelog().is_empty());
 
-        net.remove_node("node2").unwrap();
-        assert_eq!(net.node_count(), 0);
+        // Operations through the network are recorded, including earthing and resets
+        net.create_component::<DisconnectorEarth>("dse").unwrap();
+        net.close_earth("dse").unwrap();
+        net.open_earth("dse").unwrap();
+        net.close("dse").unwrap();
+        net.update("vt", 50f64).unwrap();
+        net.reset("vt").unwrap();
+        net.reset("dse").unwrap();
+        assert!(net.open("vt").is_err());
+        let log: Vec<String> = net.changelog().iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            log,
+            vec![
+                "1.1: dse earth Open -> earth Closed",
+                "1.1: dse earth Closed -> earth Open",
+                "1.1: dse Open -> Closed",
+                "1.1: vt 150 -> 50",
+                "1.1: vt 50 -> 0",
+                "1.1: dse Closed -> Open",
+            ]
+        );
     }
 
     #[test]
-    fn network_connect() {
+    fn network_due_for_maintenance() {
         let net = Network::new("net");
-        net.create_node("node").unwrap();
         net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<Disconnector>("ds").unwrap();
+        let cb = net.get_component("cb").unwrap();
+        for _ in 0..5 {
+            cb.close().unwrap();
+            cb.open().unwrap();
+        }
+        // Failed operations are not counted
+        assert!(cb.open().is_err());
+        net.get_component("ds").unwrap().close().unwrap();
+        assert_eq!(cb.operation_count().unwrap(), 10);
+
+        assert!(net.due_for_maintenance().is_empty());
+        net.set_maintenance_threshold(Some(10));
+        assert_eq!(net.due_for_maintenance(), vec![("cb".to_string(), 10)]);
+        net.set_maintenance_threshold(Some(1));
+        assert_eq!(net.due_for_maintenance().len(), 2);
+
+        let loaded = Network::from_json(&net.to_json()).unwrap();
+        assert_eq!(loaded.maintenance_threshold(), Some(1));
+        assert_eq!(loaded.due_for_maintenance(), net.due_for_maintenance());
+
+        cb.reset_counter().unwrap();
+        assert_eq!(net.due_for_maintenance(), vec![("ds".to_string(), 1)]);
+        assert_eq!(cb.operation_count().unwrap(), 0);
+        net.create_component::<Load>("load").unwrap();
+        assert!(net
+            .get_component("load")
+            .unwrap()
+            .operation_count()
+            .is_err());
+    }
+
+    #[test]
+    fn network_measurements() {
+        let net = Network::new("net");
+        net.create_component::<VoltageTransformer>("vt1").unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<VoltageTransformer>("vt2").unwrap();
+        net.create_component::<VoltageTransformer>("vt3").unwrap();
+        net.get_component("vt1").unwrap().update(150.2).unwrap();
+        net.get_component("vt3").unwrap().update(49.8).unwrap();
 
-        assert!(net.connect("does not", "exist", 0).is_err());
-        assert!(net.disconnect("node", "cb").is_err());
-        assert!(net.connect("node", "cb", 2).is_err());
-        assert!(net.connect("node", "cb", 0).is_ok());
-        assert!(net.connect("node", "cb", 1).is_err());
-        assert!(net.disconnect("node", "cb").is_ok());
-        assert!(net.connect("node", "cb", 1).is_ok());
+        assert_eq!(
+            net.measurements(),
+            vec![
+                ("vt1".to_string(), 150.2, Unit::Kilovolt),
+                ("vt2".to_string(), 0f64, Unit::Kilovolt),
+                ("vt3".to_string(), 49.8, Unit::Kilovolt),
+            ]
+        );
+    }
+
+    #[test]
+    fn network_apply_updates() {
+        let net = Network::new("station");
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.create_component::<CurrentTransformer>("ct").unwrap();
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        let update = |component: &str, value: f64| MeasurementUpdate {
+            component: component.to_string(),
+            value,
+        };
+
+        net.apply_updates(&[update("vt", 150f64), update("ct", 400f64)])
+            .unwrap();
+        net.advance_time(1f64);
+        net.apply_updates(&[
+            update("vt", 151f64),
+            update("ct", 420f64),
+            update("vt", 152f64),
+        ])
+        .unwrap();
+        let vt = net.get_component("vt").unwrap();
+        assert_eq!(vt.value().unwrap(), 152f64);
+        assert_eq!(net.get_component("ct").unwrap().value().unwrap(), 420f64);
+        let history: Vec<(f64, f64)> = vt
+            .measurement()
+            .unwrap()
+            .borrow()
+            .history()
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(
+            history,
+            vec![(0f64, 150f64), (1f64, 151f64), (1f64, 152f64)]
+        );
+
+        // Invalid targets reject the whole batch
+        assert!(matches!(
+            net.apply_updates(&[update("vt", 1f64), update("cb", 1f64)]),
+            Err(NetworkError::Unsupported(_))
+        ));
+        assert!(matches!(
+            net.apply_updates(&[update("missing", 1f64)]),
+            Err(NetworkError::NotFound(_))
+        ));
+        assert_eq!(vt.value().unwrap(), 152f64);
+
+        // Rejected values do not stop the rest of the batch
+        vt.measurement()
+            .unwrap()
+            .borrow_mut()
+            .set_limit_action(crate::measurement::LimitAction::Reject);
+        vt.measurement()
+            .unwrap()
+            .borrow_mut()
+            .set_limits(None, Some(200f64));
+        assert!(net
+            .apply_updates(&[update("vt", 300f64), update("ct", 10f64)])
+            .is_err());
+        assert_eq!(vt.value().unwrap(), 152f64);
+        assert_eq!(net.get_component("ct").unwrap().value().unwrap(), 10f64);
+    }
+
+    #[test]
+    fn network_overloaded() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CurrentTransformer, "n2"),
+            ("n2", ComponentType::Line, "n3"),
+        ])
+        .unwrap();
+        let ct = net.get_component("component_0").unwrap();
+        let line = net.get_component("component_1").unwrap();
+        ct.update(500f64).unwrap();
+        assert!(net.overloaded().is_empty());
+
+        line.set_rating(Some(400f64)).unwrap();
+        assert_eq!(net.overloaded(), vec![("component_1".to_string(), 1.25)]);
+
+        ct.set_rating(Some(250f64)).unwrap();
+        assert_eq!(
+            net.overloaded(),
+            vec![
+                ("component_0".to_string(), 2f64),
+                ("component_1".to_string(), 1.25)
+            ]
+        );
+
+        ct.update(200f64).unwrap();
+        assert!(net.overloaded().is_empty());
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        assert!(net
+            .get_component("cb")
+            .unwrap()
+            .set_rating(Some(1f64))
+            .is_err());
+    }
+
+    #[test]
+    fn network_custom_type() {
+        let net = Network::new("network");
+        let reactor = net.register_custom_type("Series Reactor", 2, true).unwrap();
+        let blocking = net
+            .register_custom_type("Blocking Filter", 2, false)
+            .unwrap();
+        let svc = net
+            .register_custom_type("Static Var Compensator", 1, true)
+            .unwrap();
+        assert_eq!(reactor, ComponentType::Custom(1));
+        assert_eq!(net.parse_type("Series Reactor").unwrap(), reactor);
+        assert_eq!(net.terminal_count(svc), 1);
+        assert_eq!(net.custom_type(blocking).unwrap().name(), "Blocking Filter");
+        assert!(net.custom_type(ComponentType::Line).is_none());
+        assert!(!net.instantiate(blocking, "bf").conducts());
+
+        // Names are unique among built-in and registered types, and types need terminals
+        for name in ["Series Reactor", "LIN", "Line", "Custom"] {
+            assert!(matches!(
+                net.register_custom_type(name, 2, true),
+                Err(NetworkError::DuplicateName(_))
+            ));
+        }
+        assert!(matches!(
+            net.register_custom_type("Nothing", 0, true),
+            Err(NetworkError::Unsupported(_))
+        ));
+
+        // Each network has its own registry
+        let other = Network::new("other");
+        assert!(other.parse_type("Series Reactor").is_err());
+        assert!("Series Reactor".parse::<ComponentType>().is_err());
+
+        net.add_edge("src", reactor, "a").unwrap();
+        net.add_edge("a", blocking, "b").unwrap();
+        assert!(net.add_edge("b", svc, "c").is_err());
+        net.create_component::<CustomComponent>("generic").unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        assert!(net.validate_structure().is_empty());
+
+        let loaded = Network::from_json(&net.to_json()).unwrap();
+        assert_eq!(loaded.to_json(), net.to_json());
+        let c = loaded.get_component("component_0").unwrap();
+        assert_eq!(c.r#type(), reactor);
+        assert_eq!(c.type_name(), "Series Reactor");
+        assert_eq!(loaded.components_of_type(blocking).len(), 1);
+        assert_eq!(
+            loaded.get_component("generic").unwrap().r#type(),
+            ComponentType::Custom(0)
+        );
+        assert!(loaded.structurally_eq(&net));
+        let names: Vec<String> = loaded
+            .energized_nodes()
+            .iter()
+            .map(|n| n.name().clone())
+            .collect();
+        assert_eq!(names, vec!["src", "a"]);
+    }
+
+    #[test]
+    fn network_structurally_eq() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::DisconnectorEarth, "a"),
+            ("a", ComponentType::CircuitBreaker, "b"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.connect("b", "vt", 0).unwrap();
+        net.get_component("vt").unwrap().update(150f64).unwrap();
+        let copy = Network::from_json(&net.to_json()).unwrap();
+        assert!(net.structurally_eq(&copy));
+        assert!(copy.structurally_eq(&net));
+
+        copy.get_component("component_1").unwrap().close().unwrap();
+        assert!(!net.structurally_eq(&copy));
+        copy.get_component("component_1").unwrap().open().unwrap();
+        copy.get_component("component_0")
+            .unwrap()
+            .close_earth()
+            .unwrap();
+        assert!(!net.structurally_eq(&copy));
+
+        // Same names but different connections
+        let rewired = Network::from_json(&net.to_json()).unwrap();
+        rewired.disconnect("b", "vt").unwrap();
+        rewired.connect("a", "vt", 0).unwrap();
+        assert!(!net.structurally_eq(&rewired));
+    }
+
+    #[test]
+    fn network_naming_policy() {
+        let net = Network::new("station");
+        let mut policy = NamingPolicy::new("X", 1);
+        policy.set_prefix(ComponentType::CircuitBreaker, "CB");
+        policy.set_prefix(ComponentType::Disconnector, "DS");
+        net.set_naming_policy(policy);
+        net.create_node("CB2").unwrap();
+
+        let names: Vec<String> = [
+            ("a", ComponentType::CircuitBreaker, "b"),
+            ("b", ComponentType::Disconnector, "c"),
+            ("c", ComponentType::CircuitBreaker, "d"),
+            ("d", ComponentType::Line, "e"),
+            ("e", ComponentType::CircuitBreaker, "f"),
+        ]
+        .iter()
+        .map(|(a, t, b)| net.add_edge(a, *t, b).unwrap().name().clone())
+        .collect();
+        // CB2 is taken by a node and skipped
+        assert_eq!(names, vec!["CB1", "DS1", "CB3", "X1", "CB4"]);
+        assert_eq!(net.auto_name(&ComponentType::Disconnector), "DS2");
+
+        // A rejected edge does not use up a name
+        assert!(net
+            .add_edge("f", ComponentType::VoltageTransformer, "g")
+            .is_err());
+        assert_eq!(
+            net.add_edge("f", ComponentType::Disconnector, "g")
+                .unwrap()
+                .name()
+                .as_str(),
+            "DS3"
+        );
+        let mut policy = NamingPolicy::new("X", 1);
+        policy.set_prefix(ComponentType::Disconnector, "DS");
+        let prefixed = Network::from_edges_with(
+            policy.clone(),
+            &[
+                ("a", ComponentType::VoltageTransformer, "b"),
+                ("a", ComponentType::Disconnector, "b"),
+            ],
+        );
+        assert!(matches!(prefixed, Err(NetworkError::Unsupported(_))));
+        let prefixed = Network::from_edges_with(
+            policy.clone(),
+            &[
+                ("a", ComponentType::Disconnector, "b"),
+                ("b", ComponentType::Line, "c"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(prefixed.naming_policy(), policy);
+        assert!(prefixed.get_component("DS1").is_ok());
+        assert!(prefixed.get_component("X1").is_ok());
+
+        // The default policy matches the names from_edges has always used
+        let net = Network::from_edges(&[("a", ComponentType::Line, "b")]).unwrap();
+        assert_eq!(net.naming_policy(), NamingPolicy::default());
+        assert!(net.get_component("component_0").is_ok());
+        assert_eq!(
+            net.add_edge("b", ComponentType::CircuitBreaker, "c")
+                .unwrap()
+                .name()
+                .as_str(),
+            "component_1"
+        );
+    }
+
+    #[test]
+    fn network_save_load() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::DisconnectorEarth, "n1"),
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::CurrentTransformer, "line"),
+        ])
+        .unwrap();
+        net.create_component::<VoltageTransformer>("vt \"1\"")
+            .unwrap();
+        net.connect("line", "vt \"1\"", 0).unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("line", "tf", 0).unwrap();
+        let bus = net.get_node("bus").unwrap();
+        bus.set_source(true);
+        bus.set_voltage_level(Some(150f64));
+        bus.set_meta("station", "Zwolle");
+        bus.set_coordinates(Some((1f64, -2.5)));
+        let cb = net.get_component("component_1").unwrap();
+        cb.close().unwrap();
+        cb.trip().unwrap();
+        cb.set_auto_reclose(Some(AutoReclose::new(3, 0.3))).unwrap();
+        cb.set_meta("asset_id", "CB-1");
+        net.get_component("component_0")
+            .unwrap()
+            .close_earth()
+            .unwrap();
+        net.get_component("component_2")
+            .unwrap()
+            .set_rating(Some(800f64))
+            .unwrap();
+        net.get_component("vt \"1\"")
+            .unwrap()
+            .update(-1f64)
+            .unwrap();
+        net.get_component("tf")
+            .unwrap()
+            .policy()
+            .unwrap()
+            .set(EnergizationPolicy::GalvanicIsolation);
+        net.advance_time(2.5);
+
+        let path = std::env::temp_dir().join(format!("hvnv_save_load_{}.json", std::process::id()));
+        net.save(&path).unwrap();
+        let loaded = Network::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.to_json(), net.to_json());
+        assert_eq!(loaded.time(), 2.5);
+        assert_eq!(loaded.switchgear_states(), net.switchgear_states());
+        let names = |net: &Network| -> Vec<String> {
+            net.energized_nodes()
+                .iter()
+                .map(|n| n.name().clone())
+                .collect()
+        };
+        assert_eq!(names(&loaded), names(&net));
+        let bus = loaded.get_node("bus").unwrap();
+        assert_eq!(bus.get_meta("station"), Some("Zwolle".to_string()));
+        assert_eq!(bus.coordinates(), Some((1f64, -2.5)));
+        let cb = loaded.get_component("component_1").unwrap();
+        assert!(cb.position().unwrap().borrow().command_position_mismatch());
+        assert_eq!(cb.terminal_node_name(0).unwrap(), Some("n1".to_string()));
+        let vt = loaded.get_component("vt \"1\"").unwrap();
+        assert_eq!(vt.measurement().unwrap().borrow().quality(), Quality::Bad);
+        assert_eq!(
+            loaded.get_node("line").unwrap().remove_component(vt),
+            Ok(())
+        );
+
+        assert!(matches!(
+            Network::from_json("{\"name\": \"net\""),
+            Err(NetworkError::Parse(_))
+        ));
+        assert!(matches!(
+            Network::from_json("{\"name\": \"net\", \"nodes\": []}"),
+            Err(NetworkError::Parse(_))
+        ));
+        assert!(matches!(
+            Network::load(std::env::temp_dir().join("hvnv_does_not_exist.json")),
+            Err(NetworkError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn network_json_stable_order() {
+        let build = || {
+            let net = Network::from_edges(&[
+                ("bus", ComponentType::CircuitBreaker, "feeder"),
+                ("feeder", ComponentType::Line, "remote"),
+            ])
+            .unwrap();
+            for (i, key) in ["owner", "bay", "voltage", "vendor", "year", "id"]
+                .iter()
+                .enumerate()
+            {
+                net.get_node("bus").unwrap().set_meta(key, &i.to_string());
+                net.get_component("component_1")
+                    .unwrap()
+                    .set_meta(key, &i.to_string());
+            }
+            net
+        };
+        let net = build();
+        let json = net.to_json();
+        assert_eq!(net.to_json(), json);
+        assert_eq!(build().to_json(), json);
+        assert_eq!(Network::from_json(&json).unwrap().to_json(), json);
+        let bay = json.find("\"bay\"").unwrap();
+        assert!(bay < json.find("\"id\"").unwrap());
+        assert!(json.find("\"owner\"").unwrap() < json.find("\"year\"").unwrap());
+    }
+
+    #[test]
+    fn network_diff() {
+        let net = Network::from_edges(&[
+            ("bus", ComponentType::Disconnector, "n1"),
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+        ])
+        .unwrap();
+        net.get_component("component_1").unwrap().close().unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        let snapshot = Network::from_json(&net.to_json()).unwrap();
+        assert!(snapshot.diff(&net).is_empty());
+
+        net.get_component("component_1").unwrap().open().unwrap();
+        let diff = snapshot.diff(&net);
+        assert_eq!(
+            diff.changed_positions(),
+            &vec![(
+                "component_1".to_string(),
+                PositionState::Closed,
+                PositionState::Open
+            )]
+        );
+        assert!(diff.changed_measurements().is_empty());
+        assert!(diff.added_nodes().is_empty() && diff.removed_nodes().is_empty());
+        assert!(diff.added_components().is_empty() && diff.removed_components().is_empty());
+
+        net.get_component("vt").unwrap().update(150f64).unwrap();
+        net.remove_component("component_0").unwrap();
+        net.create_node("n3").unwrap();
+        let diff = snapshot.diff(&net);
+        assert_eq!(
+            diff.changed_measurements(),
+            &vec![("vt".to_string(), 0f64, 150f64)]
+        );
+        assert_eq!(diff.removed_components(), &vec!["component_0".to_string()]);
+        assert_eq!(diff.added_nodes(), &vec!["n3".to_string()]);
+        assert_eq!(
+            net.diff(&snapshot).added_components(),
+            &vec!["component_0".to_string()]
+        );
+    }
+
+    #[test]
+    fn network_ampacity_violations() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Line, "n3"),
+        ])
+        .unwrap();
+        let line = net.get_component("component_1").unwrap();
+        line.set_rating(Some(1000f64)).unwrap();
+        line.terminal(0)
+            .unwrap()
+            .borrow_mut()
+            .set_ampacity(Some(1250f64));
+        assert!(net.ampacity_violations().is_empty());
+
+        line.terminal(1)
+            .unwrap()
+            .borrow_mut()
+            .set_ampacity(Some(800f64));
+        assert_eq!(
+            net.ampacity_violations(),
+            vec![("component_1".to_string(), 1, 1000f64, 800f64)]
+        );
+
+        // A known measured current takes precedence over the rating
+        net.create_component::<CurrentTransformer>("ct").unwrap();
+        net.connect("n3", "ct", 0).unwrap();
+        net.get_component("ct").unwrap().update(500f64).unwrap();
+        assert!(net.ampacity_violations().is_empty());
+
+        // The breaker has no known current
+        let cb = net.get_component("component_0").unwrap();
+        cb.terminal(0)
+            .unwrap()
+            .borrow_mut()
+            .set_ampacity(Some(1f64));
+        assert!(net.ampacity_violations().is_empty());
+    }
+
+    #[test]
+    fn network_nearest_component() {
+        let net = Network::new("net");
+        assert!(net.nearest_component(0f64, 0f64).is_none());
+        for (name, coordinates) in [
+            ("cb", Some((0f64, 0f64))),
+            ("ds", Some((10f64, 0f64))),
+            ("es", None),
+            ("vt", Some((0f64, 10f64))),
+        ] {
+            net.create_component::<CircuitBreaker>(name).unwrap();
+            net.get_component(name)
+                .unwrap()
+                .set_coordinates(coordinates);
+        }
+
+        let nearest = |x: f64, y: f64| net.nearest_component(x, y).unwrap().name().clone();
+        assert_eq!(nearest(1f64, 1f64), "cb");
+        assert_eq!(nearest(8f64, -3f64), "ds");
+        assert_eq!(nearest(-2f64, 7f64), "vt");
+        assert_eq!(nearest(100f64, 0f64), "ds");
+        // Equidistant points resolve to the component added first
+        assert_eq!(nearest(5f64, 0f64), "cb");
+    }
+
+    #[test]
+    fn network_to_svg() {
+        let net = Network::from_edges(&[
+            ("src", ComponentType::CircuitBreaker, "a"),
+            ("a", ComponentType::Disconnector, "b"),
+            ("b", ComponentType::Line, "c"),
+        ])
+        .unwrap();
+        net.create_component::<Transformer>("tf <1>").unwrap();
+        net.connect("c", "tf <1>", 0).unwrap();
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("b", "es", 0).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.get_node("src")
+            .unwrap()
+            .set_coordinates(Some((-20f64, 300f64)));
+        net.get_component("component_0").unwrap().close().unwrap();
+
+        let svg = net.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_eq!(
+            svg.matches("<g class=\"component\"").count(),
+            net.component_count()
+        );
+        assert_eq!(svg.matches("<g class=\"node\"").count(), net.node_count());
+        assert!(svg.contains("data-name=\"tf &lt;1&gt;\""));
+        assert!(svg.contains("stroke=\"red\""));
+        assert!(svg.contains("<rect"));
+        assert_eq!(svg.matches("<g class=\"ground\"").count(), 1);
+
+        // Every tag is closed in order
+        let mut open: Vec<&str> = vec![];
+        for tag in svg.split('<').skip(1) {
+            let tag = &tag[..tag.find('>').unwrap()];
+            let name = tag
+                .trim_start_matches('/')
+                .split([' ', '/'])
+                .next()
+                .unwrap();
+            if tag.starts_with('/') {
+                assert_eq!(open.pop(), Some(name));
+            } else if !tag.ends_with('/') {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty());
+    }
+
+    #[test]
+    fn network_to_svg_view_box() {
+        let net = Network::from_edges(&[("n1", ComponentType::CircuitBreaker, "n2")]).unwrap();
+        net.get_node("n1")
+            .unwrap()
+            .set_coordinates(Some((500f64, 500f64)));
+        net.get_node("n2")
+            .unwrap()
+            .set_coordinates(Some((600f64, f64::NAN)));
+        net.get_component("component_0")
+            .unwrap()
+            .set_coordinates(Some((f64::INFINITY, 520f64)));
+
+        // The box is fitted to the positions, not to the origin
+        let svg = net.to_svg();
+        assert!(svg.contains("viewBox=\"100 0 450 550\""));
+        assert!(!svg.contains("NaN"));
+        assert!(!svg.contains("inf"));
+    }
+
+    #[test]
+    fn network_layout_json() {
+        let net = Network::from_edges(&[("n1", ComponentType::CircuitBreaker, "n2")]).unwrap();
+        net.get_node("n1")
+            .unwrap()
+            .set_coordinates(Some((0f64, 10f64)));
+        net.get_node("n2")
+            .unwrap()
+            .set_coordinates(Some((0f64, -2.5)));
+        net.get_component("component_0")
+            .unwrap()
+            .set_coordinates(Some((1f64, 4f64)));
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+
+        let json = net.layout_json();
+        assert_eq!(json, net.layout_json());
+        let layout = JsonValue::parse(&json).unwrap();
+        let elements = layout.get("elements").unwrap().as_array().unwrap();
+        let element = |id: &str| {
+            elements
+                .iter()
+                .find(|e| e.get("id").unwrap().as_str() == Some(id))
+                .unwrap()
+        };
+        let fields = |id: &str| {
+            let e = element(id);
+            (
+                e.get("type").unwrap().as_str().unwrap().to_string(),
+                e.get("x").unwrap().as_f64(),
+                e.get("y").unwrap().as_f64(),
+                e.get("state").unwrap().as_str().unwrap().to_string(),
+            )
+        };
+        assert_eq!(
+            fields("n2"),
+            (
+                "Node".to_string(),
+                Some(0f64),
+                Some(-2.5),
+                "n/a".to_string()
+            )
+        );
+        assert_eq!(
+            fields("component_0"),
+            (
+                "CircuitBreaker".to_string(),
+                Some(1f64),
+                Some(4f64),
+                "open".to_string()
+            )
+        );
+        assert_eq!(
+            fields("vt"),
+            (
+                "Voltage Transformer".to_string(),
+                None,
+                None,
+                "n/a".to_string()
+            )
+        );
+        assert!(element("vt").get("x").unwrap().is_null());
+        assert_eq!(
+            layout.get("unpositioned"),
+            Some(&JsonValue::Array(vec!["vt".into()]))
+        );
+
+        // Non-finite coordinates can not be written as json numbers
+        net.get_component("vt")
+            .unwrap()
+            .set_coordinates(Some((f64::NAN, 1f64)));
+        net.get_node("n1")
+            .unwrap()
+            .set_coordinates(Some((f64::INFINITY, 1f64)));
+        let layout = JsonValue::parse(&net.layout_json()).unwrap();
+        assert_eq!(
+            layout.get("unpositioned"),
+            Some(&JsonValue::Array(vec!["n1".into(), "vt".into()]))
+        );
+
+        // Names are escaped by the json writer
+        net.rename("vt", "vt \"a\"").unwrap();
+        let layout = JsonValue::parse(&net.layout_json()).unwrap();
+        assert_eq!(
+            layout.get("unpositioned"),
+            Some(&JsonValue::Array(vec!["n1".into(), "vt \"a\"".into()]))
+        );
+    }
+
+    #[test]
+    fn network_auto_layout() {
+        let net = Network::from_edges(&[
+            ("n1", ComponentType::CircuitBreaker, "n2"),
+            ("n2", ComponentType::Disconnector, "n3"),
+        ])
+        .unwrap();
+        net.create_node("n4").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+
+        net.auto_layout(50);
+        let xy = |name: &str| net.get_node(name).unwrap().coordinates().unwrap();
+        let distance =
+            |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        assert!(distance(xy("n1"), xy("n2")) < distance(xy("n1"), xy("n4")));
+        assert!(distance(xy("n2"), xy("n3")) < distance(xy("n2"), xy("n4")));
+        assert!(net.get_component("vt").unwrap().coordinates().is_some());
+        let layout = JsonValue::parse(&net.layout_json()).unwrap();
+        assert_eq!(layout.get("unpositioned"), Some(&JsonValue::Array(vec![])));
+    }
+
+    #[test]
+    fn network_validate_structure() {
+        let net = Network::new("net");
+        for t in [
+            ComponentType::CircuitBreaker,
+            ComponentType::Disconnector,
+            ComponentType::DisconnectorEarth,
+            ComponentType::EarthingSwitch,
+            ComponentType::VoltageTransformer,
+            ComponentType::Transformer,
+        ] {
+            net.register(t.instantiate(&t.to_string()));
+        }
+        assert!(net.validate_structure().is_empty());
+
+        net.create_component::<MalformedEarthingSwitch>("bad_es")
+            .unwrap();
+        assert_eq!(
+            net.validate_structure(),
+            vec!["Component bad_es of type Earthing Switch has 2 terminals; expected 1"]
+        );
+    }
+
+    #[test]
+    fn network_stubs() {
+        let net = Network::from_edges(&[("src", ComponentType::CircuitBreaker, "a")]).unwrap();
+        net.get_node("src").unwrap().set_source(true);
+        net.get_component("component_0").unwrap().close().unwrap();
+        net.create_component::<Line>("stub").unwrap();
+        net.connect("a", "stub", 0).unwrap();
+        net.create_component::<Line>("spare").unwrap();
+        assert!(net.stubs().iter().map(|c| c.name().clone()).eq(["stub"]));
+
+        // Analyses and exports tolerate the free terminal
+        assert!(net.validate().is_empty());
+        assert_eq!(net.energized_nodes().len(), 2);
+        assert_eq!(net.islands().len(), 1);
+        assert_eq!(net.downstream_load("stub"), 0f64);
+        assert!(net.to_ascii().contains("stub"));
+        net.reduce();
+        let loaded = Network::from_json(&net.to_json()).unwrap();
+        assert_eq!(loaded.stubs().len(), 1);
+
+        net.create_node("b").unwrap();
+        net.connect("b", "stub", 1).unwrap();
+        assert!(net.stubs().is_empty());
+    }
+
+    #[test]
+    fn network_validate_self_connected() {
+        let net = Network::from_edges(&[("a", ComponentType::CircuitBreaker, "b")]).unwrap();
+        assert!(net.validate().is_empty());
+
+        // connect rejects the short outright, so wire the terminal directly
+        assert!(net.connect("a", "component_0", 1).is_err());
+        let cb = net.get_component("component_0").unwrap();
+        let a = net.get_node("a").unwrap();
+        cb.disconnect_terminal(1).unwrap();
+        cb.with_terminal(1, |t| t.connect(a.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            net.validate(),
+            vec!["Component component_0 has multiple terminals connected to node a"]
+        );
+    }
+
+    #[test]
+    fn network_validate_voltage_consistency() {
+        let net = Network::from_edges(&[
+            ("hv", ComponentType::CircuitBreaker, "hv2"),
+            ("hv2", ComponentType::CircuitBreaker, "mv"),
+            ("mv", ComponentType::Disconnector, "unknown"),
+        ])
+        .unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+        net.connect("hv2", "tf", 0).unwrap();
+        net.connect("mv", "tf", 1).unwrap();
+        for (node, kv) in [("hv", 150f64), ("hv2", 150f64), ("mv", 20f64)] {
+            net.get_node(node).unwrap().set_voltage_level(Some(kv));
+        }
+
+        assert_eq!(
+            net.validate_voltage_consistency(),
+            vec!["Component component_1 of type CircuitBreaker connects voltage levels 150 kV and 20 kV"]
+        );
+    }
+
+    #[test]
+    fn network_close_earth() {
+        let net = Network::new("net");
+        net.create_component::<DisconnectorEarth>("dse").unwrap();
+
+        // Line to earth has to pass through open
+        net.close("dse").unwrap();
+        assert!(matches!(
+            net.close_earth("dse"),
+            Err(NetworkError::Interlock(_))
+        ));
+        net.open("dse").unwrap();
+
+        // A locked component can not be earthed or unearthed
+        let dse = net.get_component("dse").unwrap();
+        dse.lock().unwrap();
+        assert!(matches!(
+            net.close_earth("dse"),
+            Err(NetworkError::Locked(_))
+        ));
+        assert!(dse.earth_position().unwrap().borrow().is_open());
+        dse.unlock().unwrap();
+        net.close_earth("dse").unwrap();
+        dse.lock().unwrap();
+        assert!(matches!(
+            net.open_earth("dse"),
+            Err(NetworkError::Locked(_))
+        ));
+        assert!(dse.earth_position().unwrap().borrow().is_closed());
+
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        assert!(matches!(
+            net.close_earth("cb"),
+            Err(NetworkError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn network_switchgear_states() {
+        let net = Network::new("net");
+        net.create_component::<CircuitBreaker>("cb").unwrap();
+        net.create_component::<Disconnector>("ds").unwrap();
+        net.create_component::<Disconnector>("ds2").unwrap();
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.create_component::<VoltageTransformer>("vt").unwrap();
+        net.create_component::<Transformer>("tf").unwrap();
+
+        net.get_component("cb").unwrap().close().unwrap();
+        net.get_component("es").unwrap().close().unwrap();
+        net.get_component("ds2")
+            .unwrap()
+            .position()
+            .unwrap()
+            .borrow_mut()
+            .set_operating_time(Some(2f64));
+        net.begin_close("ds2").unwrap();
+
+        assert_eq!(
+            net.switchgear_states(),
+            vec![
+                ("cb".to_string(), PositionState::Closed),
+                ("ds".to_string(), PositionState::Open),
+                ("ds2".to_string(), PositionState::Transit),
+                ("es".to_string(), PositionState::Closed),
+            ]
+        );
+    }
+
+    #[test]
+    fn network_ground() {
+        let net = Network::from_edges(&[
+            ("a", ComponentType::DisconnectorEarth, "b"),
+            ("b", ComponentType::Line, "c"),
+        ])
+        .unwrap();
+        net.create_component::<EarthingSwitch>("es_a").unwrap();
+        net.connect("a", "es_a", 0).unwrap();
+        net.create_component::<EarthingSwitch>("es").unwrap();
+        net.connect("c", "es", 0).unwrap();
+
+        let ground = net.ground();
+        assert!(Rc::ptr_eq(&ground, &net.ground()));
+        assert_eq!(ground.components().len(), 3);
+        for c in net.components.borrow().iter() {
+            let attached = ground.components().iter().any(|g| Rc::ptr_eq(g, c));
+            assert_eq!(attached, c.r#type() != ComponentType::Line);
+        }
+
+        // Ground is not a regular node
+        assert_eq!(net.node_count(), 3);
+        assert!(net.get_node("ground").is_err());
+        assert!(net.create_node("ground").is_err());
+        assert!(net.create_component::<Line>("ground").is_err());
+        assert!(net
+            .islands()
+            .iter()
+            .flatten()
+            .all(|n| !Rc::ptr_eq(n, &ground)));
+
+        net.get_component("es").unwrap().close().unwrap();
+        let id = net.get_node("c").unwrap().id().unwrap();
+        assert_eq!(net.node_status()[&id], EnergyStatus::Earthed);
+
+        net.remove_component("es").unwrap();
+        assert_eq!(ground.components().len(), 2);
     }
 }
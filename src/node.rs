@@ -1,21 +1,44 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
 use super::component::*;
+use super::error::NetworkError;
+use super::measurement::Quality;
+use super::terminal::{phases_of, Phase};
+
+/// Integer node id, see [Node::id]
+pub type NodeId = u32;
 
 /// A node, which can be connected to a set of [Component] via their [Terminal].
 pub struct Node {
     name: String,
     children: RefCell<Vec<Rc<dyn Component>>>,
+    phase: Cell<Option<Phase>>,
+    enforce_phasing: Cell<bool>,
+    voltage_level: Cell<Option<f64>>,
+    source: Cell<bool>,
+    energization_override: Cell<Option<bool>>,
+    metadata: RefCell<HashMap<String, String>>,
+    coordinates: Cell<Option<(f64, f64)>>,
+    id: Cell<Option<u32>>,
 }
 
 impl Node {
-    /// Constructor sets the node name
+    /// Constructor sets the node name, defaults to a three-phase node
     pub fn new(name: &str) -> Node {
         Node {
             name: name.to_string(),
             children: RefCell::new(vec![]),
+            phase: Cell::new(None),
+            enforce_phasing: Cell::new(false),
+            voltage_level: Cell::new(None),
+            source: Cell::new(false),
+            energization_override: Cell::new(None),
+            metadata: RefCell::new(HashMap::new()),
+            coordinates: Cell::new(None),
+            id: Cell::new(None),
         }
     }
 
@@ -24,6 +47,126 @@ impl Node {
         &self.name
     }
 
+    /// Return the single phase designation of the node, None for a three-phase node
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase.get()
+    }
+
+    /// Return the node id, assigned when it is added to a network. None if not added to a network.
+    pub fn id(&self) -> Option<NodeId> {
+        self.id.get()
+    }
+
+    /// Set the node id
+    pub(crate) fn set_id(&self, id: NodeId) {
+        self.id.set(Some(id));
+    }
+
+    /// Set the single phase designation of the node, or None for a three-phase node
+    pub fn set_phase(&self, phase: Option<Phase>) {
+        self.phase.set(phase);
+    }
+
+    /// Return the phases carried by the node
+    pub fn phases(&self) -> Vec<Phase> {
+        phases_of(self.phase.get())
+    }
+
+    /// Reject connecting terminals whose phases differ from the node phases
+    pub fn set_enforce_phasing(&self, enforce: bool) {
+        self.enforce_phasing.set(enforce);
+    }
+
+    /// True if terminals have to match the node phases to connect
+    pub fn enforces_phasing(&self) -> bool {
+        self.enforce_phasing.get()
+    }
+
+    /// Set the nominal voltage level in kV, or None if unknown
+    pub fn set_voltage_level(&self, kv: Option<f64>) {
+        self.voltage_level.set(kv);
+    }
+
+    /// Return the nominal voltage level in kV, if set
+    pub fn voltage_level(&self) -> Option<f64> {
+        self.voltage_level.get()
+    }
+
+    /// Mark the node as an energizing source, such as an external grid infeed
+    pub fn set_source(&self, source: bool) {
+        self.source.set(source);
+    }
+
+    /// True if the node is an energizing source
+    pub fn is_source(&self) -> bool {
+        self.source.get()
+    }
+
+    /// Force the node energized or de-energized regardless of the topology, for example in training mode. None restores normal energization.
+    pub fn set_energization_override(&self, state: Option<bool>) {
+        self.energization_override.set(state);
+    }
+
+    /// Return the energization override, if set
+    pub fn energization_override(&self) -> Option<bool> {
+        self.energization_override.get()
+    }
+
+    /// Set a user metadata value
+    pub fn set_meta(&self, key: &str, value: &str) {
+        self.metadata
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Returns the user metadata of the node
+    pub fn metadata(&self) -> &RefCell<HashMap<String, String>> {
+        &self.metadata
+    }
+
+    /// Get a user metadata value, if set
+    pub fn get_meta(&self, key: &str) -> Option<String> {
+        self.metadata.borrow().get(key).cloned()
+    }
+
+    /// Set the (x, y) coordinates of the node, or None if unpositioned
+    pub fn set_coordinates(&self, coordinates: Option<(f64, f64)>) {
+        self.coordinates.set(coordinates);
+    }
+
+    /// Return the (x, y) coordinates of the node, if positioned
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        self.coordinates.get()
+    }
+
+    /// Return the components attached to the node, in the order they were added
+    pub fn components(&self) -> Vec<Rc<dyn Component>> {
+        self.children.borrow().clone()
+    }
+
+    /// Return the average of the good-quality measurements of the voltage transformers attached to the node, or None if there are none
+    pub fn representative_voltage(&self) -> Option<f64> {
+        let values: Vec<f64> = self
+            .children
+            .borrow()
+            .iter()
+            .filter(|c| c.r#type() == ComponentType::VoltageTransformer)
+            .filter_map(|c| c.measurement().ok())
+            .map(|m| m.borrow())
+            .filter(|m| m.quality() == Quality::Good)
+            .map(|m| m.value())
+            .collect();
+        match values.is_empty() {
+            true => None,
+            false => Some(values.iter().sum::<f64>() / values.len() as f64),
+        }
+    }
+
+    /// Return the number of components attached to the node
+    pub fn degree(&self) -> usize {
+        self.children.borrow().len()
+    }
+
     /// Add component to node
     pub fn add_component(&self, c: Rc<dyn Component>) -> Result<(), String> {
         let index = self
@@ -44,6 +187,17 @@ impl Node {
         }
     }
 
+    /// Add a component to the node children like [Node::add_component], but return [NetworkError::Busy] instead of panicking if the children are already borrowed
+    pub fn try_add_component(&self, c: Rc<dyn Component>) -> Result<(), NetworkError> {
+        if self.children.try_borrow_mut().is_err() {
+            return Err(NetworkError::Busy(format!(
+                "Components of node {} are already borrowed",
+                self.name()
+            )));
+        }
+        Ok(self.add_component(c)?)
+    }
+
     /// Remove component from node
     pub fn remove_component(&self, c: Rc<dyn Component>) -> Result<(), String> {
         let index = self
@@ -63,6 +217,18 @@ impl Node {
             )),
         }
     }
+
+    /// Remove the component at the given address from the node children, used by components that only have a reference to themselves
+    pub(crate) fn detach(&self, component: *const ()) {
+        self.children
+            .borrow_mut()
+            .retain(|x| !std::ptr::eq(Rc::as_ptr(x) as *const (), component));
+    }
+
+    /// Remove all components from the node
+    pub(crate) fn clear(&self) {
+        self.children.borrow_mut().clear();
+    }
 }
 
 impl fmt::Display for Node {
@@ -81,6 +247,49 @@ mod tests {
         assert_eq!(n.name(), "node")
     }
 
+    #[test]
+    fn node_voltage_level() {
+        let n = Node::new("node");
+        assert_eq!(n.voltage_level(), None);
+        n.set_voltage_level(Some(150f64));
+        assert_eq!(n.voltage_level(), Some(150f64));
+        n.set_voltage_level(None);
+        assert_eq!(n.voltage_level(), None);
+    }
+
+    #[test]
+    fn node_source() {
+        let n = Node::new("node");
+        assert!(!n.is_source());
+        n.set_source(true);
+        assert!(n.is_source());
+    }
+
+    #[test]
+    fn node_representative_voltage() {
+        let n = Node::new("bus");
+        assert_eq!(n.representative_voltage(), None);
+        let vt1: Rc<dyn Component> = Rc::new(VoltageTransformer::new("vt1"));
+        let vt2: Rc<dyn Component> = Rc::new(VoltageTransformer::new("vt2"));
+        n.add_component(vt1.clone()).unwrap();
+        n.add_component(vt2.clone()).unwrap();
+        vt1.update(150.4).unwrap();
+        vt2.update(-1f64).unwrap();
+
+        // The bad-quality measurement of vt2 is excluded
+        assert_eq!(n.representative_voltage(), Some(150.4));
+        vt2.update(149.6).unwrap();
+        assert_eq!(n.representative_voltage(), Some(150f64));
+    }
+
+    #[test]
+    fn node_metadata() {
+        let n = Node::new("node");
+        assert_eq!(n.get_meta("gis"), None);
+        n.set_meta("gis", "52.37,4.89");
+        assert_eq!(n.get_meta("gis"), Some("52.37,4.89".to_string()));
+    }
+
     #[test]
     fn node_component() {
         let n = Node::new("node");
@@ -88,7 +297,7 @@ mod tests {
         let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
         let ds: Rc<dyn Component> = Rc::new(Disconnector::new("ds"));
 
-        assert!(n.children.borrow().len() == 0);
+        assert!(n.children.borrow().is_empty());
         n.add_component(cb.clone()).unwrap();
         assert!(Rc::ptr_eq(&cb, &n.children.borrow()[0]));
         assert!(n.add_component(cb.clone()).is_err());
@@ -103,6 +312,24 @@ mod tests {
         assert!(Rc::ptr_eq(&ds, &n.children.borrow()[0]));
         assert!(n.children.borrow().len() == 1);
         n.remove_component(ds).unwrap();
-        assert!(n.children.borrow().len() == 0);
+        assert!(n.children.borrow().is_empty());
+    }
+
+    #[test]
+    fn node_components() {
+        let n = Node::new("node");
+        let cb: Rc<dyn Component> = Rc::new(CircuitBreaker::new("cb"));
+        let ds: Rc<dyn Component> = Rc::new(Disconnector::new("ds"));
+        let es: Rc<dyn Component> = Rc::new(EarthingSwitch::new("es"));
+        assert!(n.components().is_empty());
+        n.add_component(ds.clone()).unwrap();
+        n.add_component(cb.clone()).unwrap();
+        n.add_component(es.clone()).unwrap();
+
+        let names: Vec<String> = n.components().iter().map(|c| c.name().clone()).collect();
+        assert_eq!(names, vec!["ds", "cb", "es"]);
+        n.remove_component(cb).unwrap();
+        assert_eq!(n.components().len(), 2);
+        assert!(Rc::ptr_eq(&n.components()[1], &es));
     }
 }
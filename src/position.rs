@@ -1,53 +1,453 @@
-#[derive(PartialEq)]
-enum SwitchPosition {
+use std::fmt;
+
+use super::json::JsonValue;
+
+/// Switchgear position state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionState {
+    /// Open
+    Open,
+    /// Closed
+    Closed,
+    /// Moving between open and closed
+    Transit,
+}
+
+impl fmt::Display for PositionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let statestr = match self {
+            PositionState::Open => "Open",
+            PositionState::Closed => "Closed",
+            PositionState::Transit => "Transit",
+        };
+        write!(f, "{}", statestr)
+    }
+}
+
+impl PositionState {
+    /// Parse the [fmt::Display] string of a position state
+    fn parse(s: &str) -> Result<PositionState, String> {
+        match s {
+            "Open" => Ok(PositionState::Open),
+            "Closed" => Ok(PositionState::Closed),
+            "Transit" => Ok(PositionState::Transit),
+            _ => Err(format!("Invalid json: unknown position state {}", s)),
+        }
+    }
+}
+
+/// State of three-position switchgear, such as a disconnector with integrated earthing switch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThreePositionState {
+    /// Open
     Open,
-    Close,
+    /// Closed to line
+    ClosedLine,
+    /// Closed to earth
+    ClosedEarth,
 }
 
 /// Switchgear Position
+#[derive(Debug, Clone)]
 pub struct SwitchgearPosition {
-    position: SwitchPosition,
+    position: PositionState,
+    normal_state: PositionState,
+    operating_time: Option<f64>,
+    transition: Option<(PositionState, f64)>,
+    command: Option<PositionState>,
+    locked: bool,
+    operations: u32,
 }
 
 impl SwitchgearPosition {
-    /// Constructor
+    /// Constructor, normally open
     pub fn new() -> SwitchgearPosition {
+        SwitchgearPosition::new_open()
+    }
+
+    /// Constructor for normally open switchgear, starts open
+    pub fn new_open() -> SwitchgearPosition {
+        SwitchgearPosition::new_normal(PositionState::Open)
+    }
+
+    /// Constructor for normally closed switchgear, starts closed
+    pub fn new_closed() -> SwitchgearPosition {
+        SwitchgearPosition::new_normal(PositionState::Closed)
+    }
+
+    /// Constructor, starts in the given normal state
+    fn new_normal(normal_state: PositionState) -> SwitchgearPosition {
         SwitchgearPosition {
-            position: SwitchPosition::Open,
+            position: normal_state,
+            normal_state,
+            operating_time: None,
+            transition: None,
+            command: None,
+            locked: false,
+            operations: 0,
         }
     }
 
+    /// Lock the switchgear against operation, for example when racked out or tagged out
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    /// Release the lock
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    /// True if locked against operation
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Set the operating time in seconds used by timed transitions, None to switch instantaneously
+    pub fn set_operating_time(&mut self, operating_time: Option<f64>) {
+        self.operating_time = operating_time;
+    }
+
+    /// Return the operating time in seconds, if set
+    pub fn operating_time(&self) -> Option<f64> {
+        self.operating_time
+    }
+
+    /// True if moving between open and closed
+    pub fn is_in_transit(&self) -> bool {
+        self.position == PositionState::Transit
+    }
+
+    /// Return the simulation time in seconds at which the running transition completes, None if not in transit
+    pub fn completes_at(&self) -> Option<f64> {
+        self.transition.map(|(_, completes_at)| completes_at)
+    }
+
+    /// Start closing at the given time, completes after the operating time. Errors if already closed or in transit.
+    pub fn begin_close(&mut self, now: f64) -> Result<(), String> {
+        if self.is_closed() {
+            return Err("Switchgear already closed".to_string());
+        }
+        self.begin(PositionState::Closed, now)
+    }
+
+    /// Start opening at the given time, completes after the operating time. Errors if already open or in transit.
+    pub fn begin_open(&mut self, now: f64) -> Result<(), String> {
+        if self.is_open() {
+            return Err("Switchgear already open".to_string());
+        }
+        self.begin(PositionState::Open, now)
+    }
+
+    /// Start a transition to the given target position state
+    fn begin(&mut self, target: PositionState, now: f64) -> Result<(), String> {
+        if self.locked {
+            return Err("Switchgear is locked".to_string());
+        }
+        if self.is_in_transit() {
+            return Err("Switchgear already in transit".to_string());
+        }
+        match self.operating_time {
+            Some(t) if t > 0f64 => {
+                self.position = PositionState::Transit;
+                self.transition = Some((target, now + t));
+            }
+            _ => self.position = target,
+        }
+        self.operations += 1;
+        Ok(())
+    }
+
+    /// Complete a running transition if its operating time has elapsed at the given time
+    pub fn settle(&mut self, now: f64) {
+        if let Some((target, completes_at)) = self.transition {
+            if now >= completes_at {
+                self.position = target;
+                self.transition = None;
+            }
+        }
+    }
+
+    /// Return the number of successful open and close operations since construction or the last [SwitchgearPosition::reset_counter]. A timed transition counts when it starts.
+    pub fn operation_count(&self) -> u32 {
+        self.operations
+    }
+
+    /// Reset the operation counter to zero, for example after maintenance
+    pub fn reset_counter(&mut self) {
+        self.operations = 0;
+    }
+
+    /// Return to the normal position, cancelling any running transition and clearing the last command. The lock, operating time and operation counter are kept.
+    pub fn reset(&mut self) {
+        self.position = self.normal_state;
+        self.transition = None;
+        self.command = None;
+    }
+
+    /// Return the normal position state
+    pub fn normal_state(&self) -> PositionState {
+        self.normal_state
+    }
+
+    /// Command the switchgear open, without changing the position
+    pub fn trip(&mut self) {
+        self.command = Some(PositionState::Open);
+    }
+
+    /// Command the switchgear closed, without changing the position
+    pub fn command_close(&mut self) {
+        self.command = Some(PositionState::Closed);
+    }
+
+    /// Return the last commanded position state, if any
+    pub fn command(&self) -> Option<PositionState> {
+        self.command
+    }
+
+    /// True if the position does not match the last command, for example when a trip command failed to open the switchgear. Switchgear in transit is not considered a mismatch.
+    pub fn command_position_mismatch(&self) -> bool {
+        match self.command {
+            Some(command) => !self.is_in_transit() && command != self.position,
+            None => false,
+        }
+    }
+
+    /// True if the current position differs from the normal position
+    pub fn is_abnormal(&self) -> bool {
+        self.position != self.normal_state
+    }
+
+    /// Return the current position state
+    pub fn state(&self) -> PositionState {
+        self.position
+    }
+
     /// True if closed
     pub fn is_closed(&self) -> bool {
-        self.position == SwitchPosition::Close
+        self.position == PositionState::Closed
     }
 
     /// True if open
     pub fn is_open(&self) -> bool {
-        self.position == SwitchPosition::Open
+        self.position == PositionState::Open
     }
 
-    /// Close and error if already closed
+    /// Close and error if already closed or locked
     pub fn close(&mut self) -> Result<(), String> {
-        if self.is_closed() {
+        if self.locked {
+            Err("Switchgear is locked".to_string())
+        } else if self.is_closed() {
             Err("Switchgear already closed".to_string())
         } else {
-            self.position = SwitchPosition::Close;
+            self.position = PositionState::Closed;
+            self.transition = None;
+            self.operations += 1;
             Ok(())
         }
     }
 
-    /// Open and error if already open
+    /// Open and error if already open or locked
     pub fn open(&mut self) -> Result<(), String> {
-        if self.is_open() {
+        if self.locked {
+            Err("Switchgear is locked".to_string())
+        } else if self.is_open() {
             Err("Switchgear already open".to_string())
         } else {
-            self.position = SwitchPosition::Open;
+            self.position = PositionState::Open;
+            self.transition = None;
+            self.operations += 1;
             Ok(())
         }
     }
 }
 
+impl SwitchgearPosition {
+    /// Serialize the full position state, including running transitions and commands
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let transition = match self.transition {
+            Some((target, completes_at)) => JsonValue::Object(vec![
+                ("target".to_string(), target.to_string().into()),
+                ("completes_at".to_string(), completes_at.into()),
+            ]),
+            None => JsonValue::Null,
+        };
+        JsonValue::Object(vec![
+            ("position".to_string(), self.position.to_string().into()),
+            (
+                "normal_state".to_string(),
+                self.normal_state.to_string().into(),
+            ),
+            ("operating_time".to_string(), self.operating_time.into()),
+            ("transition".to_string(), transition),
+            (
+                "command".to_string(),
+                self.command.map(|c| c.to_string()).into(),
+            ),
+            ("locked".to_string(), self.locked.into()),
+            ("operations".to_string(), (self.operations as f64).into()),
+        ])
+    }
+
+    /// Deserialize a position state written by [SwitchgearPosition::to_json]
+    pub(crate) fn from_json(json: &JsonValue) -> Result<SwitchgearPosition, String> {
+        let state = |key: &str| -> Result<PositionState, String> {
+            match json.field(key)?.as_str() {
+                Some(s) => PositionState::parse(s),
+                None => Err(format!("Invalid json: {} is not a string", key)),
+            }
+        };
+        let transition = match json.field("transition")? {
+            JsonValue::Null => None,
+            t => {
+                let target = match t.field("target")?.as_str() {
+                    Some(s) => PositionState::parse(s)?,
+                    None => return Err("Invalid json: target is not a string".to_string()),
+                };
+                match t.field("completes_at")?.as_f64() {
+                    Some(completes_at) => Some((target, completes_at)),
+                    None => return Err("Invalid json: completes_at is not a number".to_string()),
+                }
+            }
+        };
+        Ok(SwitchgearPosition {
+            position: state("position")?,
+            normal_state: state("normal_state")?,
+            operating_time: json.field("operating_time")?.as_f64(),
+            transition,
+            command: match json.field("command")? {
+                JsonValue::Null => None,
+                _ => Some(state("command")?),
+            },
+            locked: json.get("locked").and_then(|l| l.as_bool()) == Some(true),
+            operations: json
+                .get("operations")
+                .and_then(|o| o.as_f64())
+                .unwrap_or(0f64) as u32,
+        })
+    }
+}
+
+impl fmt::Display for SwitchgearPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.state())
+    }
+}
+
+impl Default for SwitchgearPosition {
+    fn default() -> SwitchgearPosition {
+        SwitchgearPosition::new()
+    }
+}
+
+/// Auto-reclose scheme of a circuit breaker; recloses a configured number of times after a dead time following a protection trip, then locks out
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoReclose {
+    attempts: u32,
+    dead_time: f64,
+    remaining: u32,
+    reclose_at: Option<f64>,
+    locked_out: bool,
+}
+
+impl AutoReclose {
+    /// Constructor, sets the number of reclose attempts and the dead time in seconds between trip and reclose
+    pub fn new(attempts: u32, dead_time: f64) -> AutoReclose {
+        AutoReclose {
+            attempts,
+            dead_time,
+            remaining: attempts,
+            reclose_at: None,
+            locked_out: false,
+        }
+    }
+
+    /// Return the configured number of reclose attempts
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Return the dead time in seconds
+    pub fn dead_time(&self) -> f64 {
+        self.dead_time
+    }
+
+    /// Return the number of reclose attempts left
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Return the time at which the next reclose is due, if one is pending
+    pub fn reclose_at(&self) -> Option<f64> {
+        self.reclose_at
+    }
+
+    /// True if all attempts are exhausted and the breaker stays open until reset
+    pub fn is_locked_out(&self) -> bool {
+        self.locked_out
+    }
+
+    /// Register a protection trip at the given time; schedules a reclose if attempts are left, otherwise locks out
+    pub fn on_trip(&mut self, now: f64) {
+        if self.locked_out {
+            return;
+        }
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.reclose_at = Some(now + self.dead_time);
+        } else {
+            self.reclose_at = None;
+            self.locked_out = true;
+        }
+    }
+
+    /// True once if a pending reclose is due at the given time
+    pub fn take_due(&mut self, now: f64) -> bool {
+        match self.reclose_at {
+            Some(at) if now >= at => {
+                self.reclose_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Serialize the scheme settings and state
+    pub(crate) fn to_json(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            ("attempts".to_string(), f64::from(self.attempts).into()),
+            ("dead_time".to_string(), self.dead_time.into()),
+            ("remaining".to_string(), f64::from(self.remaining).into()),
+            ("reclose_at".to_string(), self.reclose_at.into()),
+            ("locked_out".to_string(), self.locked_out.into()),
+        ])
+    }
+
+    /// Deserialize a scheme written by [AutoReclose::to_json]
+    pub(crate) fn from_json(json: &JsonValue) -> Result<AutoReclose, String> {
+        let number = |key: &str| -> Result<f64, String> {
+            match json.field(key)?.as_f64() {
+                Some(n) => Ok(n),
+                None => Err(format!("Invalid json: {} is not a number", key)),
+            }
+        };
+        Ok(AutoReclose {
+            attempts: number("attempts")? as u32,
+            dead_time: number("dead_time")?,
+            remaining: number("remaining")? as u32,
+            reclose_at: json.field("reclose_at")?.as_f64(),
+            locked_out: json.field("locked_out")?.as_bool() == Some(true),
+        })
+    }
+
+    /// Reset the lockout and restore all attempts
+    pub fn reset(&mut self) {
+        self.remaining = self.attempts;
+        self.reclose_at = None;
+        self.locked_out = false;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +467,109 @@ mod tests {
         assert!(pos.is_open());
         assert!(!pos.is_closed());
     }
+
+    #[test]
+    fn switchgear_position_display() {
+        let mut pos = SwitchgearPosition::new();
+        assert_eq!(pos.to_string(), "Open");
+        pos.close().unwrap();
+        assert_eq!(pos.to_string(), "Closed");
+    }
+
+    #[test]
+    fn switchgear_timed_transition() {
+        let mut pos = SwitchgearPosition::new();
+
+        // Without an operating time switching is instantaneous
+        pos.begin_close(0f64).unwrap();
+        assert!(pos.is_closed());
+        pos.begin_open(0f64).unwrap();
+        assert!(pos.is_open());
+
+        pos.set_operating_time(Some(0.1));
+        pos.begin_close(1f64).unwrap();
+        assert_eq!(pos.state(), PositionState::Transit);
+        assert!(!pos.is_open() && !pos.is_closed());
+        assert!(pos.begin_close(1f64).is_err());
+        assert!(pos.begin_open(1f64).is_err());
+        pos.settle(1.05);
+        assert!(pos.is_in_transit());
+        pos.settle(1.1);
+        assert!(pos.is_closed());
+        assert!(pos.begin_close(2f64).is_err());
+    }
+
+    #[test]
+    fn switchgear_command() {
+        let mut pos = SwitchgearPosition::new_closed();
+        assert_eq!(pos.command(), None);
+        assert!(!pos.command_position_mismatch());
+
+        // Failed trip, the breaker stays closed
+        pos.trip();
+        assert_eq!(pos.command(), Some(PositionState::Open));
+        assert!(pos.is_closed());
+        assert!(pos.command_position_mismatch());
+
+        // Successful trip
+        pos.open().unwrap();
+        assert!(!pos.command_position_mismatch());
+
+        // Close command in progress is not a mismatch
+        pos.set_operating_time(Some(0.1));
+        pos.command_close();
+        pos.begin_close(0f64).unwrap();
+        assert!(!pos.command_position_mismatch());
+        pos.settle(0.1);
+        assert!(!pos.command_position_mismatch());
+    }
+
+    #[test]
+    fn switchgear_auto_reclose() {
+        let mut ar = AutoReclose::new(1, 0.5);
+        assert!(!ar.take_due(10f64));
+        ar.on_trip(1f64);
+        assert_eq!(ar.reclose_at(), Some(1.5));
+        assert!(!ar.take_due(1.2));
+        assert!(ar.take_due(1.5));
+        assert!(!ar.take_due(1.6));
+        ar.on_trip(2f64);
+        assert!(ar.is_locked_out());
+        assert_eq!(ar.reclose_at(), None);
+        ar.reset();
+        assert!(!ar.is_locked_out());
+        assert_eq!(ar.remaining(), 1);
+    }
+
+    #[test]
+    fn switchgear_lock() {
+        let mut pos = SwitchgearPosition::new();
+        pos.lock();
+        assert!(pos.is_locked());
+        assert!(pos.close().is_err());
+        assert!(pos.begin_close(0f64).is_err());
+        assert!(pos.is_open());
+        pos.unlock();
+        assert!(pos.close().is_ok());
+    }
+
+    #[test]
+    fn switchgear_normal_state() {
+        let mut pos = SwitchgearPosition::new_open();
+        assert_eq!(pos.normal_state(), PositionState::Open);
+        assert!(!pos.is_abnormal());
+        pos.close().unwrap();
+        assert!(pos.is_abnormal());
+        pos.open().unwrap();
+        assert!(!pos.is_abnormal());
+
+        let mut pos = SwitchgearPosition::new_closed();
+        assert_eq!(pos.normal_state(), PositionState::Closed);
+        assert!(pos.is_closed());
+        assert!(!pos.is_abnormal());
+        pos.open().unwrap();
+        assert!(pos.is_abnormal());
+        pos.close().unwrap();
+        assert!(!pos.is_abnormal());
+    }
 }
@@ -0,0 +1,156 @@
+use super::component::{Component, ComponentType};
+use super::network::EnergyStatus;
+
+/// Half the width of a bus line
+pub(crate) const BUS_HALF_WIDTH: f64 = 30f64;
+
+/// Escape the characters that may not appear literally in xml text or attribute values
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Return the stroke color of an element with the given energization status
+pub(crate) fn color(status: EnergyStatus) -> &'static str {
+    match status {
+        EnergyStatus::Energized => "red",
+        EnergyStatus::Earthed => "green",
+        EnergyStatus::Dead => "gray",
+        EnergyStatus::Unknown => "orange",
+    }
+}
+
+/// Draw a node as a horizontal bus line centered on (x, y)
+pub(crate) fn bus(name: &str, x: f64, y: f64, status: EnergyStatus) -> String {
+    format!(
+        "  <g class=\"node\" data-name=\"{}\">\n    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"4\"/>\n    <text x=\"{}\" y=\"{}\" font-size=\"8\">{}</text>\n  </g>\n",
+        escape(name),
+        x - BUS_HALF_WIDTH,
+        y,
+        x + BUS_HALF_WIDTH,
+        y,
+        color(status),
+        x + BUS_HALF_WIDTH + 4f64,
+        y + 3f64,
+        escape(name)
+    )
+}
+
+/// Draw the ground node as an earth symbol centered on (x, y) with a dashed line to each earthing component position
+pub(crate) fn ground(name: &str, x: f64, y: f64, components: &[(f64, f64)]) -> String {
+    let stroke = color(EnergyStatus::Earthed);
+    let mut out = format!("  <g class=\"ground\" data-name=\"{}\">\n", escape(name));
+    for (cx, cy) in components {
+        out += &format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-dasharray=\"2\"/>\n",
+            cx, cy, x, y, stroke
+        );
+    }
+    for (i, half) in [12f64, 8f64, 4f64].iter().enumerate() {
+        let ly = y + 4f64 * i as f64;
+        out += &format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            x - half,
+            ly,
+            x + half,
+            ly,
+            stroke
+        );
+    }
+    out + "  </g>\n"
+}
+
+/// Draw a component as a group with a line to each connected node position and its single-line symbol centered on (x, y).
+/// Circuit breakers are squares, filled when closed; disconnectors and earthing switches are blades, angled when open; transformers are two circles.
+pub(crate) fn component(
+    c: &dyn Component,
+    (x, y): (f64, f64),
+    nodes: &[(f64, f64)],
+    status: EnergyStatus,
+) -> String {
+    let stroke = color(status);
+    let closed = c.position().is_ok_and(|p| p.borrow().is_closed());
+    let mut out = format!(
+        "  <g class=\"component\" data-name=\"{}\" data-type=\"{}\">\n",
+        escape(&c.name()),
+        escape(&c.type_name())
+    );
+    for (nx, ny) in nodes {
+        out += &format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\n",
+            x, y, nx, ny, stroke
+        );
+    }
+    let blade = |closed: bool| {
+        let (tip_x, tip_y) = match closed {
+            true => (x, y - 10f64),
+            false => (x + 8f64, y - 8f64),
+        };
+        format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            x,
+            y + 10f64,
+            tip_x,
+            tip_y,
+            stroke
+        )
+    };
+    let circle = |cy: f64, r: f64| {
+        format!(
+            "    <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"white\" stroke=\"{}\"/>\n",
+            x, cy, r, stroke
+        )
+    };
+    out += &match c.r#type() {
+        ComponentType::CircuitBreaker => format!(
+            "    <rect x=\"{}\" y=\"{}\" width=\"16\" height=\"16\" fill=\"{}\" stroke=\"{}\"/>\n",
+            x - 8f64,
+            y - 8f64,
+            if closed { stroke } else { "white" },
+            stroke
+        ),
+        ComponentType::Disconnector | ComponentType::DisconnectorEarth => blade(closed),
+        ComponentType::EarthingSwitch => {
+            blade(closed)
+                + &format!(
+                    "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\n",
+                    x - 6f64,
+                    y + 12f64,
+                    x + 6f64,
+                    y + 12f64,
+                    stroke
+                )
+        }
+        ComponentType::Transformer => circle(y - 5f64, 8f64) + &circle(y + 5f64, 8f64),
+        ComponentType::Load => format!(
+            "    <polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>\n",
+            x - 6f64,
+            y - 4f64,
+            x + 6f64,
+            y - 4f64,
+            x,
+            y + 6f64,
+            stroke
+        ),
+        ComponentType::Generator => {
+            circle(y, 10f64)
+                + &format!(
+                "    <text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">G</text>\n",
+                x,
+                y + 4f64
+            )
+        }
+        _ => circle(y, 5f64),
+    };
+    out + "  </g>\n"
+}
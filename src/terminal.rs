@@ -2,19 +2,77 @@ use std::rc::Rc;
 
 use super::node::Node;
 
+/// Single phase designation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    /// Phase A
+    A,
+    /// Phase B
+    B,
+    /// Phase C
+    C,
+}
+
+/// Return the phases carried by an optional single phase designation; no designation means a three-phase bundle
+pub fn phases_of(phase: Option<Phase>) -> Vec<Phase> {
+    match phase {
+        Some(p) => vec![p],
+        None => vec![Phase::A, Phase::B, Phase::C],
+    }
+}
+
 /// Model [Component] terminal, can be connected to a node
 pub struct Terminal {
     node: Option<Rc<Node>>,
+    phase: Option<Phase>,
+    ampacity: Option<f64>,
 }
 
 impl Terminal {
-    /// Constructor
+    /// Constructor, defaults to a three-phase terminal
     pub fn new() -> Terminal {
-        Terminal { node: None }
+        Terminal {
+            node: None,
+            phase: None,
+            ampacity: None,
+        }
+    }
+
+    /// Return the current carrying capacity in A, if set
+    pub fn ampacity(&self) -> Option<f64> {
+        self.ampacity
+    }
+
+    /// Set the current carrying capacity in A, None for unlimited
+    pub fn set_ampacity(&mut self, ampacity: Option<f64>) {
+        self.ampacity = ampacity;
+    }
+
+    /// Return the single phase designation of the terminal, None for a three-phase terminal
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase
+    }
+
+    /// Set the single phase designation of the terminal, or None for a three-phase terminal
+    pub fn set_phase(&mut self, phase: Option<Phase>) {
+        self.phase = phase;
+    }
+
+    /// Return the phases carried by the terminal
+    pub fn phases(&self) -> Vec<Phase> {
+        phases_of(self.phase)
     }
 
-    /// Connect to node if not already connected
+    /// Connect to node if not already connected. If the node enforces phasing the terminal and node phases have to match.
     pub fn connect(&mut self, node: Rc<Node>) -> Result<(), String> {
+        if node.enforces_phasing() && node.phases() != self.phases() {
+            return Err(format!(
+                "Terminal phases {:?} do not match phases {:?} of node {}",
+                self.phases(),
+                node.phases(),
+                node.name()
+            ));
+        }
         match self.node {
             Some(_) => Err("Terminal already connected".to_string()),
             None => {
@@ -44,6 +102,12 @@ impl Terminal {
     }
 }
 
+impl Default for Terminal {
+    fn default() -> Terminal {
+        Terminal::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +127,32 @@ mod tests {
         t.disconnect().unwrap();
         assert!(t.get_node().is_err());
     }
+
+    #[test]
+    fn terminal_phases() {
+        let n = Rc::new(Node::new("node"));
+        let mut t = Terminal::new();
+        assert_eq!(t.phases(), vec![Phase::A, Phase::B, Phase::C]);
+
+        // Without phasing enforcement any terminal may connect
+        t.set_phase(Some(Phase::A));
+        assert_eq!(t.phases(), vec![Phase::A]);
+        t.connect(n.clone()).unwrap();
+        t.disconnect().unwrap();
+
+        // Single phase terminal to three-phase node
+        n.set_enforce_phasing(true);
+        assert!(t.connect(n.clone()).is_err());
+        t.set_phase(None);
+        t.connect(n.clone()).unwrap();
+        t.disconnect().unwrap();
+
+        // Matching and mismatching single phase node
+        n.set_phase(Some(Phase::B));
+        assert!(t.connect(n.clone()).is_err());
+        t.set_phase(Some(Phase::C));
+        assert!(t.connect(n.clone()).is_err());
+        t.set_phase(Some(Phase::B));
+        t.connect(n).unwrap();
+    }
 }
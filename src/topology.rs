@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use super::component::Component;
+use super::node::Node;
+
+/// A bus in a [BusBranchModel]: a group of nodes joined by closed switchgear
+pub struct Bus {
+    nodes: Vec<Rc<Node>>,
+}
+
+impl Bus {
+    /// Return the nodes that make up the bus
+    pub fn nodes(&self) -> &Vec<Rc<Node>> {
+        &self.nodes
+    }
+
+    /// True if the bus contains the node with the given name
+    pub fn contains(&self, node_name: &str) -> bool {
+        self.nodes.iter().any(|n| n.name() == node_name)
+    }
+}
+
+/// A branch in a [BusBranchModel]: a non-switchgear component connecting buses
+pub struct Branch {
+    component: Rc<dyn Component>,
+    buses: Vec<usize>,
+}
+
+impl Branch {
+    /// Return the component that forms the branch
+    pub fn component(&self) -> &Rc<dyn Component> {
+        &self.component
+    }
+
+    /// Return the indices of the buses connected to each connected terminal of the component
+    pub fn buses(&self) -> &Vec<usize> {
+        &self.buses
+    }
+}
+
+/// Reduced bus-branch model of a network, where nodes joined by closed switchgear are collapsed into buses
+pub struct BusBranchModel {
+    buses: Vec<Bus>,
+    branches: Vec<Branch>,
+}
+
+impl BusBranchModel {
+    /// Constructor, groups nodes into buses by the given union-find root of each node. Branches list the indices of their connected nodes.
+    pub(crate) fn new(
+        nodes: &[Rc<Node>],
+        root_of_node: &[usize],
+        branches: Vec<(Rc<dyn Component>, Vec<usize>)>,
+    ) -> BusBranchModel {
+        let mut buses: Vec<Bus> = vec![];
+        let mut bus_of_root: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut bus_index: Vec<usize> = vec![0; nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            let root = root_of_node[i];
+            let index = match bus_of_root[root] {
+                Some(index) => index,
+                None => {
+                    buses.push(Bus { nodes: vec![] });
+                    bus_of_root[root] = Some(buses.len() - 1);
+                    buses.len() - 1
+                }
+            };
+            bus_index[i] = index;
+            buses[index].nodes.push(node.clone());
+        }
+        let branches = branches
+            .into_iter()
+            .map(|(component, node_indices)| Branch {
+                component,
+                buses: node_indices.iter().map(|i| bus_index[*i]).collect(),
+            })
+            .collect();
+        BusBranchModel { buses, branches }
+    }
+
+    /// Return the buses
+    pub fn buses(&self) -> &Vec<Bus> {
+        &self.buses
+    }
+
+    /// Return the branches
+    pub fn branches(&self) -> &Vec<Branch> {
+        &self.branches
+    }
+
+    /// Return the number of buses
+    pub fn bus_count(&self) -> usize {
+        self.buses.len()
+    }
+
+    /// Return the index of the bus containing the node with the given name
+    pub fn bus_of(&self, node_name: &str) -> Option<usize> {
+        self.buses.iter().position(|b| b.contains(node_name))
+    }
+}